@@ -0,0 +1,32 @@
+//! Headless game server: runs the match and streams it to the players and spectators.
+
+use std::env;
+
+use amethyst::{config::Config, utils::application_root_dir};
+use log::info;
+use pong::{
+    cli::parse_server_args,
+    net::GameServer,
+    settings::{ControlsConfig, GameplayConfig, CONTROLS_CONFIG_FILE, GAMEPLAY_CONFIG_FILE},
+    startup::setup_logger,
+};
+
+fn main() -> amethyst::Result<()> {
+    let app_root = application_root_dir()?;
+    let server_options = parse_server_args(env::args().skip(1))?;
+
+    setup_logger();
+
+    // the server simulates the match with the same settings as the game
+    let settings_dir = app_root.join("src").join("settings");
+    let controls_config = ControlsConfig::load(settings_dir.join(CONTROLS_CONFIG_FILE))?;
+    let gameplay_config = GameplayConfig::load(settings_dir.join(GAMEPLAY_CONFIG_FILE))?;
+
+    let mut server = GameServer::bind(server_options.port, controls_config, gameplay_config)?;
+
+    info!("Game server listening on {}", server.local_addr()?);
+
+    server.run();
+
+    Ok(())
+}
@@ -0,0 +1,637 @@
+//! Command line options.
+
+use std::{error::Error, fmt, net::SocketAddr};
+
+use crate::{
+    entities::{Force, Side},
+    net::{NetplayOptions, OnlineOptions, DEFAULT_INPUT_DELAY, DEFAULT_SERVER_PORT},
+    settings::GameMode,
+};
+
+/// Name shown to the other players when none is given.
+pub const DEFAULT_PLAYER_NAME: &str = "PLAYER";
+
+/// Options given on the command line.
+#[derive(Default, PartialEq, Debug)]
+pub struct CliOptions {
+    pub netplay: Option<NetplayOptions>,
+    /// Game server to play on (or to watch).
+    pub online: Option<OnlineOptions>,
+    /// Port spectators connect to, to watch a local or netplay match.
+    pub spectator_port: Option<u16>,
+    /// Name shown to the other players, if given.
+    pub name: Option<String>,
+    /// Start in the LAN lobby.
+    pub lobby: bool,
+    /// Which paddles take part in a local match.
+    pub mode: GameMode,
+    /// Players around the arena of a ring match.
+    pub ring_players: Option<usize>,
+    /// Local match with extra balls.
+    pub multi_ball: bool,
+    /// Local match with power-up pickups.
+    pub power_ups: bool,
+    /// File name (without extension) of the level of a local match.
+    pub level: Option<String>,
+    /// Pick the level of a local match from a menu.
+    pub select_level: bool,
+    /// Set the players' handicaps of a local match from a menu.
+    pub handicaps: bool,
+    /// Practice drills against the ball launcher instead of a match.
+    pub practice: bool,
+    /// Resume the last local match left before its end.
+    pub resume: bool,
+    /// Show the players' statistics instead of starting a match.
+    pub stats: bool,
+    /// Pick the players' profiles of a local match from a menu.
+    pub profiles: bool,
+    /// Name of a profile to create before starting.
+    pub new_profile: Option<String>,
+    /// Show the profiles' ratings instead of starting a match.
+    pub leaderboard: bool,
+    /// Arena physics of a local match.
+    pub forces: Vec<Force>,
+    /// Seed of the random numbers, to play a match again.
+    pub seed: Option<u64>,
+}
+
+/// Options given to the game server on the command line.
+#[derive(PartialEq, Debug)]
+pub struct ServerOptions {
+    pub port: u16,
+}
+
+/// Error returned for unknown or malformed command line arguments.
+#[derive(PartialEq, Debug)]
+pub struct CliError(String);
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid arguments: {}", self.0)
+    }
+}
+
+impl Error for CliError {}
+
+/// Parses the program arguments (without the program name):
+///
+/// `--netplay <local port> <peer address> [--side left|right] [--input-delay <ticks>]`,
+/// `--connect <server address>`, `--spectate <address>` or `--lobby`, with `--name <name>`, plus
+/// `--host-spectators <port>` for local and netplay matches. `--four-player`, `--doubles`,
+/// `--squash`, `--breakout`, `--ring <players>` or `--air-hockey` starts a local match in that
+/// mode, optionally with `--multi-ball` and `--power-ups`. Local matches are played in a level
+/// with `--level <name>`, or one picked with `--select-level`, and `--handicaps` sets the
+/// players' handicaps from a menu first. `--gravity`, `--wind` and `--drag` turn on the arena
+/// physics of local matches. `--practice` picks a training drill instead of starting a match and
+/// `--resume` resumes the last local match left before its end, while `--stats` shows the
+/// statistics of the local matches played so far. `--new-profile <name>` creates a player
+/// profile, `--profiles` picks the players' profiles of a local match from a menu and
+/// `--leaderboard` shows the profiles' ratings. `--seed <seed>` seeds the random numbers of
+/// local and netplay matches.
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<CliOptions, CliError> {
+    let mut args = args.into_iter();
+    let mut netplay_ports = None;
+    let mut server = None;
+    let mut spectate = false;
+    let mut name = None;
+    let mut spectator_port = None;
+    let mut lobby = false;
+    let mut modes = Vec::new(); // classic unless one is picked
+    let mut ring_players = None;
+    let mut multi_ball = false;
+    let mut power_ups = false;
+    let mut level = None;
+    let mut select_level = false;
+    let mut handicaps = false;
+    let mut practice = false;
+    let mut resume = false;
+    let mut stats = false;
+    let mut profiles = false;
+    let mut new_profile = None;
+    let mut leaderboard = false;
+    let mut forces = Vec::new();
+    let mut seed = None;
+    let mut side = Side::Left;
+    let mut input_delay = DEFAULT_INPUT_DELAY;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--netplay" => {
+                let local_port = next_value(&mut args, &arg)?;
+                let peer = next_value::<SocketAddr>(&mut args, &arg)?;
+
+                netplay_ports = Some((local_port, peer));
+            }
+            "--side" => {
+                side = match next_value::<String>(&mut args, &arg)?.as_str() {
+                    "left" => Side::Left,
+                    "right" => Side::Right,
+                    other => return Err(CliError(format!("unknown side {}", other))),
+                }
+            }
+            "--input-delay" => input_delay = next_value(&mut args, &arg)?,
+            "--connect" => server = Some(next_value(&mut args, &arg)?),
+            "--spectate" => {
+                server = Some(next_value(&mut args, &arg)?);
+                spectate = true;
+            }
+            "--name" => name = Some(next_value(&mut args, &arg)?),
+            "--lobby" => lobby = true,
+            "--four-player" => modes.push(GameMode::FourPlayer),
+            "--doubles" => modes.push(GameMode::Doubles),
+            "--squash" => modes.push(GameMode::Squash),
+            "--breakout" => modes.push(GameMode::Breakout),
+            "--air-hockey" => modes.push(GameMode::AirHockey),
+            "--ring" => {
+                let players = next_value(&mut args, &arg)?;
+
+                if !(2..=6).contains(&players) {
+                    return Err(CliError("ring matches are for 2 to 6 players".to_string()));
+                }
+
+                modes.push(GameMode::Ring);
+                ring_players = Some(players);
+            }
+            "--multi-ball" => multi_ball = true,
+            "--power-ups" => power_ups = true,
+            "--level" => level = Some(next_value(&mut args, &arg)?),
+            "--select-level" => select_level = true,
+            "--handicaps" => handicaps = true,
+            "--practice" => practice = true,
+            "--resume" => resume = true,
+            "--stats" => stats = true,
+            "--profiles" => profiles = true,
+            "--new-profile" => new_profile = Some(next_value(&mut args, &arg)?),
+            "--leaderboard" => leaderboard = true,
+            "--gravity" => forces.push(Force::Gravity),
+            "--wind" => forces.push(Force::Wind),
+            "--drag" => forces.push(Force::Drag),
+            "--host-spectators" => spectator_port = Some(next_value(&mut args, &arg)?),
+            "--seed" => seed = Some(next_value(&mut args, &arg)?),
+            other => return Err(CliError(format!("unknown argument {}", other))),
+        }
+    }
+
+    let mode = match modes.as_slice() {
+        [] => GameMode::Classic,
+        [mode] => *mode,
+        _ => {
+            return Err(CliError(
+                "only one of --four-player, --doubles, --squash, --breakout, --ring and \
+                 --air-hockey can be used"
+                    .to_string(),
+            ))
+        }
+    };
+
+    if lobby && (netplay_ports.is_some() || server.is_some()) {
+        return Err(CliError(
+            "--lobby can't be used with --netplay, --connect or --spectate".to_string(),
+        ));
+    }
+
+    // only classic matches are played over the network
+    if (mode != GameMode::Classic
+        || multi_ball
+        || power_ups
+        || level.is_some()
+        || select_level
+        || handicaps
+        || practice
+        || resume
+        || stats
+        || profiles
+        || new_profile.is_some()
+        || leaderboard
+        || !forces.is_empty())
+        && (lobby || netplay_ports.is_some() || server.is_some() || spectator_port.is_some())
+    {
+        return Err(CliError(
+            "--multi-ball, --power-ups, levels, handicaps, profiles, arena physics and non-classic \
+             modes are local only"
+                .to_string(),
+        ));
+    }
+
+    // power-ups are claimed by the sides of the arena's edges and resize straight paddles
+    if (mode == GameMode::Ring || mode == GameMode::AirHockey) && power_ups {
+        return Err(CliError(
+            "--power-ups can't be used in ring or air-hockey matches".to_string(),
+        ));
+    }
+
+    if netplay_ports.is_some() && server.is_some() {
+        return Err(CliError(
+            "--netplay can't be used with --connect or --spectate".to_string(),
+        ));
+    }
+
+    // the game server streams its matches to spectators itself
+    if server.is_some() && spectator_port.is_some() {
+        return Err(CliError(
+            "--host-spectators is only for local and netplay matches".to_string(),
+        ));
+    }
+
+    // the game server draws the random numbers of its matches
+    if server.is_some() && seed.is_some() {
+        return Err(CliError(
+            "--seed is only for local and netplay matches".to_string(),
+        ));
+    }
+
+    let netplay = netplay_ports.map(|(local_port, peer)| NetplayOptions {
+        local_port,
+        peer,
+        local_side: side,
+        input_delay,
+    });
+
+    let online = server.map(|server| OnlineOptions {
+        server,
+        name: name
+            .clone()
+            .unwrap_or_else(|| DEFAULT_PLAYER_NAME.to_string()),
+        spectate,
+    });
+
+    Ok(CliOptions {
+        netplay,
+        online,
+        spectator_port,
+        name,
+        lobby,
+        mode,
+        ring_players,
+        multi_ball,
+        power_ups,
+        level,
+        select_level,
+        handicaps,
+        practice,
+        resume,
+        stats,
+        profiles,
+        new_profile,
+        leaderboard,
+        forces,
+        seed,
+    })
+}
+
+/// Parses the game server's arguments (without the program name): `[--port <port>]`
+pub fn parse_server_args<I: IntoIterator<Item = String>>(
+    args: I,
+) -> Result<ServerOptions, CliError> {
+    let mut args = args.into_iter();
+    let mut port = DEFAULT_SERVER_PORT;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--port" => port = next_value(&mut args, &arg)?,
+            other => return Err(CliError(format!("unknown argument {}", other))),
+        }
+    }
+
+    Ok(ServerOptions { port })
+}
+
+/// Parses the value following an option.
+fn next_value<T: std::str::FromStr>(
+    args: &mut impl Iterator<Item = String>,
+    option: &str,
+) -> Result<T, CliError> {
+    args.next()
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| CliError(format!("missing or invalid value for {}", option)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn should_parse_no_arguments_as_local_match() {
+        // arrange
+        let no_args = args("");
+
+        // act
+        let options = parse_args(no_args);
+
+        // assert
+        assert_eq!(Ok(CliOptions::default()), options);
+    }
+
+    #[test]
+    fn should_parse_netplay_arguments() {
+        // arrange
+        let netplay_args = args("--netplay 7001 127.0.0.1:7000 --side right --input-delay 3");
+
+        // act
+        let options = parse_args(netplay_args).unwrap();
+
+        // assert
+        assert_eq!(
+            Some(NetplayOptions {
+                local_port: 7001,
+                peer: "127.0.0.1:7000".parse().unwrap(),
+                local_side: Side::Right,
+                input_delay: 3,
+            }),
+            options.netplay
+        );
+    }
+
+    #[test]
+    fn should_reject_netplay_without_peer() {
+        // arrange
+        let netplay_args = args("--netplay 7001");
+
+        // act
+        let options = parse_args(netplay_args);
+
+        // assert
+        assert_eq!(true, options.is_err());
+    }
+
+    #[test]
+    fn should_parse_game_server_address() {
+        // arrange
+        let connect_args = args("--connect 127.0.0.1:7777 --name ada");
+
+        // act
+        let options = parse_args(connect_args).unwrap();
+
+        // assert
+        assert_eq!(
+            Some(OnlineOptions {
+                server: "127.0.0.1:7777".parse().unwrap(),
+                name: "ada".to_string(),
+                spectate: false,
+            }),
+            options.online
+        );
+    }
+
+    #[test]
+    fn should_parse_spectator_arguments() {
+        // arrange
+        let spectate_args = args("--spectate 127.0.0.1:7777");
+
+        // act
+        let options = parse_args(spectate_args).unwrap();
+
+        // assert
+        assert_eq!(true, options.online.unwrap().spectate);
+    }
+
+    #[test]
+    fn should_reject_hosting_spectators_on_game_server() {
+        // arrange
+        let connect_args = args("--connect 127.0.0.1:7777 --host-spectators 7800");
+
+        // act
+        let options = parse_args(connect_args);
+
+        // assert
+        assert_eq!(true, options.is_err());
+    }
+
+    #[test]
+    fn should_parse_server_port() {
+        // arrange
+        let server_args = args("--port 9000");
+
+        // act
+        let options = parse_server_args(server_args);
+
+        // assert
+        assert_eq!(Ok(ServerOptions { port: 9000 }), options);
+    }
+
+    #[test]
+    fn should_parse_lobby_arguments() {
+        // arrange
+        let lobby_args = args("--lobby --name ada");
+
+        // act
+        let options = parse_args(lobby_args).unwrap();
+
+        // assert
+        assert_eq!(true, options.lobby);
+        assert_eq!(Some("ada".to_string()), options.name);
+    }
+
+    #[test]
+    fn should_reject_four_player_netplay() {
+        // arrange
+        let four_player_args = args("--four-player --netplay 7001 127.0.0.1:7000");
+
+        // act
+        let options = parse_args(four_player_args);
+
+        // assert
+        assert_eq!(true, options.is_err());
+    }
+
+    #[test]
+    fn should_parse_doubles_mode() {
+        // arrange
+        let doubles_args = args("--doubles");
+
+        // act
+        let options = parse_args(doubles_args).unwrap();
+
+        // assert
+        assert_eq!(GameMode::Doubles, options.mode);
+    }
+
+    #[test]
+    fn should_parse_power_ups_with_other_modes() {
+        // arrange
+        let power_ups_args = args("--four-player --multi-ball --power-ups");
+
+        // act
+        let options = parse_args(power_ups_args).unwrap();
+
+        // assert
+        assert_eq!(GameMode::FourPlayer, options.mode);
+        assert_eq!(true, options.multi_ball);
+        assert_eq!(true, options.power_ups);
+    }
+
+    #[test]
+    fn should_parse_level_name() {
+        // arrange
+        let level_args = args("--level pillars");
+
+        // act
+        let options = parse_args(level_args).unwrap();
+
+        // assert
+        assert_eq!(Some("pillars".to_string()), options.level);
+    }
+
+    #[test]
+    fn should_reject_handicaps_online() {
+        // arrange
+        let local_args = args("--handicaps --select-level");
+        let online_args = args("--handicaps --connect 127.0.0.1:4000");
+
+        // act
+        let options = parse_args(local_args).unwrap();
+        let online = parse_args(online_args);
+
+        // assert
+        assert_eq!(true, options.handicaps);
+        assert_eq!(true, online.is_err());
+    }
+
+    #[test]
+    fn should_parse_seed_of_local_matches_only() {
+        // arrange
+        let local_args = args("--practice --seed 42");
+        let online_args = args("--seed 42 --spectate 127.0.0.1:4000");
+
+        // act
+        let options = parse_args(local_args).unwrap();
+        let online = parse_args(online_args);
+
+        // assert
+        assert_eq!(Some(42), options.seed);
+        assert_eq!(true, online.is_err());
+    }
+
+    #[test]
+    fn should_resume_local_matches_only() {
+        // arrange
+        let local_args = args("--resume");
+        let netplay_args = args("--resume --netplay 7000 127.0.0.1:7001");
+
+        // act
+        let options = parse_args(local_args).unwrap();
+        let netplay = parse_args(netplay_args);
+
+        // assert
+        assert_eq!(true, options.resume);
+        assert_eq!(true, netplay.is_err());
+    }
+
+    #[test]
+    fn should_show_stats_of_local_matches_only() {
+        // arrange
+        let local_args = args("--stats");
+        let lobby_args = args("--stats --lobby");
+
+        // act
+        let options = parse_args(local_args).unwrap();
+        let lobby = parse_args(lobby_args);
+
+        // assert
+        assert_eq!(true, options.stats);
+        assert_eq!(true, lobby.is_err());
+    }
+
+    #[test]
+    fn should_parse_profile_options_of_local_matches_only() {
+        // arrange
+        let local_args = args("--new-profile ada --profiles --leaderboard");
+        let netplay_args = args("--profiles --netplay 7001 127.0.0.1:7000");
+
+        // act
+        let options = parse_args(local_args).unwrap();
+        let netplay = parse_args(netplay_args);
+
+        // assert
+        assert_eq!(Some("ada".to_string()), options.new_profile);
+        assert_eq!(true, options.profiles);
+        assert_eq!(true, options.leaderboard);
+        assert_eq!(true, netplay.is_err());
+    }
+
+    #[test]
+    fn should_parse_ring_players() {
+        // arrange
+        let ring_args = args("--ring 5");
+        let crowded_args = args("--ring 7");
+
+        // act
+        let options = parse_args(ring_args).unwrap();
+        let crowded = parse_args(crowded_args);
+
+        // assert
+        assert_eq!(GameMode::Ring, options.mode);
+        assert_eq!(Some(5), options.ring_players);
+        assert_eq!(true, crowded.is_err());
+    }
+
+    #[test]
+    fn should_reject_ring_with_another_mode() {
+        // arrange
+        let ring_first_args = args("--ring 3 --four-player");
+        let ring_last_args = args("--doubles --ring 4");
+
+        // act
+        let ring_first = parse_args(ring_first_args);
+        let ring_last = parse_args(ring_last_args);
+
+        // assert
+        assert_eq!(true, ring_first.is_err());
+        assert_eq!(true, ring_last.is_err());
+    }
+
+    #[test]
+    fn should_reject_several_modes() {
+        // arrange
+        let modes_args = args("--squash --breakout");
+
+        // act
+        let options = parse_args(modes_args);
+
+        // assert
+        assert_eq!(true, options.is_err());
+    }
+
+    #[test]
+    fn should_reject_power_ups_in_air_hockey() {
+        // arrange
+        let air_hockey_args = args("--air-hockey --multi-ball");
+        let power_ups_args = args("--air-hockey --power-ups");
+
+        // act
+        let options = parse_args(air_hockey_args).unwrap();
+        let power_ups = parse_args(power_ups_args);
+
+        // assert
+        assert_eq!(GameMode::AirHockey, options.mode);
+        assert_eq!(true, power_ups.is_err());
+    }
+
+    #[test]
+    fn should_parse_arena_physics() {
+        // arrange
+        let physics_args = args("--gravity --drag");
+
+        // act
+        let options = parse_args(physics_args).unwrap();
+
+        // assert
+        assert_eq!(vec![Force::Gravity, Force::Drag], options.forces);
+    }
+
+    #[test]
+    fn should_reject_unknown_argument() {
+        // arrange
+        let unknown_args = args("--turbo");
+
+        // act
+        let options = parse_args(unknown_args);
+
+        // assert
+        assert_eq!(true, options.is_err());
+    }
+}
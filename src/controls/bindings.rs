@@ -0,0 +1,421 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use amethyst::input::{Axis, Bindings, Button, StringBindings, VirtualKeyCode};
+use ron::ser::PrettyConfig;
+
+use crate::{
+    entities::Side,
+    settings::{KEY_BINDINGS_FILE, USER_KEY_BINDINGS_FILE},
+};
+
+/// Actions a player can bind to a key or controller button.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ControlAction {
+    Up,
+    Down,
+    Pause,
+    /// Towards the middle of the arena (air-hockey mallets, P1 and P2 only).
+    Forward,
+    /// Back towards the player's own goal (air-hockey mallets, P1 and P2 only).
+    Back,
+}
+
+/// A single rebindable control: one action of one player.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct BindingSlot {
+    pub side: Side,
+    pub action: ControlAction,
+}
+
+impl BindingSlot {
+    /// Human readable name used by the controls screen (e.g. "P1 UP"). Up/down move the
+    /// horizontal (top/bottom) paddles right/left.
+    pub fn label(&self) -> String {
+        let action = match (self.action, self.side.is_horizontal()) {
+            (ControlAction::Up, false) => "UP",
+            (ControlAction::Down, false) => "DOWN",
+            (ControlAction::Up, true) => "RIGHT",
+            (ControlAction::Down, true) => "LEFT",
+            (ControlAction::Pause, _) => "PAUSE",
+            (ControlAction::Forward, _) => "FORWARD",
+            (ControlAction::Back, _) => "BACK",
+        };
+
+        format!("{} {}", self.side.player_label(), action)
+    }
+}
+
+/// Every rebindable slot in the order shown by the controls screen.
+pub const BINDING_SLOTS: [BindingSlot; 16] = [
+    BindingSlot {
+        side: Side::Left,
+        action: ControlAction::Up,
+    },
+    BindingSlot {
+        side: Side::Left,
+        action: ControlAction::Down,
+    },
+    BindingSlot {
+        side: Side::Left,
+        action: ControlAction::Pause,
+    },
+    BindingSlot {
+        side: Side::Right,
+        action: ControlAction::Up,
+    },
+    BindingSlot {
+        side: Side::Right,
+        action: ControlAction::Down,
+    },
+    BindingSlot {
+        side: Side::Right,
+        action: ControlAction::Pause,
+    },
+    BindingSlot {
+        side: Side::Top,
+        action: ControlAction::Up,
+    },
+    BindingSlot {
+        side: Side::Top,
+        action: ControlAction::Down,
+    },
+    BindingSlot {
+        side: Side::Top,
+        action: ControlAction::Pause,
+    },
+    BindingSlot {
+        side: Side::Bottom,
+        action: ControlAction::Up,
+    },
+    BindingSlot {
+        side: Side::Bottom,
+        action: ControlAction::Down,
+    },
+    BindingSlot {
+        side: Side::Bottom,
+        action: ControlAction::Pause,
+    },
+    BindingSlot {
+        side: Side::Left,
+        action: ControlAction::Forward,
+    },
+    BindingSlot {
+        side: Side::Left,
+        action: ControlAction::Back,
+    },
+    BindingSlot {
+        side: Side::Right,
+        action: ControlAction::Forward,
+    },
+    BindingSlot {
+        side: Side::Right,
+        action: ControlAction::Back,
+    },
+];
+
+/// Error returned when a button is already bound to a different slot.
+#[derive(PartialEq, Debug)]
+pub struct BindingConflict {
+    pub button: Button,
+    pub bound_to: BindingSlot,
+}
+
+/// Editable view of the players' bindings: exactly one button per slot.
+#[derive(Clone, PartialEq, Debug)]
+pub struct KeyBindings {
+    buttons: HashMap<BindingSlot, Button>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let defaults = [
+            Button::Key(VirtualKeyCode::W),
+            Button::Key(VirtualKeyCode::S),
+            Button::Key(VirtualKeyCode::Space),
+            Button::Key(VirtualKeyCode::Up),
+            Button::Key(VirtualKeyCode::Down),
+            Button::Key(VirtualKeyCode::P),
+            Button::Key(VirtualKeyCode::L),
+            Button::Key(VirtualKeyCode::J),
+            Button::Key(VirtualKeyCode::I),
+            Button::Key(VirtualKeyCode::Right),
+            Button::Key(VirtualKeyCode::Left),
+            Button::Key(VirtualKeyCode::RShift),
+            Button::Key(VirtualKeyCode::D),
+            Button::Key(VirtualKeyCode::A),
+            Button::Key(VirtualKeyCode::Delete),
+            Button::Key(VirtualKeyCode::PageDown),
+        ];
+
+        KeyBindings {
+            buttons: BINDING_SLOTS
+                .iter()
+                .cloned()
+                .zip(defaults.iter().cloned())
+                .collect(),
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Reads the emulated paddle axes (mallet cross axes included) and pause actions from
+    /// loaded input bindings. Slots that are missing (or bound to something this screen can't
+    /// edit) keep their defaults.
+    pub fn from_bindings(bindings: &Bindings<StringBindings>) -> Self {
+        let mut key_bindings = KeyBindings::default();
+
+        for side in Side::ALL.iter() {
+            if let Some(Axis::Emulated { pos, neg }) = bindings.axis(axis_name(*side)) {
+                key_bindings.set(*side, ControlAction::Up, *pos);
+                key_bindings.set(*side, ControlAction::Down, *neg);
+            }
+
+            // only single button combos can be represented by a slot
+            let pause = bindings
+                .action_bindings(pause_action_name(*side))
+                .find(|combo| combo.len() == 1)
+                .map(|combo| combo[0]);
+
+            if let Some(button) = pause {
+                key_bindings.set(*side, ControlAction::Pause, button);
+            }
+
+            let cross_axis = cross_axis_name(*side).and_then(|name| bindings.axis(name));
+
+            if let Some(Axis::Emulated { pos, neg }) = cross_axis {
+                key_bindings.set(*side, ControlAction::Forward, *pos);
+                key_bindings.set(*side, ControlAction::Back, *neg);
+            }
+        }
+
+        key_bindings
+    }
+
+    /// Returns the button currently bound to the given slot.
+    pub fn button(&self, slot: BindingSlot) -> Button {
+        self.buttons[&slot]
+    }
+
+    /// Binds a button to a slot unless the button is already used by another slot.
+    pub fn bind(&mut self, slot: BindingSlot, button: Button) -> Result<(), BindingConflict> {
+        let bound_to = self
+            .buttons
+            .iter()
+            .find(|(other, other_button)| **other != slot && **other_button == button)
+            .map(|(other, _)| *other);
+
+        if let Some(bound_to) = bound_to {
+            return Err(BindingConflict { button, bound_to });
+        }
+
+        self.buttons.insert(slot, button);
+
+        Ok(())
+    }
+
+    /// Builds the input bindings understood by the `InputHandler`.
+    pub fn to_bindings(&self) -> amethyst::Result<Bindings<StringBindings>> {
+        let mut bindings = Bindings::new();
+
+        for side in Side::ALL.iter() {
+            bindings.insert_axis(
+                axis_name(*side),
+                Axis::Emulated {
+                    pos: self.get(*side, ControlAction::Up),
+                    neg: self.get(*side, ControlAction::Down),
+                },
+            )?;
+            bindings.insert_action_binding(
+                pause_action_name(*side).to_string(),
+                vec![self.get(*side, ControlAction::Pause)],
+            )?;
+
+            if let Some(name) = cross_axis_name(*side) {
+                bindings.insert_axis(
+                    name,
+                    Axis::Emulated {
+                        pos: self.get(*side, ControlAction::Forward),
+                        neg: self.get(*side, ControlAction::Back),
+                    },
+                )?;
+            }
+        }
+
+        Ok(bindings)
+    }
+
+    /// Serializes the bindings in the same RON format as key_bindings.ron.
+    pub fn to_ron(&self) -> amethyst::Result<String> {
+        let serialized = ron::ser::to_string_pretty(&self.to_bindings()?, PrettyConfig::default())?;
+
+        Ok(serialized)
+    }
+
+    /// Writes the bindings to a file that `InputBundle::with_bindings_from_file` can load.
+    pub fn save(&self, path: &Path) -> amethyst::Result<()> {
+        fs::write(path, self.to_ron()?)?;
+
+        Ok(())
+    }
+
+    fn get(&self, side: Side, action: ControlAction) -> Button {
+        self.button(BindingSlot { side, action })
+    }
+
+    fn set(&mut self, side: Side, action: ControlAction, button: Button) {
+        self.buttons.insert(BindingSlot { side, action }, button);
+    }
+}
+
+/// Name of the input axis that moves the paddle of the given side.
+pub fn axis_name(side: Side) -> &'static str {
+    match side {
+        Side::Left => "left_paddle",
+        Side::Right => "right_paddle",
+        Side::Top => "top_paddle",
+        Side::Bottom => "bottom_paddle",
+    }
+}
+
+/// Name of the input axis that moves the air-hockey mallet of the given side forward (positive)
+/// and back. Only P1 and P2 have one.
+pub fn cross_axis_name(side: Side) -> Option<&'static str> {
+    match side {
+        Side::Left => Some("left_paddle_cross"),
+        Side::Right => Some("right_paddle_cross"),
+        Side::Top | Side::Bottom => None,
+    }
+}
+
+/// Name of the input action that pauses the game for the given side.
+pub fn pause_action_name(side: Side) -> &'static str {
+    match side {
+        Side::Left => "left_pause",
+        Side::Right => "right_pause",
+        Side::Top => "top_pause",
+        Side::Bottom => "bottom_pause",
+    }
+}
+
+/// Whether an input action is one of the players' pause actions.
+pub fn is_pause_action(action: &str) -> bool {
+    Side::ALL
+        .iter()
+        .any(|side| pause_action_name(*side) == action)
+}
+
+/// Path of the bindings saved by the controls screen.
+pub fn user_key_bindings_path(app_root: &Path) -> PathBuf {
+    app_root
+        .join("src")
+        .join("settings")
+        .join(USER_KEY_BINDINGS_FILE)
+}
+
+/// Path of the bindings to load: the user's saved bindings if any, otherwise the defaults.
+pub fn key_bindings_path(app_root: &Path) -> PathBuf {
+    let user_path = user_key_bindings_path(app_root);
+
+    if user_path.exists() {
+        user_path
+    } else {
+        app_root
+            .join("src")
+            .join("settings")
+            .join(KEY_BINDINGS_FILE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_detect_binding_conflict() {
+        // arrange
+        let mut key_bindings = KeyBindings::default();
+        let p2_up = BINDING_SLOTS[3];
+
+        // act - W is P1's default up key
+        let result = key_bindings.bind(p2_up, Button::Key(VirtualKeyCode::W));
+
+        // assert
+        assert_eq!(
+            Err(BindingConflict {
+                button: Button::Key(VirtualKeyCode::W),
+                bound_to: BINDING_SLOTS[0],
+            }),
+            result
+        );
+        assert_eq!(Button::Key(VirtualKeyCode::Up), key_bindings.button(p2_up));
+    }
+
+    #[test]
+    fn should_bind_free_button() {
+        // arrange
+        let mut key_bindings = KeyBindings::default();
+        let p1_up = BINDING_SLOTS[0];
+
+        // act
+        let result = key_bindings.bind(p1_up, Button::Key(VirtualKeyCode::E));
+
+        // assert
+        assert_eq!(Ok(()), result);
+        assert_eq!(Button::Key(VirtualKeyCode::E), key_bindings.button(p1_up));
+    }
+
+    #[test]
+    fn should_allow_rebinding_slot_to_its_own_button() {
+        // arrange
+        let mut key_bindings = KeyBindings::default();
+        let p1_up = BINDING_SLOTS[0];
+
+        // act
+        let result = key_bindings.bind(p1_up, Button::Key(VirtualKeyCode::W));
+
+        // assert
+        assert_eq!(Ok(()), result);
+    }
+
+    #[test]
+    fn should_read_legacy_bindings_without_mallet_cross_axes() {
+        // arrange - bindings saved before air hockey existed, with a custom P1 up key
+        let mut legacy = KeyBindings::default();
+        legacy
+            .bind(BINDING_SLOTS[0], Button::Key(VirtualKeyCode::E))
+            .unwrap();
+        let mut bindings = legacy.to_bindings().unwrap();
+        bindings.remove_axis("left_paddle_cross");
+        bindings.remove_axis("right_paddle_cross");
+
+        // act
+        let key_bindings = KeyBindings::from_bindings(&bindings);
+
+        // assert - the saved keys are kept and the mallets get the default keys
+        assert_eq!(legacy, key_bindings);
+        assert_eq!(
+            Button::Key(VirtualKeyCode::D),
+            key_bindings.button(BINDING_SLOTS[12])
+        );
+    }
+
+    #[test]
+    fn should_round_trip_through_string_bindings_ron() {
+        // arrange
+        let mut key_bindings = KeyBindings::default();
+        key_bindings
+            .bind(BINDING_SLOTS[5], Button::Key(VirtualKeyCode::Return))
+            .unwrap();
+
+        // act
+        let serialized = key_bindings.to_ron().unwrap();
+        let bindings: Bindings<StringBindings> = ron::de::from_str(&serialized).unwrap();
+
+        // assert
+        assert_eq!(key_bindings, KeyBindings::from_bindings(&bindings));
+    }
+}
@@ -0,0 +1,286 @@
+use super::PaddleInput;
+use crate::{
+    entities::{predict_path, ArenaPhysics, RingArc, Side, PREDICTION_STEP, RING_CENTER},
+    settings::{ARENA_HEIGHT, ARENA_WIDTH, BALL_RADIUS},
+};
+
+/// Distance from its target past which a CPU paddle moves at full speed.
+const FULL_SPEED_DISTANCE: f32 = 8.0;
+
+/// Seconds ahead the CPU paddles predict the balls' paths.
+const PREDICTION_SECONDS: f32 = 3.0;
+
+/// Distance from their goal line CPU mallets guard it at.
+const MALLET_GUARD_DEPTH: f32 = 15.0;
+
+/// Distance CPU mallets keep from the puck while getting around it.
+const MALLET_CLEARANCE: f32 = 10.0;
+
+/// Input of a CPU driven paddle: it heads to where the nearest ball that is still heading
+/// towards its goal is predicted to reach it (bouncing off the walls and under the arena
+/// physics, if any) and goes back to the middle of its edge when there's none. Only depends on
+/// the match state, so CPU paddles are simulated the same way by every netplay peer.
+pub fn cpu_input(
+    side: Side,
+    paddle_position: [f32; 2],
+    balls: &[([f32; 2], [f32; 2])],
+    physics: Option<&ArenaPhysics>,
+) -> PaddleInput {
+    let edge_axis = 1 - side.axis();
+    let target = guard_target(side, paddle_position, balls, physics);
+    let mv_amount = (target - paddle_position[edge_axis]) / FULL_SPEED_DISTANCE;
+
+    PaddleInput::Axis(mv_amount.clamp(-1.0, 1.0))
+}
+
+/// Position along its edge a CPU paddle guarding its goal heads to.
+fn guard_target(
+    side: Side,
+    paddle_position: [f32; 2],
+    balls: &[([f32; 2], [f32; 2])],
+    physics: Option<&ArenaPhysics>,
+) -> f32 {
+    // the paddle moves along the axis the ball doesn't cross its goal along
+    let goal_axis = side.axis();
+    let edge_axis = 1 - goal_axis;
+    let middle = [ARENA_WIDTH, ARENA_HEIGHT][edge_axis] * 0.5;
+
+    let distance_to_paddle = |ball_position: &[f32; 2]| {
+        (paddle_position[goal_axis] - ball_position[goal_axis]) * side.outwards()
+    };
+
+    balls
+        .iter()
+        .filter(|(position, velocity)| {
+            velocity[goal_axis] * side.outwards() > 0.0 && distance_to_paddle(position) > 0.0
+        })
+        .min_by(|(a, _), (b, _)| {
+            distance_to_paddle(a)
+                .partial_cmp(&distance_to_paddle(b))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .map(|(position, velocity)| {
+            let steps = (PREDICTION_SECONDS / PREDICTION_STEP) as usize;
+            let path = predict_path(
+                physics,
+                *position,
+                *velocity,
+                BALL_RADIUS,
+                Some(edge_axis),
+                steps,
+            );
+
+            // the ball's position once it reaches the paddle, or as far as it was predicted
+            path.iter()
+                .find(|predicted| distance_to_paddle(predicted) <= 0.0)
+                .or_else(|| path.last())
+                .unwrap_or(position)[edge_axis]
+        })
+        .unwrap_or(middle)
+}
+
+/// Input of a CPU driven air-hockey mallet: it strikes the nearest puck in its half towards the
+/// other goal, getting around it first when it's behind the puck, and guards its goal like a CPU
+/// paddle otherwise.
+pub fn mallet_cpu_input(
+    side: Side,
+    mallet_position: [f32; 2],
+    balls: &[([f32; 2], [f32; 2])],
+    physics: Option<&ArenaPhysics>,
+) -> PaddleInput {
+    let goal_axis = side.axis();
+    let edge_axis = 1 - goal_axis;
+    let length = [ARENA_WIDTH, ARENA_HEIGHT][goal_axis];
+    let goal_line = if side.outwards() < 0.0 { 0.0 } else { length };
+
+    // how far a position is from the middle line, towards the mallet's goal
+    let depth = |position: &[f32; 2]| (position[goal_axis] - length * 0.5) * side.outwards();
+    let distance = |position: &[f32; 2]| {
+        (position[0] - mallet_position[0]).hypot(position[1] - mallet_position[1])
+    };
+
+    let puck = balls
+        .iter()
+        .map(|(position, _)| position)
+        .filter(|position| depth(position) > 0.0)
+        .min_by(|a, b| {
+            distance(a)
+                .partial_cmp(&distance(b))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+    let target = match puck {
+        // between the puck and its goal: straight through the puck
+        Some(position) if depth(&mallet_position) > depth(position) => *position,
+        // past the puck: around it, beside it on the goal's side
+        Some(position) => {
+            let mut target = *position;
+            let beside = if mallet_position[edge_axis] < position[edge_axis] {
+                -1.0
+            } else {
+                1.0
+            };
+
+            target[goal_axis] += side.outwards() * MALLET_CLEARANCE;
+            target[edge_axis] += beside * MALLET_CLEARANCE;
+            target
+        }
+        None => {
+            let mut target = [0.0, 0.0];
+
+            target[goal_axis] = goal_line - side.outwards() * MALLET_GUARD_DEPTH;
+            target[edge_axis] = guard_target(side, mallet_position, balls, physics);
+            target
+        }
+    };
+
+    let mv_amount = |axis: usize| {
+        ((target[axis] - mallet_position[axis]) / FULL_SPEED_DISTANCE).clamp(-1.0, 1.0)
+    };
+
+    PaddleInput::Stick([mv_amount(0), mv_amount(1)])
+}
+
+/// Input of a CPU driven ring paddle: it heads to where the first ball predicted to cross its
+/// arc does so, and goes back to the middle of its goal when no ball is heading there.
+pub fn ring_cpu_input(
+    arc: &RingArc,
+    paddle_position: [f32; 2],
+    balls: &[([f32; 2], [f32; 2])],
+    physics: Option<&ArenaPhysics>,
+) -> PaddleInput {
+    let steps = (PREDICTION_SECONDS / PREDICTION_STEP) as usize;
+    let is_inside = |position: &[f32; 2]| {
+        (position[0] - RING_CENTER[0]).hypot(position[1] - RING_CENTER[1]) < arc.radius
+    };
+
+    let target = balls
+        .iter()
+        .filter_map(|(position, velocity)| {
+            let path = predict_path(physics, *position, *velocity, BALL_RADIUS, None, steps);
+
+            // where (and how soon) the ball crosses the paddles' circle on its way out
+            path.iter()
+                .enumerate()
+                .skip_while(|(_, predicted)| !is_inside(predicted))
+                .find(|(_, predicted)| !is_inside(predicted))
+                .map(|(step, crossing)| (step, arc.offset(*crossing)))
+        })
+        .filter(|(_, offset)| offset.abs() <= arc.half_angle * arc.radius)
+        .min_by_key(|(step, _)| *step)
+        .map_or(0.0, |(_, offset)| offset);
+
+    let mv_amount = (target - arc.offset(paddle_position)) / FULL_SPEED_DISTANCE;
+
+    PaddleInput::Axis(mv_amount.clamp(-1.0, 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{entities::Force, settings::PhysicsConfig};
+
+    #[test]
+    fn should_follow_ball_heading_towards_goal() {
+        // arrange - ball coming towards the left goal, well above the paddle
+        let paddle_position = [30.0, 50.0];
+        let balls = [([60.0, 80.0], [-75.0, 50.0])];
+
+        // act
+        let input = cpu_input(Side::Left, paddle_position, &balls, None);
+
+        // assert
+        assert_eq!(PaddleInput::Axis(1.0), input);
+    }
+
+    #[test]
+    fn should_return_to_middle_when_ball_moves_away() {
+        // arrange - the ball already went past the paddle, towards the other team
+        let paddle_position = [30.0, 46.0];
+        let balls = [([60.0, 10.0], [75.0, -50.0])];
+
+        // act
+        let input = cpu_input(Side::Left, paddle_position, &balls, None);
+
+        // assert
+        assert_eq!(PaddleInput::Axis(0.5), input); // (50.0 - 46.0) / 8.0
+    }
+
+    #[test]
+    fn should_head_to_where_ball_bounces_off_the_wall() {
+        // arrange - ball above the paddle but about to bounce off the top wall and come down
+        let paddle_position = [30.0, 80.0];
+        let balls = [([60.0, 90.0], [-75.0, 100.0])];
+
+        // act
+        let input = cpu_input(Side::Left, paddle_position, &balls, None);
+
+        // assert
+        match input {
+            PaddleInput::Axis(mv_amount) => assert!(mv_amount < 0.0),
+            other => panic!("unexpected input {:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_strike_puck_in_own_half_towards_other_goal() {
+        // arrange - left mallet between its goal and the puck, slightly below it
+        let mallet_position = [15.0, 45.0];
+        let balls = [([40.0, 50.0], [0.0, 0.0])];
+
+        // act
+        let input = mallet_cpu_input(Side::Left, mallet_position, &balls, None);
+
+        // assert
+        assert_eq!(PaddleInput::Stick([1.0, 0.625]), input); // (50.0 - 45.0) / 8.0
+    }
+
+    #[test]
+    fn should_guard_goal_when_puck_is_in_other_half() {
+        // arrange - left mallet up front, the puck sliding in the other half
+        let mallet_position = [40.0, 50.0];
+        let balls = [([80.0, 50.0], [40.0, 0.0])];
+
+        // act
+        let input = mallet_cpu_input(Side::Left, mallet_position, &balls, None);
+
+        // assert - back to its guard depth, staying in the middle
+        assert_eq!(PaddleInput::Stick([-1.0, 0.0]), input);
+    }
+
+    #[test]
+    fn should_head_to_where_ball_crosses_ring_paddle_arc() {
+        // arrange - left ring paddle in the middle of its goal, ball heading down-left
+        let arc = RingArc {
+            seat: 0,
+            center: std::f32::consts::PI,
+            radius: 44.0,
+            half_angle: 0.5,
+        };
+        let paddle_position = [6.0, 50.0];
+        let balls = [([50.0, 50.0], [-75.0, -20.0])];
+
+        // act
+        let input = ring_cpu_input(&arc, paddle_position, &balls, None);
+
+        // assert - counterclockwise is downwards on the left
+        assert_eq!(PaddleInput::Axis(1.0), input);
+    }
+
+    #[test]
+    fn should_account_for_gravity() {
+        // arrange - ball flying straight at the paddle while gravity pulls it down
+        let paddle_position = [30.0, 50.0];
+        let balls = [([60.0, 50.0], [-75.0, 0.0])];
+        let physics = ArenaPhysics::new(&PhysicsConfig::default(), &[Force::Gravity]);
+
+        // act
+        let input = cpu_input(Side::Left, paddle_position, &balls, Some(&physics));
+
+        // assert
+        match input {
+            PaddleInput::Axis(mv_amount) => assert!(mv_amount < 0.0),
+            other => panic!("unexpected input {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+
+use amethyst::input::{ControllerAxis, ControllerButton, InputHandler, StringBindings};
+
+use crate::{entities::Side, settings::PlayerControls};
+
+/// Sides that can be driven by a controller, in the order controllers are handed out.
+const CONTROLLER_SIDES: [Side; 4] = Side::ALL;
+
+/// Resource mapping each player (paddle side) to the controller that drives it.
+#[derive(Default, Debug)]
+pub struct ControllerAssignments {
+    controllers: HashMap<Side, u32>,
+    disconnected: bool,
+}
+
+impl ControllerAssignments {
+    /// Controller currently assigned to a side, if any.
+    pub fn controller(&self, side: Side) -> Option<u32> {
+        self.controllers.get(&side).cloned()
+    }
+
+    /// Assigns a newly connected controller to the first side without one (P1, then P2, ...).
+    /// Returns the side it was assigned to, or None when every side already has a controller.
+    pub fn connect(&mut self, controller_id: u32) -> Option<Side> {
+        let free_side = CONTROLLER_SIDES
+            .iter()
+            .find(|side| !self.controllers.contains_key(side))
+            .cloned()?;
+
+        self.controllers.insert(free_side, controller_id);
+
+        Some(free_side)
+    }
+
+    /// Frees the side of a disconnected controller and flags that the game should pause.
+    pub fn disconnect(&mut self, controller_id: u32) -> Option<Side> {
+        let side = self
+            .controllers
+            .iter()
+            .find(|(_, id)| **id == controller_id)
+            .map(|(side, _)| *side)?;
+
+        self.controllers.remove(&side);
+        self.disconnected = true;
+
+        Some(side)
+    }
+
+    /// Returns whether a player's controller was disconnected since the last call.
+    pub fn take_disconnected(&mut self) -> bool {
+        std::mem::replace(&mut self.disconnected, false)
+    }
+}
+
+/// Rescales an analog axis value so that the dead zone maps to 0.0 and the rest of the stick
+/// travel maps to (0.0, 1.0], multiplied by the player's sensitivity.
+pub fn apply_dead_zone(value: f32, dead_zone: f32, sensitivity: f32) -> f32 {
+    if value.abs() <= dead_zone {
+        return 0.0;
+    }
+
+    let scaled = (value.abs() - dead_zone) / (1.0 - dead_zone) * sensitivity;
+
+    scaled.min(1.0) * value.signum()
+}
+
+/// Reads a controller's paddle axis: the D-pad moves at full speed while the left stick moves
+/// proportionally to how far it's pushed. Positive values move the paddle up, or right for
+/// horizontal (top/bottom) paddles.
+pub fn controller_axis_value(
+    input: &InputHandler<StringBindings>,
+    controller_id: u32,
+    player_controls: &PlayerControls,
+    horizontal: bool,
+) -> f32 {
+    let (positive_button, negative_button) = if horizontal {
+        (ControllerButton::DPadRight, ControllerButton::DPadLeft)
+    } else {
+        (ControllerButton::DPadUp, ControllerButton::DPadDown)
+    };
+
+    if input.controller_button_is_down(controller_id, positive_button) {
+        return 1.0;
+    }
+
+    if input.controller_button_is_down(controller_id, negative_button) {
+        return -1.0;
+    }
+
+    let stick = if horizontal {
+        input
+            .controller_axis_value(controller_id, ControllerAxis::LeftX)
+            .unwrap_or(0.0)
+    } else {
+        // controllers report the y axis growing downwards
+        -input
+            .controller_axis_value(controller_id, ControllerAxis::LeftY)
+            .unwrap_or(0.0)
+    };
+
+    apply_dead_zone(
+        stick,
+        player_controls.dead_zone,
+        player_controls.sensitivity,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_ignore_values_inside_dead_zone() {
+        // arrange
+        let value = -0.1;
+
+        // act
+        let axis_value = apply_dead_zone(value, 0.2, 1.0);
+
+        // assert
+        assert_eq!(0.0, axis_value);
+    }
+
+    #[test]
+    fn should_rescale_values_outside_dead_zone() {
+        // arrange
+        let value = -0.75; // halfway between the dead zone and the stick's end
+
+        // act
+        let axis_value = apply_dead_zone(value, 0.5, 1.0);
+
+        // assert
+        assert_eq!(-0.5, axis_value);
+    }
+
+    #[test]
+    fn should_clamp_sensitivity_scaled_values() {
+        // arrange
+        let value = 0.8;
+
+        // act
+        let axis_value = apply_dead_zone(value, 0.2, 2.0);
+
+        // assert
+        assert_eq!(1.0, axis_value);
+    }
+
+    #[test]
+    fn should_assign_controllers_to_players_in_order() {
+        // arrange
+        let mut assignments = ControllerAssignments::default();
+
+        // act
+        let first = assignments.connect(7);
+        let second = assignments.connect(3);
+        let third = assignments.connect(5);
+        let fourth = assignments.connect(2);
+        let fifth = assignments.connect(9);
+
+        // assert
+        assert_eq!(Some(Side::Left), first);
+        assert_eq!(Some(Side::Right), second);
+        assert_eq!(Some(Side::Top), third);
+        assert_eq!(Some(Side::Bottom), fourth);
+        assert_eq!(None, fifth);
+        assert_eq!(Some(3), assignments.controller(Side::Right));
+    }
+
+    #[test]
+    fn should_give_two_controllers_to_left_and_right_players() {
+        // arrange
+        let mut assignments = ControllerAssignments::default();
+
+        // act
+        let first = assignments.connect(0);
+        let second = assignments.connect(1);
+
+        // assert - two-player matches are driven by P1 and P2 only
+        assert_eq!(Some(Side::Left), first);
+        assert_eq!(Some(Side::Right), second);
+        assert_eq!(None, assignments.controller(Side::Top));
+        assert_eq!(None, assignments.controller(Side::Bottom));
+    }
+
+    #[test]
+    fn should_reassign_reconnected_controller_to_free_side() {
+        // arrange
+        let mut assignments = ControllerAssignments::default();
+        assignments.connect(0);
+        assignments.connect(1);
+
+        // act
+        let lost_side = assignments.disconnect(0);
+        let new_side = assignments.connect(2);
+
+        // assert
+        assert_eq!(Some(Side::Left), lost_side);
+        assert_eq!(Some(Side::Left), new_side);
+        assert_eq!(true, assignments.take_disconnected());
+        assert_eq!(false, assignments.take_disconnected());
+    }
+}
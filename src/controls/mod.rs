@@ -0,0 +1,14 @@
+mod bindings;
+mod cpu;
+mod gamepad;
+mod mouse;
+mod paddle_input;
+
+pub use bindings::{
+    axis_name, cross_axis_name, is_pause_action, key_bindings_path, user_key_bindings_path,
+    BindingSlot, KeyBindings, BINDING_SLOTS,
+};
+pub use cpu::{cpu_input, mallet_cpu_input, ring_cpu_input};
+pub use gamepad::{controller_axis_value, ControllerAssignments};
+pub use mouse::{cursor_arena_position, move_towards};
+pub use paddle_input::{PaddleInput, PaddleInputs};
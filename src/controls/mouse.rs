@@ -0,0 +1,70 @@
+use amethyst::{
+    core::{
+        math::{Point3, Vector2},
+        Transform,
+    },
+    input::{InputHandler, StringBindings},
+    renderer::Camera,
+    window::ScreenDimensions,
+};
+
+/// Converts the mouse cursor position from screen space to arena coordinates by unprojecting it
+/// with the game camera. Returns None while the cursor is outside the window.
+pub fn cursor_arena_position(
+    input: &InputHandler<StringBindings>,
+    camera: &Camera,
+    camera_transform: &Transform,
+    screen_dimensions: &ScreenDimensions,
+) -> Option<[f32; 2]> {
+    let (cursor_x, cursor_y) = input.mouse_position()?;
+
+    let arena_position = camera.screen_to_world_point(
+        Point3::new(cursor_x, cursor_y, 0.0),
+        Vector2::new(screen_dimensions.width(), screen_dimensions.height()),
+        camera_transform,
+    );
+
+    Some([arena_position.x, arena_position.y])
+}
+
+/// Moves a position towards a target without moving more than max_step.
+pub fn move_towards(current: f32, target: f32, max_step: f32) -> f32 {
+    let distance = target - current;
+
+    if distance.abs() <= max_step {
+        target
+    } else {
+        current + max_step * distance.signum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_reach_close_target() {
+        // arrange
+        let current = 40.0;
+        let target = 41.0;
+
+        // act
+        let updated = move_towards(current, target, 1.5);
+
+        // assert
+        assert_eq!(target, updated);
+    }
+
+    #[test]
+    fn should_limit_movement_towards_far_target() {
+        // arrange
+        let current = 40.0;
+        let target = 10.0;
+
+        // act
+        let updated = move_towards(current, target, 1.5);
+
+        // assert
+        assert_eq!(38.5, updated);
+    }
+}
@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::entities::Side;
+
+/// What a player asked their paddle to do during a frame.
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize, Serialize)]
+pub enum PaddleInput {
+    /// Requested speed, from -1.0 (full speed down/left) to 1.0 (full speed up/right).
+    Axis(f32),
+    /// Arena y position (x for top/bottom paddles) the paddle should follow (mouse control
+    /// scheme).
+    Target(f32),
+    /// Requested velocity of an air-hockey mallet in arena x and y, each from -1.0 to 1.0.
+    Stick([f32; 2]),
+    /// Arena position an air-hockey mallet should follow (mouse control scheme).
+    Point([f32; 2]),
+}
+
+impl Default for PaddleInput {
+    fn default() -> Self {
+        PaddleInput::Axis(0.0)
+    }
+}
+
+/// Resource holding the input of every paddle for the current frame. It decouples reading the
+/// devices from moving the paddles, so inputs can also come from the network.
+#[derive(Default, Debug)]
+pub struct PaddleInputs {
+    inputs: HashMap<Side, PaddleInput>,
+}
+
+impl PaddleInputs {
+    pub fn get(&self, side: Side) -> PaddleInput {
+        self.inputs.get(&side).cloned().unwrap_or_default()
+    }
+
+    pub fn set(&mut self, side: Side, input: PaddleInput) {
+        self.inputs.insert(side, input);
+    }
+}
@@ -1,27 +1,42 @@
 use amethyst::{
     assets::Handle,
     core::Transform,
-    ecs::{Component, DenseVecStorage},
+    ecs::{Component, DenseVecStorage, Entities, Entity, WriteStorage},
     prelude::{Builder, WorldExt},
     renderer::{SpriteRender, SpriteSheet},
     shred::World,
 };
+use serde::{Deserialize, Serialize};
 
-use crate::settings::{ARENA_HEIGHT, ARENA_WIDTH, BALL_RADIUS, BALL_VELOCITY_X, BALL_VELOCITY_Y};
+use super::Side;
+use crate::settings::{ARENA_HEIGHT, ARENA_WIDTH, BALL_RADIUS};
+
+/// Storages needed to serve a ball from within a system.
+pub type BallStorages<'a, 's> = (
+    &'a mut WriteStorage<'s, Ball>,
+    &'a mut WriteStorage<'s, Transform>,
+    &'a mut WriteStorage<'s, SpriteRender>,
+);
 
 /// Ball entity definition.
-#[derive(Debug)]
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
 pub struct Ball {
     pub velocity: [f32; 2],
     pub radius: f32,
+    /// Paddle that last sent the ball back, if any since it was served.
+    pub last_hit_by: Option<Side>,
+    /// Angular velocity (counterclockwise) in radians per second, curving the ball's path.
+    pub spin: f32,
 }
 
 impl Ball {
     // associated function
-    fn new(velocity: [f32; 2], radius: f32) -> Self {
+    pub fn new(velocity: [f32; 2], radius: f32) -> Self {
         Ball {
             velocity: velocity,
             radius: radius,
+            last_hit_by: None,
+            spin: 0.0,
         }
     }
 }
@@ -30,24 +45,54 @@ impl Component for Ball {
     type Storage = DenseVecStorage<Self>;
 }
 
-pub fn initialize_ball(world: &mut World, sprite_sheet_handle: Handle<SpriteSheet>) {
+/// Spawns a ball in the center of the arena with the given velocity. Without a sprite sheet
+/// (headless server) the ball isn't rendered.
+pub fn initialize_ball(
+    world: &mut World,
+    velocity: [f32; 2],
+    sprite_sheet_handle: Option<Handle<SpriteSheet>>,
+) {
     let mut position = Transform::default();
 
     // ball starts in the center of the screen
     position.set_translation_xyz(ARENA_WIDTH * 0.5, ARENA_HEIGHT * 0.5, 0.0);
 
-    let ball = Ball::new([BALL_VELOCITY_X, BALL_VELOCITY_Y], BALL_RADIUS);
-
-    let ball_sprite_render = SpriteRender::new(sprite_sheet_handle, 1);
+    let ball = Ball::new(velocity, BALL_RADIUS);
 
     // creates a new entity and associates the following components to it:
     // 1. Ball's state (struct)
     // 2. Ball's transform (position, rotation, scale vectors)
     // 3. Ball's sprite renderer from a sprite sheet
-    world
-        .create_entity()
-        .with(ball)
-        .with(position)
-        .with(ball_sprite_render)
-        .build();
+    let mut ball_builder = world.create_entity().with(ball).with(position);
+
+    if let Some(handle) = sprite_sheet_handle {
+        ball_builder = ball_builder.with(SpriteRender::new(handle, 1));
+    }
+
+    ball_builder.build();
+}
+
+/// Serves a ball from the center of the arena with the given velocity, from within a system
+/// (e.g. extra balls of multi-ball matches).
+pub fn serve_ball(
+    entities: &Entities,
+    storages: BallStorages,
+    velocity: [f32; 2],
+    sprite_sheet_handle: Option<Handle<SpriteSheet>>,
+) -> Entity {
+    let (ball_storage, transform_storage, sprite_render_storage) = storages;
+    let mut position = Transform::default();
+
+    position.set_translation_xyz(ARENA_WIDTH * 0.5, ARENA_HEIGHT * 0.5, 0.0);
+
+    let mut ball_builder = entities
+        .build_entity()
+        .with(Ball::new(velocity, BALL_RADIUS), ball_storage)
+        .with(position, transform_storage);
+
+    if let Some(handle) = sprite_sheet_handle {
+        ball_builder = ball_builder.with(SpriteRender::new(handle, 1), sprite_render_storage);
+    }
+
+    ball_builder.build()
 }
@@ -0,0 +1,152 @@
+use amethyst::{
+    assets::Handle,
+    core::{math::Vector3, Transform},
+    ecs::{Component, DenseVecStorage},
+    prelude::{Builder, WorldExt},
+    renderer::{palette::Srgba, resources::Tint, SpriteRender, SpriteSheet},
+    shred::World,
+};
+use serde::{Deserialize, Serialize};
+
+use super::Side;
+use crate::settings::{BreakoutConfig, ARENA_HEIGHT, ARENA_WIDTH};
+
+pub const BRICK_SPRITE: usize = 2;
+pub const CRACKED_BRICK_SPRITE: usize = 3;
+
+/// Size of the brick sprites, in pixels.
+const BRICK_SPRITE_SIZE: [f32; 2] = [8.0, 4.0];
+
+const BRICK_WIDTH: f32 = 4.0;
+const BRICK_GAP: f32 = 1.0;
+
+/// Clear strip in the middle of the arena, where the balls are served.
+const MIDDLE_GAP: f32 = 10.0;
+
+/// Brick of a breakout match: broken after being hit `max_hit_points` times.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct Brick {
+    pub hit_points: u32,
+    pub max_hit_points: u32,
+    pub width: f32,
+    pub height: f32,
+    pub last_hit_by: Option<Side>, // gets the points once the brick breaks
+}
+
+impl Brick {
+    /// Color of a brick, darker for the tougher ones.
+    pub fn color(max_hit_points: u32) -> [f32; 4] {
+        let shade = 1.0 / max_hit_points.max(1) as f32;
+
+        [1.0, 0.3 + 0.5 * shade, 0.2, 1.0]
+    }
+}
+
+impl Component for Brick {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Brick height for the given number of rows, leaving a gap between them.
+pub fn brick_height(rows: usize) -> f32 {
+    ARENA_HEIGHT / rows.max(1) as f32 - BRICK_GAP
+}
+
+/// Centers and hit points of the bricks: the columns are mirrored on both halves of the arena,
+/// from the middle outwards.
+pub fn brick_positions(config: &BreakoutConfig) -> Vec<([f32; 2], u32)> {
+    let row_height = ARENA_HEIGHT / config.rows.max(1) as f32;
+    let mut positions = Vec::new();
+
+    for (column, hit_points) in config.columns.iter().enumerate() {
+        let distance =
+            MIDDLE_GAP * 0.5 + BRICK_WIDTH * 0.5 + column as f32 * (BRICK_WIDTH + BRICK_GAP);
+
+        for x in [ARENA_WIDTH * 0.5 - distance, ARENA_WIDTH * 0.5 + distance].iter() {
+            for row in 0..config.rows {
+                let y = row_height * (row as f32 + 0.5);
+
+                positions.push(([*x, y], *hit_points));
+            }
+        }
+    }
+
+    positions
+}
+
+/// Creates the brick field of a breakout match.
+pub fn initialize_bricks(
+    world: &mut World,
+    config: &BreakoutConfig,
+    sprite_sheet_handle: Option<Handle<SpriteSheet>>,
+) {
+    let height = brick_height(config.rows);
+
+    for ([x, y], hit_points) in brick_positions(config) {
+        let mut transform = Transform::default();
+
+        transform.set_translation_xyz(x, y, 0.0);
+        transform.set_scale(Vector3::new(
+            BRICK_WIDTH / BRICK_SPRITE_SIZE[0],
+            height / BRICK_SPRITE_SIZE[1],
+            1.0,
+        ));
+
+        let brick = Brick {
+            hit_points,
+            max_hit_points: hit_points,
+            width: BRICK_WIDTH,
+            height,
+            last_hit_by: None,
+        };
+
+        spawn_brick(world, brick, transform, sprite_sheet_handle.clone());
+    }
+}
+
+/// Adds a brick to the arena, tinted after its toughness (the BreakoutSystem cracks it once
+/// it's been hit).
+pub fn spawn_brick(
+    world: &mut World,
+    brick: Brick,
+    transform: Transform,
+    sprite_sheet_handle: Option<Handle<SpriteSheet>>,
+) {
+    let [red, green, blue, alpha] = Brick::color(brick.max_hit_points);
+    let builder = world
+        .create_entity()
+        .with(brick)
+        .with(transform)
+        .with(Tint(Srgba::new(red, green, blue, alpha)));
+
+    // nothing is drawn when running headless
+    match sprite_sheet_handle {
+        Some(handle) => builder.with(SpriteRender::new(handle, BRICK_SPRITE)),
+        None => builder,
+    }
+    .build();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_mirror_brick_columns_around_the_middle() {
+        // arrange
+        let config = BreakoutConfig {
+            columns: vec![2, 1],
+            rows: 4,
+            ..BreakoutConfig::default()
+        };
+
+        // act
+        let positions = brick_positions(&config);
+
+        // assert
+        assert_eq!(16, positions.len());
+        assert_eq!(([43.0, 12.5], 2), positions[0]);
+        assert_eq!(([57.0, 12.5], 2), positions[4]);
+        assert_eq!(([38.0, 87.5], 1), positions[11]);
+        assert!(positions.iter().all(|([x, _], _)| (x - 50.0).abs() >= 5.0));
+    }
+}
@@ -0,0 +1,14 @@
+use amethyst::{
+    assets::{AssetStorage, Handle, Loader},
+    prelude::WorldExt,
+    shred::World,
+    ui::{FontAsset, TtfFormat},
+};
+
+/// Uses an asset loader to fetch font assets and return a font handle to the loaded asset.
+pub fn load_font_handle(font_location: &str, world: &mut World) -> Handle<FontAsset> {
+    let asset_loader = world.read_resource::<Loader>();
+    let storage = world.read_resource::<AssetStorage<FontAsset>>();
+
+    asset_loader.load(font_location, TtfFormat, (), &storage)
+}
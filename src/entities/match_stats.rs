@@ -0,0 +1,82 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// Resource present in local matches, collecting what their record in the match history needs:
+/// when the match started, how long it was played and its rallies, in paddle hits.
+#[derive(Clone, Default, PartialEq, Debug, Deserialize, Serialize)]
+pub struct MatchStats {
+    /// Random id telling apart the matches started in the same second.
+    #[serde(default)]
+    pub id: u64,
+    /// Start of the match, in seconds since the Unix epoch.
+    pub started_at: u64,
+    /// Seconds played, pauses excluded.
+    pub duration: f32,
+    pub rallies: u32,
+    pub longest_rally: u32,
+    pub total_hits: u32,
+    rally_hits: u32, // paddle hits since the last point
+}
+
+impl MatchStats {
+    /// Statistics of a match starting now.
+    pub fn new() -> Self {
+        let started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+
+        MatchStats {
+            id: rand::random(), // not from the GameRng: a match played again is another match
+            started_at,
+            ..MatchStats::default()
+        }
+    }
+
+    pub fn count_hit(&mut self) {
+        self.rally_hits += 1;
+        self.total_hits += 1;
+    }
+
+    /// A point was scored (or a life lost): the rally is over.
+    pub fn end_rally(&mut self) {
+        self.rallies += 1;
+        self.longest_rally = self.longest_rally.max(self.rally_hits);
+        self.rally_hits = 0;
+    }
+
+    /// Paddle hits per rally.
+    pub fn average_rally(&self) -> f32 {
+        if self.rallies == 0 {
+            0.0
+        } else {
+            self.total_hits as f32 / self.rallies as f32
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_keep_longest_and_average_rally() {
+        // arrange
+        let mut stats = MatchStats::default();
+
+        // act
+        for hits in [3, 1].iter() {
+            for _ in 0..*hits {
+                stats.count_hit();
+            }
+
+            stats.end_rally();
+        }
+
+        // assert
+        assert_eq!(2, stats.rallies);
+        assert_eq!(3, stats.longest_rally);
+        assert_eq!(2.0, stats.average_rally());
+    }
+}
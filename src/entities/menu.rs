@@ -0,0 +1,200 @@
+use amethyst::{
+    assets::Handle,
+    ecs::Entity,
+    input::{Button, InputEvent, VirtualKeyCode},
+    prelude::{Builder, WorldExt},
+    shred::World,
+    ui::{Anchor, FontAsset, LineMode, UiText, UiTransform},
+    StateEvent,
+};
+
+use super::font::load_font_handle;
+use crate::settings::SQUARE_FONT_PATH;
+
+const ITEM_COLOR: [f32; 4] = [1., 1., 1., 1.];
+const SELECTED_ITEM_COLOR: [f32; 4] = [1., 1., 0., 1.];
+const LINE_HEIGHT: f32 = 30.0;
+const MENU_HEIGHT: f32 = 500.0; // the window's height
+
+/// Navigation commands understood by every menu screen.
+#[derive(PartialEq, Eq, Debug)]
+pub enum MenuCommand {
+    Previous,
+    Next,
+    Confirm,
+    Back,
+}
+
+/// Maps a key press to a menu command. Input events are used (instead of window events) so
+/// that a key press is only ever seen once by a state.
+pub fn menu_command(event: &StateEvent) -> Option<MenuCommand> {
+    match event {
+        StateEvent::Input(InputEvent::ButtonPressed(Button::Key(key))) => match key {
+            VirtualKeyCode::Up => Some(MenuCommand::Previous),
+            VirtualKeyCode::Down => Some(MenuCommand::Next),
+            VirtualKeyCode::Return => Some(MenuCommand::Confirm),
+            VirtualKeyCode::Escape => Some(MenuCommand::Back),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// A title, a vertical list of selectable items and a footer line for messages.
+pub struct Menu {
+    title: Entity,
+    items: Vec<Entity>,
+    footer: Entity,
+    selected: usize,
+}
+
+impl Menu {
+    /// Index of the currently highlighted item.
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Highlights an item (the last one if there are fewer items).
+    pub fn select(&mut self, world: &mut World, index: usize) {
+        self.selected = index.min(self.items.len() - 1);
+        self.highlight(world);
+    }
+
+    pub fn select_previous(&mut self, world: &mut World) {
+        self.selected = (self.selected + self.items.len() - 1) % self.items.len();
+        self.highlight(world);
+    }
+
+    pub fn select_next(&mut self, world: &mut World) {
+        self.selected = (self.selected + 1) % self.items.len();
+        self.highlight(world);
+    }
+
+    pub fn set_item_text(&self, world: &mut World, index: usize, text: String) {
+        set_text(world, self.items[index], text);
+    }
+
+    pub fn set_footer(&self, world: &mut World, text: String) {
+        set_text(world, self.footer, text);
+    }
+
+    /// Removes the menu's UI entities from the world.
+    pub fn delete(self, world: &mut World) {
+        let mut entities = self.items;
+        entities.push(self.title);
+        entities.push(self.footer);
+
+        world
+            .delete_entities(&entities)
+            .expect("menu entities should still be alive");
+    }
+
+    fn highlight(&self, world: &mut World) {
+        let mut ui_text_storage = world.write_storage::<UiText>();
+
+        for (index, item) in self.items.iter().enumerate() {
+            if let Some(text) = ui_text_storage.get_mut(*item) {
+                text.color = if index == self.selected {
+                    SELECTED_ITEM_COLOR
+                } else {
+                    ITEM_COLOR
+                };
+            }
+        }
+    }
+}
+
+fn set_text(world: &mut World, entity: Entity, text: String) {
+    if let Some(ui_text) = world.write_storage::<UiText>().get_mut(entity) {
+        ui_text.text = text;
+    }
+}
+
+fn create_line(
+    world: &mut World,
+    font_handle: Handle<FontAsset>,
+    id: String,
+    y: f32,
+    line_height: f32,
+    font_size: f32,
+    text: String,
+) -> Entity {
+    let transform = UiTransform::new(
+        id,
+        Anchor::Middle,
+        Anchor::Middle,
+        0.0,
+        y,
+        1.0,
+        500.0,
+        line_height,
+    );
+
+    world
+        .create_entity()
+        .with(transform)
+        .with(UiText::new(
+            font_handle,
+            text,
+            ITEM_COLOR,
+            font_size,
+            LineMode::Single,
+            Anchor::Middle,
+        ))
+        .build()
+}
+
+/// Creates a menu centered on the screen with the first item selected. Lines get closer
+/// together when there are too many items to fit the window.
+pub fn initialize_menu(world: &mut World, title: &str, items: &[String]) -> Menu {
+    let font_handle = load_font_handle(SQUARE_FONT_PATH, world);
+    // title, a blank line, the items, a blank line and the footer
+    let line_height = LINE_HEIGHT.min(MENU_HEIGHT / (items.len() as f32 + 4.0));
+    let top = line_height * (items.len() as f32 * 0.5 + 2.0);
+
+    let title = create_line(
+        world,
+        font_handle.clone(),
+        "menu_title".to_string(),
+        top,
+        line_height,
+        30.,
+        title.to_string(),
+    );
+
+    let items = items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            create_line(
+                world,
+                font_handle.clone(),
+                format!("menu_item_{}", index),
+                top - line_height * (index as f32 + 2.0),
+                line_height,
+                20.,
+                item.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let footer = create_line(
+        world,
+        font_handle,
+        "menu_footer".to_string(),
+        top - line_height * (items.len() as f32 + 3.0),
+        line_height,
+        15.,
+        String::new(),
+    );
+
+    let menu = Menu {
+        title,
+        items,
+        footer,
+        selected: 0,
+    };
+    menu.highlight(world);
+
+    menu
+}
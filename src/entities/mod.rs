@@ -1,10 +1,43 @@
 mod ball;
+mod brick;
 mod camera;
+mod font;
+mod match_stats;
+mod menu;
+mod multi_ball;
+mod obstacle;
+mod overlay;
 mod paddle;
+mod physics;
+mod power_up;
+mod practice;
+mod ring;
 mod score;
+mod squash;
 
 // exposes Ball struct
-pub use ball::{initialize_ball, Ball};
+pub use ball::{initialize_ball, serve_ball, Ball, BallStorages};
+pub use brick::{initialize_bricks, spawn_brick, Brick, BRICK_SPRITE, CRACKED_BRICK_SPRITE};
 pub use camera::initialize_camera;
-pub use paddle::{initialize_paddles, Paddle, Side};
-pub use score::{intialize_scoreboard, ScoreText};
+pub use match_stats::MatchStats;
+pub use menu::{initialize_menu, menu_command, Menu, MenuCommand};
+pub use multi_ball::MultiBall;
+pub use obstacle::{initialize_level, Obstacle, ObstacleMotion, ObstacleShape, Portal};
+pub use overlay::initialize_overlay;
+pub use paddle::{
+    apply_handicaps, initialize_mallets, initialize_paddles, Mallet, Paddle, PaddleDriver, Side,
+};
+pub use physics::{
+    initialize_physics, predict_path, ArenaPhysics, Force, TrajectoryPreview, PREDICTION_STEP,
+};
+pub use power_up::{
+    initialize_power_ups, pick_rule, spawn_pickup, ActiveEffect, HudSlot, PickupStorages, PowerUp,
+    PowerUpKind, PowerUps, StuckBall, STICKY_HOLD_SECONDS,
+};
+pub use practice::{initialize_practice, shot_velocity, zone_points, PracticeSession, ShotResult};
+pub use ring::{
+    boundary_bounce, initialize_ring, restore_ring_lives, ring_angle, seat_label, Ring, RingArc,
+    GOAL_TINT, RING_CENTER, WALL_TINT,
+};
+pub use score::{intialize_scoreboard, ScoreText, Scoreboard};
+pub use squash::{speed_up, Squash};
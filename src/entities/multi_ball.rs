@@ -0,0 +1,144 @@
+use amethyst::{assets::Handle, renderer::SpriteSheet};
+
+use crate::settings::{MultiBallConfig, BALL_SPAWN_DELAY, BALL_VELOCITY_X, BALL_VELOCITY_Y};
+
+/// Directions the balls are served in, in turn, so that they don't all follow the same path.
+const SERVE_DIRECTIONS: [[f32; 2]; 4] = [[1.0, 1.0], [-1.0, -1.0], [-1.0, 1.0], [1.0, -1.0]];
+
+/// Resource present in multi-ball matches: balls leave the arena when they go into a goal and
+/// the MultiBallSystem serves new ones, both when none are left and during rallies.
+pub struct MultiBall {
+    sprite_sheet_handle: Option<Handle<SpriteSheet>>,
+    serve_timer: f32, // seconds without any ball in play
+    spawn_timer: f32, // seconds since the last ball was served
+    hits: u32,        // paddle hits since the last ball was served
+    served: usize,
+}
+
+impl MultiBall {
+    pub fn new(sprite_sheet_handle: Option<Handle<SpriteSheet>>) -> Self {
+        MultiBall {
+            sprite_sheet_handle,
+            serve_timer: 0.0,
+            spawn_timer: 0.0,
+            hits: 0,
+            served: 0,
+        }
+    }
+
+    /// Sprite sheet of the served balls (none when running headless).
+    pub fn sprite_sheet_handle(&self) -> Option<Handle<SpriteSheet>> {
+        self.sprite_sheet_handle.clone()
+    }
+
+    /// Counts a paddle hitting one of the balls.
+    pub fn count_hit(&mut self) {
+        self.hits += 1;
+    }
+
+    /// Advances the timers by a frame and returns whether a ball should be served now: after
+    /// the usual delay when there's no ball left, or when an extra ball is due.
+    pub fn should_serve(
+        &mut self,
+        ball_count: usize,
+        config: &MultiBallConfig,
+        delta_seconds: f32,
+    ) -> bool {
+        if ball_count == 0 {
+            self.serve_timer += delta_seconds;
+            self.spawn_timer = 0.0;
+            self.hits = 0;
+
+            if self.serve_timer < BALL_SPAWN_DELAY {
+                return false;
+            }
+
+            self.serve_timer = 0.0;
+
+            return true;
+        }
+
+        self.serve_timer = 0.0;
+
+        // no extra balls are due while the arena is full
+        if ball_count >= config.max_balls {
+            self.spawn_timer = 0.0;
+            self.hits = 0;
+
+            return false;
+        }
+
+        self.spawn_timer += delta_seconds;
+
+        let timer_due = config
+            .spawn_interval
+            .map_or(false, |interval| self.spawn_timer >= interval);
+        let hits_due = config.hits_per_ball.map_or(false, |hits| self.hits >= hits);
+
+        if timer_due || hits_due {
+            self.spawn_timer = 0.0;
+            self.hits = 0;
+        }
+
+        timer_due || hits_due
+    }
+
+    /// Velocity of the next ball to serve.
+    pub fn next_serve_velocity(&mut self) -> [f32; 2] {
+        let direction = SERVE_DIRECTIONS[self.served % SERVE_DIRECTIONS.len()];
+
+        self.served += 1;
+
+        [
+            direction[0] * BALL_VELOCITY_X,
+            direction[1] * BALL_VELOCITY_Y,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_serve_after_delay_when_no_ball_is_left() {
+        // arrange
+        let mut multi_ball = MultiBall::new(None);
+        let config = MultiBallConfig::default();
+
+        // act
+        let served_early = multi_ball.should_serve(0, &config, BALL_SPAWN_DELAY * 0.5);
+        let served_after_delay = multi_ball.should_serve(0, &config, BALL_SPAWN_DELAY * 0.5);
+
+        // assert
+        assert_eq!(false, served_early);
+        assert_eq!(true, served_after_delay);
+    }
+
+    #[test]
+    fn should_serve_extra_ball_after_hits_unless_arena_is_full() {
+        // arrange
+        let mut multi_ball = MultiBall::new(None);
+        let config = MultiBallConfig {
+            spawn_interval: None,
+            hits_per_ball: Some(2),
+            max_balls: 2,
+            ball_collisions: false,
+        };
+
+        multi_ball.count_hit();
+        multi_ball.count_hit();
+
+        // act
+        let served = multi_ball.should_serve(1, &config, 0.1);
+
+        multi_ball.count_hit();
+        multi_ball.count_hit();
+
+        let served_when_full = multi_ball.should_serve(2, &config, 0.1);
+
+        // assert
+        assert_eq!(true, served);
+        assert_eq!(false, served_when_full);
+    }
+}
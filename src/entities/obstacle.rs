@@ -0,0 +1,253 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use amethyst::{
+    assets::Handle,
+    core::{math::Vector3, Transform},
+    ecs::{Component, DenseVecStorage, EntityBuilder},
+    prelude::{Builder, WorldExt},
+    renderer::{palette::Srgba, resources::Tint, SpriteRender, SpriteSheet},
+    shred::World,
+};
+use serde::{Deserialize, Serialize};
+
+use super::Side;
+use crate::settings::{
+    GameMode, Level, ARENA_HEIGHT, ARENA_WIDTH, BALL_RADIUS, PADDLE_HEIGHT, PADDLE_WIDTH,
+};
+
+const OBSTACLE_COLOR: [f32; 4] = [0.6, 0.6, 0.6, 1.0];
+const PORTAL_COLOR: [f32; 4] = [0.7, 0.3, 1.0, 0.8];
+
+/// Thickness of the walls beside narrowed goals.
+const GOAL_WALL_THICKNESS: f32 = 1.0;
+
+/// Shape of an obstacle, centered on its position.
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize, Serialize)]
+pub enum ObstacleShape {
+    Rectangle { width: f32, height: f32 },
+    Circle { radius: f32 },
+}
+
+/// Back and forth movement of a moving obstacle, eased at both ends.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ObstacleMotion {
+    pub from: [f32; 2],
+    pub to: [f32; 2],
+    pub period: f32,  // seconds to go there and back
+    pub elapsed: f32, // seconds since the level started
+}
+
+impl ObstacleMotion {
+    /// Position and velocity of the obstacle at the current time.
+    pub fn state(&self) -> ([f32; 2], [f32; 2]) {
+        let angle = 2.0 * PI * self.elapsed / self.period;
+        let progress = (1.0 - angle.cos()) * 0.5;
+        let speed = angle.sin() * PI / self.period;
+
+        let mut position = self.from;
+        let mut velocity = [0.0, 0.0];
+
+        for axis in 0..2 {
+            let distance = self.to[axis] - self.from[axis];
+
+            position[axis] += distance * progress;
+            velocity[axis] = distance * speed;
+        }
+
+        (position, velocity)
+    }
+}
+
+/// Static or moving obstacle the balls bounce off.
+pub struct Obstacle {
+    pub shape: ObstacleShape,
+    pub motion: Option<ObstacleMotion>,
+    pub velocity: [f32; 2], // of moving obstacles, in arena units per second
+}
+
+impl Component for Obstacle {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Portal sending the balls going into it to the position of its twin.
+pub struct Portal {
+    pub exit: [f32; 2],
+    pub radius: f32,
+}
+
+impl Component for Portal {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Creates the obstacles, portals and goal walls of a level and inserts the level as a resource
+/// (read by the collision and scoring systems).
+pub fn initialize_level(
+    world: &mut World,
+    level: Level,
+    mode: GameMode,
+    sprite_sheet_handle: Option<Handle<SpriteSheet>>,
+) {
+    for layout in level.obstacles.iter() {
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(layout.position[0], layout.position[1], 0.0);
+
+        // paddles are stretched into rectangles and balls into circles
+        let (sprite_number, scale) = match layout.shape {
+            ObstacleShape::Rectangle { width, height } => {
+                (0, [width / PADDLE_WIDTH, height / PADDLE_HEIGHT])
+            }
+            ObstacleShape::Circle { radius } => (1, [radius / BALL_RADIUS; 2]),
+        };
+        transform.set_scale(Vector3::new(scale[0], scale[1], 1.0));
+
+        let obstacle = Obstacle {
+            shape: layout.shape,
+            motion: layout.path.as_ref().map(|path| ObstacleMotion {
+                from: layout.position,
+                to: path.to,
+                period: path.period,
+                elapsed: 0.0,
+            }),
+            velocity: [0.0, 0.0],
+        };
+
+        build_scenery(
+            world,
+            transform,
+            sprite_number,
+            OBSTACLE_COLOR,
+            sprite_sheet_handle.clone(),
+        )
+        .with(obstacle)
+        .build();
+    }
+
+    for layout in level.portals.iter() {
+        for (entrance, exit) in [(layout.a, layout.b), (layout.b, layout.a)].iter() {
+            let mut transform = Transform::default();
+            let scale = layout.radius / BALL_RADIUS;
+
+            transform.set_translation_xyz(entrance[0], entrance[1], 0.0);
+            transform.set_scale(Vector3::new(scale, scale, 1.0));
+
+            let portal = Portal {
+                exit: *exit,
+                radius: layout.radius,
+            };
+
+            build_scenery(
+                world,
+                transform,
+                1,
+                PORTAL_COLOR,
+                sprite_sheet_handle.clone(),
+            )
+            .with(portal)
+            .build();
+        }
+    }
+
+    // narrowed goals: the walls beside them are only drawn, the ball bounces off the edge
+    if let Some(goal_size) = level.goal_size {
+        for side in mode.sides() {
+            let edge_length = if side.is_horizontal() {
+                ARENA_WIDTH
+            } else {
+                ARENA_HEIGHT
+            };
+            let wall_length = ((edge_length - goal_size) * 0.5).max(0.0);
+
+            for center in [wall_length * 0.5, edge_length - wall_length * 0.5].iter() {
+                let transform = goal_wall_transform(*side, *center, wall_length);
+
+                build_scenery(
+                    world,
+                    transform,
+                    0,
+                    OBSTACLE_COLOR,
+                    sprite_sheet_handle.clone(),
+                )
+                .build();
+            }
+        }
+    }
+
+    world.insert(level);
+}
+
+/// Entity drawn with a tinted sprite (nothing is drawn when running headless).
+fn build_scenery(
+    world: &mut World,
+    transform: Transform,
+    sprite_number: usize,
+    color: [f32; 4],
+    sprite_sheet_handle: Option<Handle<SpriteSheet>>,
+) -> EntityBuilder<'_> {
+    let [red, green, blue, alpha] = color;
+
+    let builder = world
+        .create_entity()
+        .with(transform)
+        .with(Tint(Srgba::new(red, green, blue, alpha)));
+
+    match sprite_sheet_handle {
+        Some(handle) => builder.with(SpriteRender::new(handle, sprite_number)),
+        None => builder,
+    }
+}
+
+/// Transform of a wall along a goal's edge, given its center along the edge.
+fn goal_wall_transform(side: Side, center: f32, length: f32) -> Transform {
+    let mut transform = Transform::default();
+    let depth = GOAL_WALL_THICKNESS * 0.5;
+
+    let (x, y) = match side {
+        Side::Left => (depth, center),
+        Side::Right => (ARENA_WIDTH - depth, center),
+        Side::Top => (center, ARENA_HEIGHT - depth),
+        Side::Bottom => (center, depth),
+    };
+    transform.set_translation_xyz(x, y, 0.0);
+
+    // the paddle sprite is upright
+    if side.is_horizontal() {
+        transform.set_rotation_2d(FRAC_PI_2);
+    }
+
+    transform.set_scale(Vector3::new(
+        GOAL_WALL_THICKNESS / PADDLE_WIDTH,
+        length / PADDLE_HEIGHT,
+        1.0,
+    ));
+
+    transform
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_move_obstacle_there_and_back() {
+        // arrange
+        let mut motion = ObstacleMotion {
+            from: [50.0, 20.0],
+            to: [50.0, 80.0],
+            period: 4.0,
+            elapsed: 0.0,
+        };
+
+        // act
+        let (start, start_velocity) = motion.state();
+        motion.elapsed = 2.0; // half a period
+        let (halfway, _) = motion.state();
+        motion.elapsed = 1.0; // a quarter: full speed towards `to`
+        let (_, quarter_velocity) = motion.state();
+
+        // assert
+        assert_eq!([50.0, 20.0], start);
+        assert_eq!([0.0, 0.0], start_velocity);
+        assert_eq!([50.0, 80.0], halfway);
+        assert!(quarter_velocity[1] > 0.0);
+    }
+}
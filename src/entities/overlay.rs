@@ -0,0 +1,38 @@
+use amethyst::{
+    ecs::Entity,
+    prelude::{Builder, WorldExt},
+    shred::World,
+    ui::{Anchor, LineMode, UiText, UiTransform},
+};
+
+use super::font::load_font_handle;
+use crate::settings::SQUARE_FONT_PATH;
+
+/// Creates small text lines at the bottom of the screen, used for stats overlays.
+pub fn initialize_overlay(world: &mut World) -> Entity {
+    let font_handle = load_font_handle(SQUARE_FONT_PATH, world);
+
+    let transform = UiTransform::new(
+        "overlay".to_string(),
+        Anchor::BottomMiddle,
+        Anchor::BottomMiddle,
+        0.0,
+        10.0,
+        1.0,
+        480.0,
+        36.0,
+    );
+
+    world
+        .create_entity()
+        .with(transform)
+        .with(UiText::new(
+            font_handle,
+            String::new(),
+            [0.6, 0.6, 0.6, 1.],
+            12.,
+            LineMode::Wrap,
+            Anchor::Middle,
+        ))
+        .build()
+}
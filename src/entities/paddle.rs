@@ -1,37 +1,174 @@
+use std::f32::consts::FRAC_PI_2;
+
 use amethyst::{
     assets::Handle,
-    core::Transform,
-    ecs::{Component, DenseVecStorage},
+    core::{math::Vector3, Transform},
+    ecs::{Component, DenseVecStorage, Join},
     prelude::{Builder, WorldExt},
     renderer::{SpriteRender, SpriteSheet},
     shred::World,
 };
+use serde::{Deserialize, Serialize};
 
-use crate::settings::{ARENA_HEIGHT, ARENA_WIDTH, PADDLE_HEIGHT, PADDLE_WIDTH};
+use super::RingArc;
+use crate::settings::{
+    AirHockeyConfig, Handicap, Handicaps, Lineup, ARENA_HEIGHT, ARENA_WIDTH, BALL_RADIUS,
+    PADDLE_HEIGHT, PADDLE_WIDTH,
+};
 
-/// Enum used to identify the left/right paddles.
-#[derive(PartialEq, Eq, Debug)]
+/// Enum used to identify the paddles by the arena edge they defend.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Deserialize, Serialize)]
 pub enum Side {
     Left,
     Right,
+    Top,
+    Bottom,
+}
+
+impl Side {
+    /// Every side, in player order (P1 to P4).
+    pub const ALL: [Side; 4] = [Side::Left, Side::Right, Side::Top, Side::Bottom];
+
+    /// Player name shown on screen.
+    pub fn player_label(self) -> &'static str {
+        match self {
+            Side::Left => "P1",
+            Side::Right => "P2",
+            Side::Top => "P3",
+            Side::Bottom => "P4",
+        }
+    }
+
+    /// Side across the arena.
+    pub fn opponent(self) -> Side {
+        match self {
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
+            Side::Top => Side::Bottom,
+            Side::Bottom => Side::Top,
+        }
+    }
+
+    /// Index of the axis (0 for x, 1 for y) the ball crosses this side's edge along.
+    pub fn axis(self) -> usize {
+        match self {
+            Side::Left | Side::Right => 0,
+            Side::Top | Side::Bottom => 1,
+        }
+    }
+
+    /// Sign of the direction leaving the arena through this side's edge.
+    pub fn outwards(self) -> f32 {
+        match self {
+            Side::Left | Side::Bottom => -1.0,
+            Side::Right | Side::Top => 1.0,
+        }
+    }
+
+    /// Whether the paddle lies along the top/bottom edge, moving along x instead of y.
+    pub fn is_horizontal(self) -> bool {
+        self.axis() == 1
+    }
+}
+
+/// Who moves a paddle.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub enum PaddleDriver {
+    /// The player using the controls (keys, controller or mouse) of the given side.
+    Player(Side),
+    /// The computer.
+    Cpu,
+}
+
+/// Round paddle of air-hockey matches, which also moves across its edge within its half of the
+/// arena.
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize, Serialize)]
+pub struct Mallet {
+    pub radius: f32,
+    pub velocity: f32, // across its edge (x for left/right mallets) in arena units per second
 }
 
 /// Paddle component struct.
-#[derive(Debug)]
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
 pub struct Paddle {
-    pub side: Side,
+    pub side: Side, // team (and goal) the paddle plays for
+    pub driver: PaddleDriver,
     pub width: f32,
     pub height: f32,
+    pub velocity: f32, // along its edge (x for top/bottom paddles) in arena units per second
+    pub arc: Option<RingArc>, // moves along this arc instead of an edge (ring matches)
+    pub mallet: Option<Mallet>, // round and also moving across its edge (air-hockey matches)
+    pub handicap: Handicap, // of the player driving it
 }
 
 impl Paddle {
-    fn new(side: Side) -> Paddle {
+    pub fn new(side: Side) -> Paddle {
+        // top/bottom paddles are lying down
+        let (width, height) = if side.is_horizontal() {
+            (PADDLE_HEIGHT, PADDLE_WIDTH)
+        } else {
+            (PADDLE_WIDTH, PADDLE_HEIGHT)
+        };
+
         Paddle {
             side,
-            width: PADDLE_WIDTH,
-            height: PADDLE_HEIGHT,
+            driver: PaddleDriver::Player(side),
+            width,
+            height,
+            velocity: 0.0,
+            arc: None,
+            mallet: None,
+            handicap: Handicap::default(),
         }
     }
+
+    /// Paddle with a player's handicap: its length is scaled by it (mallets keep their size).
+    pub fn with_handicap(self, handicap: Handicap) -> Paddle {
+        let mut paddle = Paddle { handicap, ..self };
+
+        if paddle.mallet.is_none() {
+            paddle.set_length(paddle.base_length());
+        }
+
+        paddle
+    }
+
+    /// Length of the paddle without power-ups, given its handicap.
+    pub fn base_length(&self) -> f32 {
+        PADDLE_HEIGHT * self.handicap.paddle_length
+    }
+
+    /// Resizes the paddle along its edge (or arc).
+    pub fn set_length(&mut self, length: f32) {
+        if self.side.is_horizontal() {
+            self.width = length;
+        } else {
+            self.height = length;
+        }
+    }
+
+    /// Air-hockey mallet of the given radius.
+    pub fn mallet(side: Side, radius: f32) -> Paddle {
+        Paddle {
+            width: radius * 2.0,
+            height: radius * 2.0,
+            mallet: Some(Mallet {
+                radius,
+                velocity: 0.0,
+            }),
+            ..Paddle::new(side)
+        }
+    }
+
+    /// Velocity in arena x and y: along its edge, and across it for mallets.
+    pub fn velocity_2d(&self) -> [f32; 2] {
+        let mut velocity = [0.0, 0.0];
+
+        velocity[1 - self.side.axis()] = self.velocity;
+        velocity[self.side.axis()] = self.mallet.map_or(0.0, |mallet| mallet.velocity);
+
+        velocity
+    }
 }
 
 // This adds the 'Component' behavior to the Paddle
@@ -39,34 +176,102 @@ impl Component for Paddle {
     type Storage = DenseVecStorage<Self>; // Component<Paddle>
 }
 
-/// Creates the left and right paddles at their starting position and attaches them
-/// to the World object.
-pub fn initialize_paddles(world: &mut World, sprite_sheet_handle: Handle<SpriteSheet>) {
-    let mut left_paddle_transform = Transform::default();
-    let mut right_paddle_transform = Transform::default();
-    let starting_y = ARENA_HEIGHT / 2.0;
-
-    // SpriteRender is a component that is a 'slice' of a spritesheet
-    let paddle_left = SpriteRender::new(sprite_sheet_handle, 0); // paddle is the first sprite
-    let paddle_right = paddle_left.clone();
-
-    // coordinate transforms to position the paddles
-    left_paddle_transform.set_translation_xyz(PADDLE_WIDTH * 0.5, starting_y, 0.0);
-    right_paddle_transform.set_translation_xyz(ARENA_WIDTH - PADDLE_WIDTH * 0.5, starting_y, 0.0);
-
-    // left paddle creation
-    world
-        .create_entity()
-        .with(Paddle::new(Side::Left)) // component used in ReadStorage
-        .with(paddle_left)
-        .with(left_paddle_transform)
-        .build();
-
-    // right paddle creation (entity + component<state> in ECS)
-    world
-        .create_entity()
-        .with(Paddle::new(Side::Right))
-        .with(paddle_right) // sprite renderer
-        .with(right_paddle_transform)
-        .build();
+/// Creates the paddles of a lineup at their starting position and attaches them to the World
+/// object. Without a sprite sheet (headless server) the paddles aren't rendered.
+pub fn initialize_paddles(
+    world: &mut World,
+    sprite_sheet_handle: Option<Handle<SpriteSheet>>,
+    lineup: &Lineup,
+) {
+    for placement in lineup.paddles.iter() {
+        let side = placement.side;
+        let mut transform = Transform::default();
+
+        // coordinate transforms to position the paddles in the middle of their edge, at their
+        // depth (distance from their goal line)
+        let (x, y) = match side {
+            Side::Left => (placement.depth, ARENA_HEIGHT / 2.0),
+            Side::Right => (ARENA_WIDTH - placement.depth, ARENA_HEIGHT / 2.0),
+            Side::Top => (ARENA_WIDTH / 2.0, ARENA_HEIGHT - placement.depth),
+            Side::Bottom => (ARENA_WIDTH / 2.0, placement.depth),
+        };
+        transform.set_translation_xyz(x, y, 0.0);
+
+        // the paddle sprite is upright
+        if side.is_horizontal() {
+            transform.set_rotation_2d(FRAC_PI_2);
+        }
+
+        let paddle = Paddle {
+            driver: placement.driver,
+            ..Paddle::new(side)
+        };
+
+        // paddle creation (entity + component<state> in ECS)
+        let mut paddle_builder = world.create_entity().with(paddle).with(transform);
+
+        // SpriteRender is a component that is a 'slice' of a spritesheet
+        if let Some(handle) = sprite_sheet_handle.clone() {
+            paddle_builder = paddle_builder.with(SpriteRender::new(handle, 0)); // paddle is the first sprite
+        }
+
+        paddle_builder.build();
+    }
+}
+
+/// Gives the paddles driven by players their player's handicap, resizing them along with their
+/// sprite.
+pub fn apply_handicaps(world: &mut World, handicaps: &Handicaps) {
+    let mut paddle_storage = world.write_storage::<Paddle>();
+    let mut transform_storage = world.write_storage::<Transform>();
+
+    for (paddle, transform) in (&mut paddle_storage, &mut transform_storage).join() {
+        let player = match paddle.driver {
+            PaddleDriver::Player(player) => player,
+            PaddleDriver::Cpu => continue,
+        };
+
+        *paddle = paddle.clone().with_handicap(handicaps.get(player));
+
+        // the sprite's height is the paddle's length, even when rotated
+        if paddle.mallet.is_none() {
+            transform.set_scale(Vector3::new(1.0, paddle.handicap.paddle_length, 1.0));
+        }
+    }
+}
+
+/// Creates the mallets of an air-hockey lineup in front of their goal, at the configured depth,
+/// and attaches them to the World object. Mallets are drawn with the (scaled) ball sprite.
+pub fn initialize_mallets(
+    world: &mut World,
+    sprite_sheet_handle: Option<Handle<SpriteSheet>>,
+    lineup: &Lineup,
+    config: &AirHockeyConfig,
+) {
+    for placement in lineup.paddles.iter() {
+        let side = placement.side;
+        let mut transform = Transform::default();
+
+        let x = match side {
+            Side::Right => ARENA_WIDTH - config.mallet_depth,
+            _ => config.mallet_depth,
+        };
+        transform.set_translation_xyz(x, ARENA_HEIGHT / 2.0, 0.0);
+
+        let scale = config.mallet_radius / BALL_RADIUS;
+        transform.set_scale(Vector3::new(scale, scale, 1.0));
+
+        let paddle = Paddle {
+            driver: placement.driver,
+            ..Paddle::mallet(side, config.mallet_radius)
+        };
+
+        let mut mallet_builder = world.create_entity().with(paddle).with(transform);
+
+        if let Some(handle) = sprite_sheet_handle.clone() {
+            mallet_builder = mallet_builder.with(SpriteRender::new(handle, 1)); // ball sprite
+        }
+
+        mallet_builder.build();
+    }
 }
@@ -0,0 +1,247 @@
+use std::f32::consts::PI;
+
+use amethyst::{
+    assets::Handle,
+    core::{math::Vector3, Transform},
+    ecs::Entity,
+    prelude::{Builder, WorldExt},
+    renderer::{palette::Srgba, resources::Tint, SpriteRender, SpriteSheet},
+    shred::World,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::settings::{
+    GameplayConfig, PhysicsConfig, WindConfig, ARENA_HEIGHT, ARENA_WIDTH, BALL_VELOCITY_X,
+    BALL_VELOCITY_Y,
+};
+
+/// Dots of the trajectory preview.
+const PREVIEW_DOTS: usize = 15;
+const PREVIEW_DOT_SCALE: f32 = 0.3;
+
+/// Step used to predict the balls' paths, in seconds.
+pub const PREDICTION_STEP: f32 = 1.0 / 60.0;
+
+/// Force acting on the balls, turned on from the command line.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub enum Force {
+    Gravity,
+    Wind,
+    Drag,
+}
+
+/// Resource present when any of the arena physics is on: the forces acting on the balls and
+/// the time they've been acting for (the wind gusts depend on it).
+#[derive(Clone, Default, Debug)]
+pub struct ArenaPhysics {
+    gravity: Option<f32>,
+    wind: Option<WindConfig>,
+    drag: Option<f32>,
+    pub elapsed: f32,
+}
+
+impl ArenaPhysics {
+    pub fn new(config: &PhysicsConfig, forces: &[Force]) -> Self {
+        ArenaPhysics {
+            gravity: Some(config.gravity).filter(|_| forces.contains(&Force::Gravity)),
+            wind: Some(config.wind.clone()).filter(|_| forces.contains(&Force::Wind)),
+            drag: Some(config.drag).filter(|_| forces.contains(&Force::Drag)),
+            elapsed: 0.0,
+        }
+    }
+
+    /// Acceleration of the wind along x at the given time: gusts rise and fall smoothly.
+    pub fn wind(&self, elapsed: f32) -> f32 {
+        let wind = match self.wind.as_ref() {
+            Some(wind) if wind.interval > 0.0 && wind.gust > 0.0 => wind,
+            _ => return 0.0,
+        };
+
+        let phase = elapsed % wind.interval;
+
+        if phase >= wind.gust {
+            return 0.0;
+        }
+
+        let direction = if (elapsed / wind.interval) as u32 % 2 == 0 {
+            1.0
+        } else {
+            -1.0
+        };
+
+        direction * wind.strength * (PI * phase / wind.gust).sin()
+    }
+
+    /// Ball's velocity after a step of the simulation starting at the given time.
+    pub fn step_velocity(&self, velocity: [f32; 2], elapsed: f32, delta_seconds: f32) -> [f32; 2] {
+        let mut velocity = [
+            velocity[0] + self.wind(elapsed) * delta_seconds,
+            velocity[1] - self.gravity.unwrap_or(0.0) * delta_seconds,
+        ];
+
+        // only slows the ball down to the serve speed, so that it never stalls
+        if let Some(drag) = self.drag {
+            let serve_speed = BALL_VELOCITY_X.hypot(BALL_VELOCITY_Y);
+            let speed = velocity[0].hypot(velocity[1]);
+
+            if speed > serve_speed {
+                let dragged = serve_speed + (speed - serve_speed) * (-drag * delta_seconds).exp();
+
+                velocity = [velocity[0] * dragged / speed, velocity[1] * dragged / speed];
+            }
+        }
+
+        velocity
+    }
+}
+
+/// Positions of a ball over the next steps of the simulation, under the arena's forces (if
+/// any). The ball bounces off the edges along `bounce_axis` and the prediction stops once it
+/// leaves the arena along the other axis.
+pub fn predict_path(
+    physics: Option<&ArenaPhysics>,
+    position: [f32; 2],
+    velocity: [f32; 2],
+    radius: f32,
+    bounce_axis: Option<usize>,
+    steps: usize,
+) -> Vec<[f32; 2]> {
+    let arena_size = [ARENA_WIDTH, ARENA_HEIGHT];
+    let mut elapsed = physics.map_or(0.0, |physics| physics.elapsed);
+    let mut position = position;
+    let mut velocity = velocity;
+    let mut path = Vec::with_capacity(steps);
+
+    for _ in 0..steps {
+        // same integration as the BallSystem: velocity first, then position
+        if let Some(physics) = physics {
+            velocity = physics.step_velocity(velocity, elapsed, PREDICTION_STEP);
+        }
+        elapsed += PREDICTION_STEP;
+
+        for axis in 0..2 {
+            position[axis] += velocity[axis] * PREDICTION_STEP;
+        }
+
+        if let Some(axis) = bounce_axis {
+            let heading_out = (position[axis] < radius && velocity[axis] < 0.0)
+                || (position[axis] > arena_size[axis] - radius && velocity[axis] > 0.0);
+
+            if heading_out {
+                velocity[axis] *= -1.0;
+            }
+        }
+
+        let outside = (0..2).any(|axis| position[axis] < 0.0 || position[axis] > arena_size[axis]);
+
+        if outside {
+            break;
+        }
+
+        path.push(position);
+    }
+
+    path
+}
+
+/// Dots showing the predicted path of the ball, moved by the TrajectorySystem.
+pub struct TrajectoryPreview {
+    pub dots: Vec<Entity>,
+    /// Seconds of the path shown.
+    pub seconds: f32,
+}
+
+/// Inserts the ArenaPhysics resource and, if configured, the trajectory preview dots.
+pub fn initialize_physics(
+    world: &mut World,
+    forces: &[Force],
+    sprite_sheet_handle: Option<Handle<SpriteSheet>>,
+) {
+    let config = world.read_resource::<GameplayConfig>().physics.clone();
+
+    world.insert(ArenaPhysics::new(&config, forces));
+
+    let seconds = match config.preview_seconds {
+        Some(seconds) => seconds,
+        None => return,
+    };
+
+    let dots = (0..PREVIEW_DOTS)
+        .map(|_| {
+            let mut transform = Transform::default();
+
+            // out of sight until there's a ball to preview
+            transform.set_translation_xyz(-ARENA_WIDTH, -ARENA_HEIGHT, 0.0);
+            transform.set_scale(Vector3::new(PREVIEW_DOT_SCALE, PREVIEW_DOT_SCALE, 1.0));
+
+            let builder = world
+                .create_entity()
+                .with(transform)
+                .with(Tint(Srgba::new(1.0, 1.0, 1.0, 0.4)));
+
+            // small balls (nothing is drawn when running headless)
+            match sprite_sheet_handle.clone() {
+                Some(handle) => builder.with(SpriteRender::new(handle, 1)),
+                None => builder,
+            }
+            .build()
+        })
+        .collect();
+
+    world.insert(TrajectoryPreview { dots, seconds });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn physics(forces: &[Force]) -> ArenaPhysics {
+        ArenaPhysics::new(&PhysicsConfig::default(), forces)
+    }
+
+    #[test]
+    fn should_blow_gusts_in_alternating_directions() {
+        // arrange - gusts of 3 seconds every 8 seconds
+        let physics = physics(&[Force::Wind]);
+
+        // act
+        let first_gust = physics.wind(1.5);
+        let calm = physics.wind(5.0);
+        let second_gust = physics.wind(9.5);
+
+        // assert
+        assert_eq!(30.0, first_gust);
+        assert_eq!(0.0, calm);
+        assert_eq!(-30.0, second_gust);
+    }
+
+    #[test]
+    fn should_slow_ball_down_to_serve_speed_only() {
+        // arrange
+        let physics = physics(&[Force::Drag]);
+
+        // act
+        let fast = physics.step_velocity([150.0, 0.0], 0.0, 1.0);
+        let serve = physics.step_velocity([BALL_VELOCITY_X, BALL_VELOCITY_Y], 0.0, 1.0);
+
+        // assert
+        assert!(fast[0] < 150.0 && fast[0] > BALL_VELOCITY_X.hypot(BALL_VELOCITY_Y));
+        assert_eq!([BALL_VELOCITY_X, BALL_VELOCITY_Y], serve);
+    }
+
+    #[test]
+    fn should_predict_ball_arcing_under_gravity() {
+        // arrange - ball thrown right and slightly up from the left half
+        let physics = physics(&[Force::Gravity]);
+
+        // act
+        let path = predict_path(Some(&physics), [20.0, 50.0], [75.0, 20.0], 2.0, Some(1), 60);
+        let straight = predict_path(None, [20.0, 50.0], [75.0, 20.0], 2.0, Some(1), 60);
+
+        // assert - a second later the ball fell below its straight line path
+        let (arc_end, straight_end) = (path.last().unwrap(), straight.last().unwrap());
+        assert_eq!(60, path.len());
+        assert!((arc_end[0] - straight_end[0]).abs() < 0.001);
+        assert!(arc_end[1] < straight_end[1] - 15.0);
+    }
+}
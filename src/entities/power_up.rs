@@ -0,0 +1,504 @@
+use std::collections::HashMap;
+
+use amethyst::{
+    assets::Handle,
+    core::{math::Vector3, Hidden, Transform},
+    ecs::{Component, DenseVecStorage, Entities, Entity, WriteStorage},
+    prelude::{Builder, WorldExt},
+    renderer::{palette::Srgba, resources::Tint, SpriteRender, SpriteSheet},
+    shred::World,
+    ui::{Anchor, LineMode, UiImage, UiText, UiTransform},
+};
+use serde::{Deserialize, Serialize};
+
+use super::{font::load_font_handle, score::score_position, Side};
+use crate::settings::{
+    GameMode, PowerUpConfig, PowerUpRule, Stacking, BALL_RADIUS, POWER_UP_RADIUS, SQUARE_FONT_PATH,
+};
+
+/// Paddle length multiplier per stack of enlarge (or shrink) paddle.
+const ENLARGE_FACTOR: f32 = 1.5;
+const SHRINK_FACTOR: f32 = 0.6;
+
+/// Ball speed multiplier of the speed (or slow) ball power-up.
+const SPEED_FACTOR: f32 = 1.5;
+const SLOW_FACTOR: f32 = 0.6;
+
+/// Seconds a ball stays on a sticky paddle before being sent back.
+pub const STICKY_HOLD_SECONDS: f32 = 0.75;
+
+/// Icons shown per player: one for each kind of power-up.
+const HUD_SLOTS: usize = 8;
+
+/// Size of the HUD icons and space between them, in pixels.
+const ICON_SIZE: f32 = 16.0;
+const ICON_SPACING: f32 = 30.0;
+
+/// What a power-up does when claimed.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Deserialize, Serialize)]
+pub enum PowerUpKind {
+    EnlargePaddle,
+    /// Shrinks the opponents' paddles.
+    ShrinkPaddle,
+    /// Speeds up every ball.
+    SpeedBall,
+    /// Slows down every ball.
+    SlowBall,
+    /// Serves an extra ball right away.
+    MultiBall,
+    /// Balls stick to the paddle for a moment before being sent back.
+    StickyPaddle,
+    /// A wall behind the paddle keeps the ball out of the goal.
+    ShieldWall,
+    /// Reverses the opponents' controls.
+    ReverseControls,
+}
+
+impl PowerUpKind {
+    /// Sprite of the HUD icon, drawn in white and tinted with the power-up's color.
+    pub fn icon(self) -> usize {
+        match self {
+            PowerUpKind::EnlargePaddle => 4,
+            PowerUpKind::ShrinkPaddle => 5,
+            PowerUpKind::SpeedBall => 6,
+            PowerUpKind::SlowBall => 7,
+            PowerUpKind::MultiBall => 8,
+            PowerUpKind::StickyPaddle => 9,
+            PowerUpKind::ShieldWall => 10,
+            PowerUpKind::ReverseControls => 11,
+        }
+    }
+
+    /// Color of the pickup and of its HUD icon.
+    pub fn color(self) -> [f32; 4] {
+        match self {
+            PowerUpKind::EnlargePaddle => [0.2, 1.0, 0.2, 1.0],
+            PowerUpKind::ShrinkPaddle => [1.0, 0.3, 0.3, 1.0],
+            PowerUpKind::SpeedBall => [1.0, 0.6, 0.0, 1.0],
+            PowerUpKind::SlowBall => [0.3, 0.6, 1.0, 1.0],
+            PowerUpKind::MultiBall => [1.0, 1.0, 1.0, 1.0],
+            PowerUpKind::StickyPaddle => [1.0, 1.0, 0.2, 1.0],
+            PowerUpKind::ShieldWall => [0.2, 1.0, 1.0, 1.0],
+            PowerUpKind::ReverseControls => [1.0, 0.3, 1.0, 1.0],
+        }
+    }
+
+    /// Whether the power-up hinders the claiming player's opponents instead of helping them.
+    pub fn targets_opponents(self) -> bool {
+        match self {
+            PowerUpKind::ShrinkPaddle | PowerUpKind::ReverseControls => true,
+            _ => false,
+        }
+    }
+}
+
+/// Pickup waiting in the arena to be claimed by a ball.
+pub struct PowerUp {
+    pub kind: PowerUpKind,
+    pub radius: f32,
+}
+
+impl Component for PowerUp {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Effect of a claimed power-up on a player.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ActiveEffect {
+    pub kind: PowerUpKind,
+    pub side: Side,
+    pub remaining: f32, // seconds
+    pub stacks: u32,
+}
+
+/// HUD slot showing one of a player's active effects: its icon and, for stacked effects, the
+/// number of stacks.
+pub struct HudSlot {
+    pub icon: Entity,
+    pub stacks: Entity,
+}
+
+/// Ball held by a sticky paddle.
+pub struct StuckBall {
+    pub ball: Entity,
+    pub paddle: Entity,
+    /// Position of the ball along the paddle, from its center.
+    pub offset: f32,
+    pub remaining: f32, // seconds
+}
+
+/// Resource present in matches with power-ups: the effects in play and the entities showing
+/// them. Effects are applied by the PowerUpSystem (and read by the systems they change) on
+/// every frame, so they simply stop applying when they expire.
+pub struct PowerUps {
+    sprite_sheet_handle: Option<Handle<SpriteSheet>>,
+    pub spawn_timer: f32, // seconds since the last pickup was spawned
+    effects: Vec<ActiveEffect>,
+    pub stuck_balls: Vec<StuckBall>,
+    /// Walls shown behind the shielded players' paddles.
+    pub shields: HashMap<Side, Entity>,
+    /// Icons of each player's active effects, in the order they were claimed.
+    pub hud: HashMap<Side, Vec<HudSlot>>,
+}
+
+impl PowerUps {
+    pub fn new(sprite_sheet_handle: Option<Handle<SpriteSheet>>) -> Self {
+        PowerUps {
+            sprite_sheet_handle,
+            spawn_timer: 0.0,
+            effects: Vec::new(),
+            stuck_balls: Vec::new(),
+            shields: HashMap::new(),
+            hud: HashMap::new(),
+        }
+    }
+
+    /// Sprite sheet of the pickups and shields (none when running headless).
+    pub fn sprite_sheet_handle(&self) -> Option<Handle<SpriteSheet>> {
+        self.sprite_sheet_handle.clone()
+    }
+
+    pub fn effects(&self) -> &[ActiveEffect] {
+        &self.effects
+    }
+
+    /// Starts (or stacks) an effect on a player following its rule: claiming an effect the
+    /// player already has restarts its timer and, for stacking effects, adds a stack.
+    pub fn add_effect(&mut self, side: Side, rule: &PowerUpRule) {
+        let existing = self
+            .effects
+            .iter_mut()
+            .find(|effect| effect.kind == rule.kind && effect.side == side);
+
+        match existing {
+            Some(effect) => {
+                effect.remaining = rule.duration;
+
+                if rule.stacking == Stacking::Stack {
+                    effect.stacks = (effect.stacks + 1).min(rule.max_stacks.max(1));
+                }
+            }
+            None => self.effects.push(ActiveEffect {
+                kind: rule.kind,
+                side,
+                remaining: rule.duration,
+                stacks: 1,
+            }),
+        }
+    }
+
+    /// Counts the effects down, forgetting the expired ones.
+    pub fn update_effects(&mut self, delta_seconds: f32) {
+        for effect in self.effects.iter_mut() {
+            effect.remaining -= delta_seconds;
+        }
+
+        self.effects.retain(|effect| effect.remaining > 0.0);
+    }
+
+    fn stacks(&self, kind: PowerUpKind, side: Option<Side>) -> u32 {
+        self.effects
+            .iter()
+            .filter(|effect| effect.kind == kind && side.map_or(true, |side| effect.side == side))
+            .map(|effect| effect.stacks)
+            .sum()
+    }
+
+    /// Length multiplier of a player's paddles.
+    pub fn paddle_length_factor(&self, side: Side) -> f32 {
+        let enlarged = self.stacks(PowerUpKind::EnlargePaddle, Some(side));
+        let shrunk = self.stacks(PowerUpKind::ShrinkPaddle, Some(side));
+
+        ENLARGE_FACTOR.powi(enlarged as i32) * SHRINK_FACTOR.powi(shrunk as i32)
+    }
+
+    /// Speed multiplier of every ball.
+    pub fn ball_speed_factor(&self) -> f32 {
+        let sped_up = self.stacks(PowerUpKind::SpeedBall, None);
+        let slowed_down = self.stacks(PowerUpKind::SlowBall, None);
+
+        SPEED_FACTOR.powi(sped_up as i32) * SLOW_FACTOR.powi(slowed_down as i32)
+    }
+
+    pub fn has_effect(&self, kind: PowerUpKind, side: Side) -> bool {
+        self.stacks(kind, Some(side)) > 0
+    }
+
+    /// Goals left open: shielded ones are walls while the shield lasts.
+    pub fn open_goals(&self, goals: Vec<Side>) -> Vec<Side> {
+        goals
+            .into_iter()
+            .filter(|goal| !self.has_effect(PowerUpKind::ShieldWall, *goal))
+            .collect()
+    }
+
+    /// Whether a ball is held by a sticky paddle.
+    pub fn is_stuck(&self, ball: Entity) -> bool {
+        self.stuck_balls.iter().any(|stuck| stuck.ball == ball)
+    }
+
+    /// Active effects of a player, in the order they were claimed.
+    pub fn player_effects(&self, side: Side) -> Vec<&ActiveEffect> {
+        self.effects
+            .iter()
+            .filter(|effect| effect.side == side)
+            .collect()
+    }
+}
+
+/// Storages needed to spawn a pickup from within a system.
+pub type PickupStorages<'a, 's> = (
+    &'a mut WriteStorage<'s, PowerUp>,
+    &'a mut WriteStorage<'s, Transform>,
+    &'a mut WriteStorage<'s, Tint>,
+    &'a mut WriteStorage<'s, SpriteRender>,
+);
+
+/// Spawns a pickup at the given position, from within a system. Pickups look like (bigger)
+/// balls of the power-up's color.
+pub fn spawn_pickup(
+    entities: &Entities,
+    storages: PickupStorages,
+    kind: PowerUpKind,
+    position: [f32; 2],
+    sprite_sheet_handle: Option<Handle<SpriteSheet>>,
+) -> Entity {
+    let (power_up_storage, transform_storage, tint_storage, sprite_render_storage) = storages;
+    let mut transform = Transform::default();
+    let scale = POWER_UP_RADIUS / BALL_RADIUS;
+
+    transform.set_translation_xyz(position[0], position[1], 0.0);
+    transform.set_scale(Vector3::new(scale, scale, 1.0));
+
+    let [red, green, blue, alpha] = kind.color();
+    let pickup = PowerUp {
+        kind,
+        radius: POWER_UP_RADIUS,
+    };
+
+    let mut pickup_builder = entities
+        .build_entity()
+        .with(pickup, power_up_storage)
+        .with(transform, transform_storage)
+        .with(Tint(Srgba::new(red, green, blue, alpha)), tint_storage);
+
+    if let Some(handle) = sprite_sheet_handle {
+        pickup_builder = pickup_builder.with(SpriteRender::new(handle, 1), sprite_render_storage);
+    }
+
+    pickup_builder.build()
+}
+
+/// Inserts the PowerUps resource along with a row of (hidden) HUD icons under each player's
+/// score, showing their active effects. There are no icons when running headless.
+pub fn initialize_power_ups(
+    world: &mut World,
+    mode: GameMode,
+    sprite_sheet_handle: Option<Handle<SpriteSheet>>,
+) {
+    let mut power_ups = PowerUps::new(sprite_sheet_handle.clone());
+    let sprite_sheet_handle = match sprite_sheet_handle {
+        Some(handle) => handle,
+        None => {
+            world.insert(power_ups);
+            return;
+        }
+    };
+
+    let font_handle = load_font_handle(SQUARE_FONT_PATH, world);
+
+    for side in mode.sides() {
+        // right next to the score, towards the middle of the screen
+        let (anchor, x, y) = score_position(mode, *side);
+        let y = match anchor {
+            Anchor::TopMiddle => y - 35.0,
+            Anchor::BottomMiddle => y + 35.0,
+            _ => y - 25.0,
+        };
+
+        let slots = (0..HUD_SLOTS)
+            .map(|slot| {
+                let icon_x = x + (slot as f32 - (HUD_SLOTS - 1) as f32 * 0.5) * ICON_SPACING;
+                let name = format!("{}_power_up_{}", side.player_label(), slot);
+
+                let icon = world
+                    .create_entity()
+                    .with(UiTransform::new(
+                        name.clone(),
+                        anchor,
+                        anchor,
+                        icon_x,
+                        y,
+                        1.0,
+                        ICON_SIZE,
+                        ICON_SIZE,
+                    ))
+                    // sprite and tint set by the PowerUpSystem
+                    .with(UiImage::Sprite(SpriteRender::new(
+                        sprite_sheet_handle.clone(),
+                        PowerUpKind::EnlargePaddle.icon(),
+                    )))
+                    .with(Tint(Srgba::new(1.0, 1.0, 1.0, 1.0)))
+                    .with(Hidden)
+                    .build();
+
+                let stacks = world
+                    .create_entity()
+                    .with(UiTransform::new(
+                        format!("{}_stacks", name),
+                        anchor,
+                        anchor,
+                        icon_x + ICON_SIZE * 0.75,
+                        y - ICON_SIZE * 0.5,
+                        1.0,
+                        ICON_SIZE,
+                        ICON_SIZE * 0.5,
+                    ))
+                    .with(UiText::new(
+                        font_handle.clone(),
+                        String::new(), // set by the PowerUpSystem
+                        [1., 1., 1., 1.],
+                        8.,
+                        LineMode::Single,
+                        Anchor::MiddleLeft,
+                    ))
+                    .build();
+
+                HudSlot { icon, stacks }
+            })
+            .collect();
+
+        power_ups.hud.insert(*side, slots);
+    }
+
+    world.insert(power_ups);
+}
+
+/// Picks the rule of the next pickup given a roll between 0 and the sum of the weights.
+pub fn pick_rule(config: &PowerUpConfig, roll: u32) -> Option<&PowerUpRule> {
+    let mut remaining = roll;
+
+    config.rules.iter().find(|rule| {
+        if remaining < rule.weight {
+            true
+        } else {
+            remaining -= rule.weight;
+            false
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(kind: PowerUpKind, stacking: Stacking) -> PowerUpRule {
+        PowerUpRule {
+            kind,
+            weight: 1,
+            duration: 10.0,
+            stacking,
+            max_stacks: 2,
+        }
+    }
+
+    #[test]
+    fn should_stack_effects_up_to_max_stacks() {
+        // arrange
+        let mut power_ups = PowerUps::new(None);
+        let enlarge = rule(PowerUpKind::EnlargePaddle, Stacking::Stack);
+
+        // act
+        for _ in 0..3 {
+            power_ups.add_effect(Side::Left, &enlarge);
+        }
+
+        // assert
+        assert_eq!(2, power_ups.effects()[0].stacks);
+        assert_eq!(2.25, power_ups.paddle_length_factor(Side::Left)); // 1.5 * 1.5
+        assert_eq!(1.0, power_ups.paddle_length_factor(Side::Right));
+    }
+
+    #[test]
+    fn should_refresh_effect_timer_when_claimed_again() {
+        // arrange
+        let mut power_ups = PowerUps::new(None);
+        let speed = rule(PowerUpKind::SpeedBall, Stacking::Refresh);
+
+        power_ups.add_effect(Side::Left, &speed);
+        power_ups.update_effects(8.0);
+
+        // act
+        power_ups.add_effect(Side::Left, &speed);
+        power_ups.update_effects(8.0);
+
+        // assert
+        assert_eq!(1, power_ups.effects()[0].stacks);
+        assert_eq!(1.5, power_ups.ball_speed_factor());
+    }
+
+    #[test]
+    fn should_list_player_effects_in_claim_order() {
+        // arrange
+        let mut power_ups = PowerUps::new(None);
+        let shield = rule(PowerUpKind::ShieldWall, Stacking::Refresh);
+        let enlarge = rule(PowerUpKind::EnlargePaddle, Stacking::Stack);
+
+        // act
+        power_ups.add_effect(Side::Left, &shield);
+        power_ups.add_effect(Side::Right, &enlarge);
+        power_ups.add_effect(Side::Left, &enlarge);
+        power_ups.add_effect(Side::Left, &enlarge);
+
+        // assert
+        let icons = power_ups
+            .player_effects(Side::Left)
+            .iter()
+            .map(|effect| (effect.kind.icon(), effect.stacks))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                (PowerUpKind::ShieldWall.icon(), 1),
+                (PowerUpKind::EnlargePaddle.icon(), 2)
+            ],
+            icons
+        );
+        assert_ne!(
+            PowerUpKind::ShieldWall.icon(),
+            PowerUpKind::EnlargePaddle.icon()
+        );
+    }
+
+    #[test]
+    fn should_pick_rules_by_weight() {
+        // arrange
+        let mut config = PowerUpConfig::default();
+        config.rules = vec![
+            PowerUpRule {
+                weight: 3,
+                ..rule(PowerUpKind::EnlargePaddle, Stacking::Stack)
+            },
+            PowerUpRule {
+                weight: 1,
+                ..rule(PowerUpKind::ShieldWall, Stacking::Refresh)
+            },
+        ];
+
+        // act
+        let picked = (0..4)
+            .map(|roll| pick_rule(&config, roll).map(|rule| rule.kind))
+            .collect::<Vec<_>>();
+
+        // assert
+        assert_eq!(
+            vec![
+                Some(PowerUpKind::EnlargePaddle),
+                Some(PowerUpKind::EnlargePaddle),
+                Some(PowerUpKind::EnlargePaddle),
+                Some(PowerUpKind::ShieldWall),
+            ],
+            picked
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use amethyst::{
+    assets::Handle,
+    core::{math::Vector3, Transform},
+    ecs::Entity,
+    prelude::{Builder, WorldExt},
+    renderer::{palette::Srgba, resources::Tint, SpriteRender, SpriteSheet},
+    shred::World,
+    ui::{Anchor, LineMode, UiText, UiTransform},
+};
+
+use super::font::load_font_handle;
+use crate::settings::{
+    Drill, TargetZone, ARENA_HEIGHT, ARENA_WIDTH, PADDLE_HEIGHT, PADDLE_WIDTH, SQUARE_FONT_PATH,
+};
+
+/// Thickness of the target zones drawn along the right wall.
+const TARGET_ZONE_THICKNESS: f32 = 1.0;
+
+/// How a shot of the launcher ended.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ShotResult {
+    /// The ball went into the player's goal.
+    Missed,
+    /// Returned to the wall, outside of the target zones.
+    Returned,
+    /// Returned into a target zone worth the given points.
+    OnTarget(u32),
+}
+
+/// Resource present in practice sessions: the drill being played, the shots fired so far and
+/// how they ended.
+pub struct PracticeSession {
+    drill: Drill,
+    shots: Vec<[f32; 2]>,
+    results: Vec<ShotResult>,
+    pub hud: Option<Entity>,
+}
+
+impl PracticeSession {
+    pub fn new(drill: Drill) -> Self {
+        PracticeSession {
+            drill,
+            shots: Vec::new(),
+            results: Vec::new(),
+            hud: None,
+        }
+    }
+
+    pub fn drill(&self) -> &Drill {
+        &self.drill
+    }
+
+    /// Records a shot fired by the launcher with the given velocity.
+    pub fn launch(&mut self, velocity: [f32; 2]) {
+        self.shots.push(velocity);
+    }
+
+    /// Records how the shot in play ended.
+    pub fn record(&mut self, result: ShotResult) {
+        if self.results.len() < self.shots.len() {
+            self.results.push(result);
+        }
+    }
+
+    /// Whether a shot is in play (fired and not ended yet).
+    pub fn is_shot_in_play(&self) -> bool {
+        self.results.len() < self.shots.len()
+    }
+
+    /// Whether every shot of the drill was played.
+    pub fn is_over(&self) -> bool {
+        self.results.len() >= self.drill.shots as usize
+    }
+
+    /// Forgets how the last shot ended, returning its velocity to fire it again.
+    pub fn rewind(&mut self) -> Option<[f32; 2]> {
+        let velocity = *self.shots.last()?;
+
+        self.results.truncate(self.shots.len() - 1);
+
+        Some(velocity)
+    }
+
+    pub fn points(&self) -> u32 {
+        self.results
+            .iter()
+            .map(|result| match result {
+                ShotResult::OnTarget(points) => *points,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// Percentage of the played shots returned into a target zone.
+    pub fn accuracy(&self) -> u32 {
+        if self.results.is_empty() {
+            return 0;
+        }
+
+        let on_target = self
+            .results
+            .iter()
+            .filter(|result| matches!(result, ShotResult::OnTarget(_)))
+            .count();
+
+        (on_target * 100 / self.results.len()) as u32
+    }
+
+    pub fn hud_text(&self) -> String {
+        let progress = if self.is_over() {
+            "DRILL OVER".to_string()
+        } else {
+            format!("SHOT {}/{}", self.shots.len().max(1), self.drill.shots)
+        };
+
+        format!(
+            "{}  {}  ACCURACY {}%  POINTS {}",
+            self.drill.name.to_uppercase(),
+            progress,
+            self.accuracy(),
+            self.points()
+        )
+    }
+}
+
+/// Velocity of a shot fired towards the player's goal (on the left), at the given angle from
+/// the horizontal in degrees.
+pub fn shot_velocity(angle: f32, speed: f32, upwards: bool) -> [f32; 2] {
+    let radians = angle.to_radians();
+    let vertical = if upwards { 1.0 } else { -1.0 };
+
+    [-speed * radians.cos(), vertical * speed * radians.sin()]
+}
+
+/// Points of the target zone at the given height of the wall, if any.
+pub fn zone_points(zones: &[TargetZone], y: f32) -> Option<u32> {
+    zones
+        .iter()
+        .filter(|zone| (y - zone.center).abs() <= zone.size * 0.5)
+        .map(|zone| zone.points)
+        .max()
+}
+
+/// Draws the target zones along the right wall and inserts the PracticeSession resource along
+/// with its HUD text.
+pub fn initialize_practice(
+    world: &mut World,
+    drill: Drill,
+    zones: &[TargetZone],
+    sprite_sheet_handle: Option<Handle<SpriteSheet>>,
+) {
+    for zone in zones {
+        let mut transform = Transform::default();
+
+        transform.set_translation_xyz(ARENA_WIDTH - TARGET_ZONE_THICKNESS * 0.5, zone.center, 0.0);
+        transform.set_scale(Vector3::new(
+            TARGET_ZONE_THICKNESS / PADDLE_WIDTH,
+            zone.size / PADDLE_HEIGHT,
+            1.0,
+        ));
+
+        // the more points, the greener
+        let shade = 1.0 / zone.points.max(1) as f32;
+        let builder = world
+            .create_entity()
+            .with(transform)
+            .with(Tint(Srgba::new(shade, 1.0, shade, 0.8)));
+
+        // a paddle stretched along the zone (nothing is drawn when running headless)
+        match sprite_sheet_handle.clone() {
+            Some(handle) => builder.with(SpriteRender::new(handle, 0)),
+            None => builder,
+        }
+        .build();
+    }
+
+    let font_handle = load_font_handle(SQUARE_FONT_PATH, world);
+    let transform = UiTransform::new(
+        "practice_hud".to_string(),
+        Anchor::TopMiddle,
+        Anchor::TopMiddle,
+        0.0,
+        -30.0,
+        1.0,
+        600.0,
+        30.0,
+    );
+
+    let hud = world
+        .create_entity()
+        .with(transform)
+        .with(UiText::new(
+            font_handle,
+            String::new(), // set by the PracticeSystem
+            [1., 1., 1., 1.],
+            20.,
+            LineMode::Single,
+            Anchor::Middle,
+        ))
+        .build();
+
+    let mut session = PracticeSession::new(drill);
+    session.hud.replace(hud);
+
+    world.insert(session);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_fire_the_last_shot_again_when_rewinding() {
+        // arrange
+        let drill = Drill {
+            name: "random".to_string(),
+            min_angle: 0.0,
+            max_angle: 60.0,
+            min_speed: 60.0,
+            max_speed: 150.0,
+            shots: 2,
+        };
+        let mut session = PracticeSession::new(drill);
+        session.launch([-75.0, 50.0]);
+        session.record(ShotResult::OnTarget(3));
+        session.launch([-100.0, 0.0]);
+        session.record(ShotResult::Missed);
+        let over_before = session.is_over();
+
+        // act
+        let rewound = session.rewind();
+
+        // assert
+        assert_eq!(true, over_before);
+        assert_eq!(Some([-100.0, 0.0]), rewound);
+        assert_eq!(false, session.is_over());
+        assert_eq!(100, session.accuracy());
+        assert_eq!(3, session.points());
+    }
+
+    #[test]
+    fn should_score_returns_by_target_zone() {
+        // arrange
+        let zones = vec![
+            TargetZone {
+                center: 50.0,
+                size: 20.0,
+                points: 1,
+            },
+            TargetZone {
+                center: 85.0,
+                size: 15.0,
+                points: 3,
+            },
+        ];
+
+        // act
+        let middle = zone_points(&zones, 45.0);
+        let top = zone_points(&zones, 90.0);
+        let outside = zone_points(&zones, 70.0);
+
+        // assert
+        assert_eq!(Some(1), middle);
+        assert_eq!(Some(3), top);
+        assert_eq!(None, outside);
+    }
+}
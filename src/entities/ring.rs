@@ -0,0 +1,440 @@
+use std::f32::consts::PI;
+
+use amethyst::{
+    assets::Handle,
+    core::{math::Vector3, Transform},
+    ecs::Entity,
+    prelude::{Builder, WorldExt},
+    renderer::{palette::Srgba, resources::Tint, SpriteRender, SpriteSheet},
+    shred::World,
+    ui::{Anchor, LineMode, UiText, UiTransform},
+};
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use super::{font::load_font_handle, Paddle, PaddleDriver, Side};
+use crate::settings::{
+    PlayerProfiles, RingConfig, ARENA_HEIGHT, ARENA_WIDTH, BALL_RADIUS, PADDLE_WIDTH,
+    SQUARE_FONT_PATH,
+};
+
+/// Center of the round arena.
+pub const RING_CENTER: [f32; 2] = [ARENA_WIDTH * 0.5, ARENA_HEIGHT * 0.5];
+
+/// Degrees between two dots of the boundary.
+const BOUNDARY_DOT_SPACING: f32 = 4.0;
+const BOUNDARY_DOT_SCALE: f32 = 0.5;
+
+/// Colors of the boundary's dots: solid along the walls, faint along the open goals.
+pub const WALL_TINT: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+pub const GOAL_TINT: [f32; 4] = [1.0, 1.0, 1.0, 0.15];
+
+/// Arc of the ring a paddle moves along, around its player's goal. Positions along the arc are
+/// in arena units from the goal's center, counterclockwise.
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize, Serialize)]
+pub struct RingArc {
+    /// Player (0 for P1) defending the goal.
+    pub seat: usize,
+    /// Angle of the goal's center, in radians.
+    pub center: f32,
+    /// Distance from the arena's center to the paddle's center.
+    pub radius: f32,
+    /// Half of the angle the goal spans, in radians.
+    pub half_angle: f32,
+}
+
+impl RingArc {
+    /// Position along the arc of the given arena position.
+    pub fn offset(&self, position: [f32; 2]) -> f32 {
+        angle_between(self.center, ring_angle(position)) * self.radius
+    }
+
+    /// Arena position and angle of the given position along the arc.
+    pub fn position(&self, offset: f32) -> ([f32; 2], f32) {
+        let angle = self.center + offset / self.radius;
+
+        (ring_point(angle, self.radius), angle)
+    }
+
+    /// Furthest position along the arc (either way) keeping a paddle of the given length in
+    /// front of the goal.
+    pub fn max_offset(&self, paddle_length: f32) -> f32 {
+        (self.half_angle * self.radius - paddle_length * 0.5).max(0.0)
+    }
+
+    /// Sign turning "up" (or "right", for the goals at the top and bottom of the arena) into
+    /// the arc's counterclockwise direction, so that the controls follow the screen.
+    pub fn screen_direction(&self) -> f32 {
+        let (sin, cos) = self.center.sin_cos();
+
+        // counterclockwise tangent: (-sin, cos)
+        let along = if cos.abs() >= sin.abs() { cos } else { -sin };
+
+        if along < 0.0 {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Player seated around the ring.
+#[derive(Clone, Debug)]
+pub struct Seat {
+    /// Name shown on screen: the player's profile name, or their label (e.g. P1).
+    pub name: String,
+    /// Angle of the center of the player's goal, in radians.
+    pub center: f32,
+    pub lives: u32,
+    /// Dots of the boundary along the player's goal, turned into a wall once they're out.
+    pub goal_dots: Vec<Entity>,
+}
+
+/// Resource present in ring matches: the arena's size and the players seated around it.
+#[derive(Clone, Debug)]
+pub struct Ring {
+    radius: f32,
+    half_goal: f32,
+    seats: Vec<Seat>,
+    pub hud: Option<Entity>,
+}
+
+impl Ring {
+    pub fn new(players: usize, config: &RingConfig) -> Self {
+        let players = players.max(2).min(6);
+
+        // goals never overlap
+        let half_goal = (config.goal_arc.to_radians() * 0.5).min(PI / players as f32);
+
+        Ring {
+            radius: config.radius,
+            half_goal,
+            seats: (0..players)
+                .map(|seat| Seat {
+                    name: seat_label(seat),
+                    center: seat_angle(seat, players),
+                    lives: config.lives,
+                    goal_dots: Vec::new(),
+                })
+                .collect(),
+            hud: None,
+        }
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    pub fn seats(&self) -> &[Seat] {
+        &self.seats
+    }
+
+    /// Arc the paddle of the given player moves along, just inside the boundary.
+    pub fn arc(&self, seat: usize) -> RingArc {
+        RingArc {
+            seat,
+            center: self.seats[seat].center,
+            radius: self.radius - PADDLE_WIDTH,
+            half_angle: self.half_goal,
+        }
+    }
+
+    /// Player whose goal is at the given angle of the boundary, if it's still open.
+    pub fn goal_at(&self, angle: f32) -> Option<usize> {
+        self.seats.iter().position(|seat| {
+            seat.lives > 0 && angle_between(seat.center, angle).abs() <= self.half_goal
+        })
+    }
+
+    /// Takes a life from a player. Returns whether it was their last one.
+    pub fn concede(&mut self, seat: usize) -> bool {
+        match self.seats.get_mut(seat) {
+            Some(seat) if seat.lives > 0 => {
+                seat.lives -= 1;
+                seat.lives == 0
+            }
+            _ => false,
+        }
+    }
+
+    /// Last player standing, once the others are out.
+    pub fn winner(&self) -> Option<usize> {
+        let mut standing = (0..self.seats.len()).filter(|seat| self.seats[*seat].lives > 0);
+
+        match (standing.next(), standing.next()) {
+            (Some(seat), None) => Some(seat),
+            _ => None,
+        }
+    }
+
+    /// Lives left of every player, in seat order.
+    pub fn lives(&self) -> Vec<u32> {
+        self.seats.iter().map(|seat| seat.lives).collect()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.seats.iter().filter(|seat| seat.lives > 0).count() < 2
+    }
+
+    pub fn hud_text(&self) -> String {
+        if let Some(seat) = self.winner() {
+            return format!("{} WINS", self.seats[seat].name);
+        }
+
+        self.seats
+            .iter()
+            .map(|seat| match seat.lives {
+                0 => format!("{} OUT", seat.name),
+                lives => format!("{} {}", seat.name, lives),
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+}
+
+/// Player name shown on screen (P1 to P6).
+pub fn seat_label(seat: usize) -> String {
+    format!("P{}", seat + 1)
+}
+
+/// Angle of the center of a player's goal: P1 on the left and the others evenly spread around
+/// the ring, counterclockwise (P2 on the right when there are two).
+fn seat_angle(seat: usize, players: usize) -> f32 {
+    PI + seat as f32 * 2.0 * PI / players as f32
+}
+
+/// Signed angle from `from` to `to`, between -PI and PI.
+fn angle_between(from: f32, to: f32) -> f32 {
+    let difference = (to - from).rem_euclid(2.0 * PI);
+
+    if difference > PI {
+        difference - 2.0 * PI
+    } else {
+        difference
+    }
+}
+
+fn ring_point(angle: f32, radius: f32) -> [f32; 2] {
+    let (sin, cos) = angle.sin_cos();
+
+    [RING_CENTER[0] + radius * cos, RING_CENTER[1] + radius * sin]
+}
+
+/// Angle of the given arena position, seen from the arena's center.
+pub fn ring_angle(position: [f32; 2]) -> f32 {
+    (position[1] - RING_CENTER[1]).atan2(position[0] - RING_CENTER[0])
+}
+
+/// Velocity of a ball bouncing off the ring's boundary (reflected about its normal), or None
+/// when the ball isn't touching it or is already heading back inside.
+pub fn boundary_bounce(
+    position: [f32; 2],
+    velocity: [f32; 2],
+    ball_radius: f32,
+    ring_radius: f32,
+) -> Option<[f32; 2]> {
+    let outwards = [position[0] - RING_CENTER[0], position[1] - RING_CENTER[1]];
+    let distance = outwards[0].hypot(outwards[1]);
+
+    if distance < ring_radius - ball_radius || distance == 0.0 {
+        return None;
+    }
+
+    let normal = [outwards[0] / distance, outwards[1] / distance];
+    let approach = velocity[0] * normal[0] + velocity[1] * normal[1];
+
+    if approach <= 0.0 {
+        return None;
+    }
+
+    Some([
+        velocity[0] - 2.0 * approach * normal[0],
+        velocity[1] - 2.0 * approach * normal[1],
+    ])
+}
+
+/// Draws the ring's boundary, seats the players' paddles around it and inserts the Ring
+/// resource along with its HUD text. Paddles of the seats missing from the config are driven
+/// by the CPU, and players who picked a profile go by its name.
+pub fn initialize_ring(
+    world: &mut World,
+    players: usize,
+    config: &RingConfig,
+    sprite_sheet_handle: Option<Handle<SpriteSheet>>,
+) {
+    let mut ring = Ring::new(players, config);
+
+    if let Some(profiles) = world.try_fetch::<PlayerProfiles>() {
+        for (seat, driver) in ring.seats.iter_mut().zip(config.seats.iter()) {
+            if let Some(profile) = profiles.driver_profile(*driver) {
+                seat.name = profile.name.clone();
+            }
+        }
+    }
+
+    let dots = (360.0 / BOUNDARY_DOT_SPACING) as usize;
+
+    for dot in 0..dots {
+        let angle = (dot as f32 * BOUNDARY_DOT_SPACING).to_radians();
+        let goal = ring.goal_at(angle);
+        let [red, green, blue, alpha] = if goal.is_some() { GOAL_TINT } else { WALL_TINT };
+        let [x, y] = ring_point(angle, ring.radius + BALL_RADIUS * BOUNDARY_DOT_SCALE);
+
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(x, y, 0.0);
+        transform.set_scale(Vector3::new(BOUNDARY_DOT_SCALE, BOUNDARY_DOT_SCALE, 1.0));
+
+        let builder = world
+            .create_entity()
+            .with(transform)
+            .with(Tint(Srgba::new(red, green, blue, alpha)));
+
+        // small balls (nothing is drawn when running headless)
+        let entity = match sprite_sheet_handle.clone() {
+            Some(handle) => builder.with(SpriteRender::new(handle, 1)),
+            None => builder,
+        }
+        .build();
+
+        if let Some(seat) = goal {
+            ring.seats[seat].goal_dots.push(entity);
+        }
+    }
+
+    for seat in 0..ring.seats.len() {
+        let arc = ring.arc(seat);
+        let ([x, y], angle) = arc.position(0.0);
+
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(x, y, 0.0);
+        transform.set_rotation_2d(angle); // upright sprite, along the arc's tangent
+
+        // ring paddles are told apart by their arc's seat, their side only picks their size
+        let paddle = Paddle {
+            driver: config.seats.get(seat).cloned().unwrap_or(PaddleDriver::Cpu),
+            arc: Some(arc),
+            ..Paddle::new(Side::Left)
+        };
+
+        let builder = world.create_entity().with(paddle).with(transform);
+
+        match sprite_sheet_handle.clone() {
+            Some(handle) => builder.with(SpriteRender::new(handle, 0)),
+            None => builder,
+        }
+        .build();
+    }
+
+    let font_handle = load_font_handle(SQUARE_FONT_PATH, world);
+    let transform = UiTransform::new(
+        "ring_hud".to_string(),
+        Anchor::TopMiddle,
+        Anchor::TopMiddle,
+        0.0,
+        -30.0,
+        1.0,
+        600.0,
+        30.0,
+    );
+
+    let hud = world
+        .create_entity()
+        .with(transform)
+        .with(UiText::new(
+            font_handle,
+            String::new(), // set by the RingSystem
+            [1., 1., 1., 1.],
+            20.,
+            LineMode::Single,
+            Anchor::Middle,
+        ))
+        .build();
+
+    ring.hud.replace(hud);
+
+    world.insert(ring);
+}
+
+/// Gives the players of the ring the lives they had left (e.g. in a resumed match): the goals
+/// of those who are out are turned into walls. Their paddles are left to the caller.
+pub fn restore_ring_lives(world: &mut World, lives: &[u32]) {
+    let mut ring = world.write_resource::<Ring>();
+    let mut tint_storage = world.write_storage::<Tint>();
+
+    for (seat, lives) in ring.seats.iter_mut().zip(lives.iter()) {
+        seat.lives = *lives;
+
+        if seat.lives > 0 {
+            continue;
+        }
+
+        for dot in seat.goal_dots.iter() {
+            let [red, green, blue, alpha] = WALL_TINT;
+
+            if let Err(err) = tint_storage.insert(*dot, Tint(Srgba::new(red, green, blue, alpha))) {
+                error!("Failed to turn a goal into a wall: {}", err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(players: usize) -> Ring {
+        Ring::new(players, &RingConfig::default())
+    }
+
+    #[test]
+    fn should_find_goal_of_player_at_angle() {
+        // arrange - P1 on the left, P2 at the bottom right and P3 at the top right
+        let mut ring = ring(3);
+
+        // act
+        let left = ring.goal_at(PI + 0.2);
+        let bottom_right = ring.goal_at(-PI / 3.0);
+        let between = ring.goal_at(0.0);
+        ring.seats[1].lives = 0;
+        let out = ring.goal_at(-PI / 3.0);
+
+        // assert
+        assert_eq!(Some(0), left);
+        assert_eq!(Some(1), bottom_right);
+        assert_eq!(None, between);
+        assert_eq!(None, out);
+    }
+
+    #[test]
+    fn should_reflect_ball_off_boundary_normal() {
+        // arrange - ball on the boundary at 45 degrees, flying right
+        let radius = 40.0;
+        let position = ring_point(PI / 4.0, radius);
+
+        // act
+        let bounced = boundary_bounce(position, [10.0, 0.0], 2.0, radius).unwrap();
+        let heading_in = boundary_bounce(position, [-10.0, 0.0], 2.0, radius);
+
+        // assert - sent back down along the tangent
+        assert!(bounced[0].abs() < 0.001);
+        assert!((bounced[1] + 10.0).abs() < 0.001);
+        assert_eq!(None, heading_in);
+    }
+
+    #[test]
+    fn should_move_paddles_up_on_the_left_and_right() {
+        // arrange
+        let ring = ring(2);
+
+        // act
+        let left = ring.arc(0);
+        let right = ring.arc(1);
+        let ([_, y], _) = left.position(left.screen_direction() * 5.0);
+
+        // assert
+        assert_eq!(-1.0, left.screen_direction());
+        assert_eq!(1.0, right.screen_direction());
+        assert!(y > RING_CENTER[1]);
+        assert!((left.offset([RING_CENTER[0] - left.radius, RING_CENTER[1]])).abs() < 0.001);
+    }
+}
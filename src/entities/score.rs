@@ -1,83 +1,293 @@
+use std::collections::HashMap;
+
 use amethyst::{
-    assets::{AssetStorage, Handle, Loader},
     ecs::Entity,
     prelude::{Builder, WorldExt},
     shred::World,
-    ui::{Anchor, FontAsset, LineMode, TtfFormat, UiText, UiTransform},
+    ui::{Anchor, LineMode, UiText, UiTransform},
 };
+use serde::{Deserialize, Serialize};
 
-use crate::settings::SQUARE_FONT_PATH;
+use super::{font::load_font_handle, Side};
+use crate::settings::{
+    FourPlayerScoring, GameMode, GameplayConfig, PlayerProfiles, SQUARE_FONT_PATH,
+};
 
-/// ScoreText holds the UI text that is print on the game screen.
-pub struct ScoreText {
-    pub p1_score: Entity,
-    pub p2_score: Entity,
+/// Resource with the players' points, keyed by the side of the paddle that scored them, and
+/// their lives when playing a four-player match with lives.
+#[derive(Clone, Default, PartialEq, Debug, Deserialize, Serialize)]
+pub struct Scoreboard {
+    scores: HashMap<Side, u32>,
+    lives: HashMap<Side, u32>,
+    finished: bool, // ended before a player was eliminated (e.g. breakout)
 }
 
-/// Uses an asset loader to fetch font assets and return a font handle to the loaded asset.
-fn load_font_handle(font_location: &str, world: &mut World) -> Handle<FontAsset> {
-    let asset_loader = world.read_resource::<Loader>();
-    let storage = world.read_resource::<AssetStorage<FontAsset>>();
+impl Scoreboard {
+    pub fn score(&self, side: Side) -> u32 {
+        self.scores.get(&side).cloned().unwrap_or(0)
+    }
+
+    pub fn set_score(&mut self, side: Side, score: u32) {
+        self.scores.insert(side, score);
+    }
+
+    pub fn add_point(&mut self, side: Side) {
+        self.add_points(side, 1);
+    }
+
+    pub fn add_points(&mut self, side: Side, points: u32) {
+        *self.scores.entry(side).or_insert(0) += points;
+    }
+
+    /// Player with the most points, if a single one has them.
+    pub fn leader(&self) -> Option<Side> {
+        let best = self.scores.values().max()?;
+        let mut leaders = self.scores.iter().filter(|(_, score)| *score == best);
+
+        match (leaders.next(), leaders.next()) {
+            (Some((side, _)), None) => Some(*side),
+            _ => None,
+        }
+    }
+
+    /// Ends the match (e.g. a breakout match once the bricks are cleared).
+    pub fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    /// Ends the match or, when rolling it back, starts it again.
+    pub fn set_finished(&mut self, finished: bool) {
+        self.finished = finished;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Lives left, or None when the match isn't played with lives.
+    pub fn lives(&self, side: Side) -> Option<u32> {
+        self.lives.get(&side).cloned()
+    }
 
-    asset_loader.load(font_location, TtfFormat, (), &storage)
+    pub fn set_lives(&mut self, side: Side, lives: u32) {
+        self.lives.insert(side, lives);
+    }
+
+    /// Takes a life from a player. Returns whether it was their last one.
+    pub fn lose_life(&mut self, side: Side) -> bool {
+        match self.lives.get_mut(&side) {
+            Some(lives) if *lives > 0 => {
+                *lives -= 1;
+                *lives == 0
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether a player ran out of lives.
+    pub fn is_eliminated(&self, side: Side) -> bool {
+        self.lives(side) == Some(0)
+    }
+
+    /// Edges that are still goals: the mode's sides whose player hasn't been eliminated. The
+    /// other edges are walls.
+    pub fn goals(&self, mode: GameMode) -> Vec<Side> {
+        mode.sides()
+            .iter()
+            .cloned()
+            .filter(|side| !self.is_eliminated(*side))
+            .collect()
+    }
+
+    /// Whether the match is over: it was finished, a single player is left standing or, in
+    /// single-player modes, the player ran out of lives.
+    pub fn is_match_over(&self, mode: GameMode) -> bool {
+        self.finished || self.goals(mode).len() < mode.sides().len().min(2)
+    }
 }
 
-/// Initializes the game Score board with the players scores.
-pub fn intialize_scoreboard(world: &mut World) {
+/// ScoreText holds the UI text that is print on the game screen, per player.
+pub struct ScoreText {
+    pub texts: HashMap<Side, Entity>,
+}
+
+/// Anchor and offset of a player's score text on the screen.
+pub(super) fn score_position(mode: GameMode, side: Side) -> (Anchor, f32, f32) {
+    match (mode, side) {
+        (GameMode::FourPlayer, Side::Left) => (Anchor::MiddleLeft, 60.0, 0.0),
+        (GameMode::FourPlayer, Side::Right) => (Anchor::MiddleRight, -60.0, 0.0),
+        (GameMode::FourPlayer, Side::Top) => (Anchor::TopMiddle, 0.0, -50.0),
+        (GameMode::FourPlayer, Side::Bottom) => (Anchor::BottomMiddle, 0.0, 50.0),
+        (GameMode::Squash, _) => (Anchor::TopMiddle, 0.0, -50.0),
+        (_, Side::Left) => (Anchor::TopMiddle, -50.0, -50.0),
+        (_, _) => (Anchor::TopMiddle, 50.0, -50.0),
+    }
+}
+
+/// Initializes the game Score board with the scores (or lives) of the mode's players. Players
+/// who picked a profile have their name shown next to their score, both in its color.
+pub fn intialize_scoreboard(world: &mut World, mode: GameMode) {
     // font asset handle
     let font_handle = load_font_handle(SQUARE_FONT_PATH, world);
 
-    // transforms to position the text
-    let p1_transform = UiTransform::new(
-        "P1".to_string(),
-        Anchor::TopMiddle,
-        Anchor::TopMiddle,
-        -50.0,
-        -50.0,
-        1.0,
-        200.0,
-        50.0,
-    );
-
-    let p2_transform = UiTransform::new(
-        "P2".to_string(),
-        Anchor::TopMiddle,
-        Anchor::TopMiddle,
-        50.0,
-        -50.0,
-        1.0,
-        200.0,
-        50.0,
-    );
-
-    // score entities creation
-    let p1_score = world
-        .create_entity()
-        .with(p1_transform)
-        .with(UiText::new(
-            font_handle.clone(),
-            "0".to_string(),  // string to render
-            [1., 1., 1., 1.], // font color
-            50.,              // font size
-            LineMode::Single,
-            Anchor::Middle,
-        ))
-        .build();
-
-    let p2_score = world
-        .create_entity()
-        .with(p2_transform)
-        .with(UiText::new(
-            font_handle,
-            "0".to_string(),
-            [1., 1., 1., 1.],
-            50.,
-            LineMode::Single,
-            Anchor::Middle,
-        ))
-        .build();
+    let gameplay_config = world.read_resource::<GameplayConfig>().clone();
+    let four_player = &gameplay_config.four_player;
+
+    let lives = match mode {
+        GameMode::FourPlayer if four_player.scoring == FourPlayerScoring::Lives => {
+            Some(four_player.lives)
+        }
+        GameMode::Squash => Some(gameplay_config.squash.lives),
+        _ => None,
+    };
+
+    if let Some(lives) = lives {
+        let mut scoreboard = world.write_resource::<Scoreboard>();
+
+        for side in mode.sides() {
+            scoreboard.set_lives(*side, lives);
+        }
+    }
+
+    // the score texts are smaller when there's one along every edge, and the squash one shows
+    // the streak, lives and high score
+    let (font_size, width) = match mode {
+        GameMode::FourPlayer => (30., 200.),
+        GameMode::Squash => (30., 500.),
+        _ => (50., 200.),
+    };
+
+    let lineup = mode.lineup(&gameplay_config);
+    let profiles = world
+        .try_fetch::<PlayerProfiles>()
+        .map(|profiles| profiles.clone())
+        .unwrap_or_default();
+
+    let mut texts = HashMap::new();
+
+    for side in mode.sides() {
+        let profile = profiles.team_profile(&lineup, *side);
+        let color = profile.map_or([1., 1., 1., 1.], |profile| profile.color);
+
+        // transforms to position the text
+        let (anchor, x, y) = score_position(mode, *side);
+
+        let transform = UiTransform::new(
+            side.player_label().to_string(),
+            anchor,
+            anchor,
+            x,
+            y,
+            1.0,
+            width,
+            50.0,
+        );
+
+        // score entity creation
+        let score = world
+            .create_entity()
+            .with(transform)
+            .with(UiText::new(
+                font_handle.clone(),
+                String::new(), // string to render (set by the ScoreTextSystem)
+                color,         // font color
+                font_size,     // font size
+                LineMode::Single,
+                Anchor::Middle,
+            ))
+            .build();
+
+        texts.insert(*side, score);
+
+        // the name sits under the score (over it along the bottom edge)
+        if let Some(profile) = profile {
+            let name_y = if *side == Side::Bottom {
+                y + 45.0
+            } else {
+                y - 45.0
+            };
+            let transform = UiTransform::new(
+                format!("{}_name", side.player_label()),
+                anchor,
+                anchor,
+                x,
+                name_y,
+                1.0,
+                width,
+                25.0,
+            );
+
+            world
+                .create_entity()
+                .with(transform)
+                .with(UiText::new(
+                    font_handle.clone(),
+                    profile.name.clone(),
+                    color,
+                    20.,
+                    LineMode::Single,
+                    Anchor::Middle,
+                ))
+                .build();
+        }
+    }
 
     // inserts ScoreText as a resource into the world to be fetched later
     // ScoreText will hold references to the scores entities (transforms, etc.)
-    world.insert(ScoreText { p1_score, p2_score });
+    world.insert(ScoreText { texts });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_eliminate_player_on_last_life() {
+        // arrange
+        let mut scoreboard = Scoreboard::default();
+        scoreboard.set_lives(Side::Top, 2);
+
+        // act
+        let eliminated_first = scoreboard.lose_life(Side::Top);
+        let eliminated_second = scoreboard.lose_life(Side::Top);
+
+        // assert
+        assert_eq!(false, eliminated_first);
+        assert_eq!(true, eliminated_second);
+        assert_eq!(true, scoreboard.is_eliminated(Side::Top));
+    }
+
+    #[test]
+    fn should_turn_eliminated_player_goal_into_wall() {
+        // arrange
+        let mut scoreboard = Scoreboard::default();
+
+        for side in GameMode::FourPlayer.sides() {
+            scoreboard.set_lives(*side, 1);
+        }
+
+        // act
+        scoreboard.lose_life(Side::Right);
+
+        // assert
+        assert_eq!(
+            vec![Side::Left, Side::Top, Side::Bottom],
+            scoreboard.goals(GameMode::FourPlayer)
+        );
+    }
+
+    #[test]
+    fn should_end_squash_match_on_last_life() {
+        // arrange
+        let mut scoreboard = Scoreboard::default();
+        scoreboard.set_lives(Side::Left, 1);
+
+        // act
+        let over_before = scoreboard.is_match_over(GameMode::Squash);
+        scoreboard.lose_life(Side::Left);
+
+        // assert
+        assert_eq!(false, over_before);
+        assert_eq!(true, scoreboard.is_match_over(GameMode::Squash));
+    }
 }
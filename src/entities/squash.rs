@@ -0,0 +1,95 @@
+/// Resource present in squash matches: the current streak of returns and the best one.
+#[derive(Clone, Default, Debug)]
+pub struct Squash {
+    streak: u32,
+    high_score: u32,
+    new_record: bool,
+}
+
+impl Squash {
+    pub fn new(high_score: u32) -> Self {
+        Squash {
+            high_score,
+            ..Default::default()
+        }
+    }
+
+    /// Returns since the player last missed the ball.
+    pub fn streak(&self) -> u32 {
+        self.streak
+    }
+
+    pub fn high_score(&self) -> u32 {
+        self.high_score
+    }
+
+    /// Whether the high score was beaten this session (and should be saved).
+    pub fn has_new_record(&self) -> bool {
+        self.new_record
+    }
+
+    /// Counts a ball returned by the player.
+    pub fn count_return(&mut self) {
+        self.streak += 1;
+
+        if self.streak > self.high_score {
+            self.high_score = self.streak;
+            self.new_record = true;
+        }
+    }
+
+    /// The player missed the ball: the streak starts over.
+    pub fn miss(&mut self) {
+        self.streak = 0;
+    }
+}
+
+/// Velocity of a returned ball: a bit faster every return, up to the max speed.
+pub fn speed_up(velocity: [f32; 2], factor: f32, max_speed: f32) -> [f32; 2] {
+    let speed = (velocity[0] * velocity[0] + velocity[1] * velocity[1]).sqrt();
+
+    if speed == 0.0 {
+        return velocity;
+    }
+
+    let scale = (speed * factor).min(max_speed.max(speed)) / speed;
+
+    [velocity[0] * scale, velocity[1] * scale]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_keep_high_score_after_missing() {
+        // arrange
+        let mut squash = Squash::new(2);
+
+        // act
+        for _ in 0..3 {
+            squash.count_return();
+        }
+        squash.miss();
+        squash.count_return();
+
+        // assert
+        assert_eq!(1, squash.streak());
+        assert_eq!(3, squash.high_score());
+        assert_eq!(true, squash.has_new_record());
+    }
+
+    #[test]
+    fn should_speed_up_ball_up_to_max_speed() {
+        // arrange
+        let velocity = [30.0, 40.0]; // speed: 50
+
+        // act
+        let faster = speed_up(velocity, 1.5, 100.0);
+        let capped = speed_up(velocity, 3.0, 100.0);
+
+        // assert
+        assert_eq!([45.0, 60.0], faster);
+        assert_eq!([60.0, 80.0], capped);
+    }
+}
@@ -0,0 +1,508 @@
+//! History of the local matches, kept in a JSON lines file (a match per line), and the players'
+//! statistics drawn from it.
+
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use amethyst::{prelude::WorldExt, shred::World};
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    entities::{MatchStats, PaddleDriver, Ring, Scoreboard},
+    rng::GameRng,
+    saved_match::MatchRules,
+    settings::{GameplayConfig, USER_MATCH_HISTORY_FILE},
+};
+
+/// Rating of a player's first match.
+pub const INITIAL_RATING: f32 = 1200.0;
+/// Most rating points a player wins (or loses) against a single opponent.
+pub const RATING_K_FACTOR: f32 = 32.0;
+
+/// A player's result in a recorded match.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct PlayerRecord {
+    /// Profile name, or label (e.g. P1) of the players without one.
+    pub name: String,
+    /// Played by the computer: left out of the players' statistics.
+    pub cpu: bool,
+    /// Points scored, or lives left in ring matches.
+    pub score: u32,
+}
+
+/// A match of the history.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct MatchRecord {
+    /// Random id of the match (0 in older records) which, along with its start, identifies it: a
+    /// match recorded again (resumed after quitting it) replaces its earlier record.
+    #[serde(default)]
+    pub id: u64,
+    /// Start of the match, in seconds since the Unix epoch.
+    pub started_at: u64,
+    pub rules: MatchRules,
+    pub players: Vec<PlayerRecord>,
+    /// Last player standing or, in matches played for points, the one with the most points.
+    pub winner: Option<String>,
+    /// Whether the match was played to its end rather than quit.
+    pub finished: bool,
+    /// Seconds played, pauses excluded.
+    pub duration: f32,
+    pub rallies: u32,
+    pub longest_rally: u32,
+    pub average_rally: f32,
+    /// Seed of the match's random numbers, to play it again.
+    pub seed: u64,
+}
+
+impl MatchRecord {
+    /// Record of the match being played in the world, if it's a local match and a rally was
+    /// played.
+    pub fn capture(world: &World, rules: MatchRules, finished: bool) -> Option<Self> {
+        let stats = world.try_fetch::<MatchStats>()?.clone();
+
+        if stats.rallies == 0 {
+            return None;
+        }
+
+        let gameplay_config = world.read_resource::<GameplayConfig>();
+        let scoreboard = world.read_resource::<Scoreboard>();
+
+        let (players, winner) = match world.try_fetch::<Ring>() {
+            Some(ring) => {
+                let players = ring
+                    .seats()
+                    .iter()
+                    .enumerate()
+                    .map(|(seat, player)| PlayerRecord {
+                        name: player.name.clone(),
+                        cpu: gameplay_config
+                            .ring
+                            .seats
+                            .get(seat)
+                            .map_or(true, |driver| *driver == PaddleDriver::Cpu),
+                        score: player.lives,
+                    })
+                    .collect();
+
+                let winner = ring.winner().map(|seat| ring.seats()[seat].name.clone());
+
+                (players, winner)
+            }
+            None => {
+                let lineup = rules.mode.lineup(&gameplay_config);
+                let players = rules
+                    .mode
+                    .sides()
+                    .iter()
+                    .map(|side| PlayerRecord {
+                        name: rules.profiles.team_name(&lineup, *side),
+                        cpu: lineup
+                            .paddles
+                            .iter()
+                            .filter(|placement| placement.side == *side)
+                            .all(|placement| placement.driver == PaddleDriver::Cpu),
+                        score: scoreboard.score(*side),
+                    })
+                    .collect();
+
+                let standing = scoreboard.goals(rules.mode);
+                let winner = match standing.as_slice() {
+                    _ if rules.mode.sides().len() < 2 => None,
+                    [last_standing] => Some(*last_standing),
+                    _ => scoreboard.leader(),
+                };
+
+                let winner = winner.map(|side| rules.profiles.team_name(&lineup, side));
+
+                (players, winner)
+            }
+        };
+
+        Some(MatchRecord {
+            id: stats.id,
+            started_at: stats.started_at,
+            rules,
+            players,
+            winner,
+            finished,
+            duration: stats.duration,
+            rallies: stats.rallies,
+            longest_rally: stats.longest_rally,
+            average_rally: stats.average_rally(),
+            seed: world.read_resource::<GameRng>().seed(),
+        })
+    }
+
+    /// Whether both records are of the same match.
+    fn is_same_match(&self, other: &MatchRecord) -> bool {
+        self.id == other.id && self.started_at == other.started_at
+    }
+
+    fn player(&self, name: &str) -> Option<&PlayerRecord> {
+        self.players
+            .iter()
+            .find(|player| !player.cpu && player.name == name)
+    }
+}
+
+/// A player's statistics over the match history.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct PlayerStats {
+    pub name: String,
+    pub matches: u32,
+    pub wins: u32,
+    pub losses: u32,
+    /// Matches quit before their end: neither won nor lost.
+    pub abandoned: u32,
+    /// Matches won since the last one lost.
+    pub streak: u32,
+    pub best_streak: u32,
+    pub best_score: u32,
+    /// Longest rally of the matches they played, in paddle hits.
+    pub longest_rally: u32,
+}
+
+/// Matches recorded so far, oldest first.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct MatchHistory {
+    records: Vec<MatchRecord>,
+}
+
+impl MatchHistory {
+    /// Loads the match history. There's none until a first match is recorded.
+    pub fn load_or_default(path: &Path) -> Self {
+        if !path.exists() {
+            return MatchHistory::default();
+        }
+
+        match fs::read_to_string(path) {
+            Ok(contents) => MatchHistory::parse(&contents),
+            Err(err) => {
+                error!("Failed to load the match history: {}", err);
+                MatchHistory::default()
+            }
+        }
+    }
+
+    /// Reads the records of a JSON lines history, skipping the malformed ones. Later records of
+    /// a match replace the earlier ones.
+    pub fn parse(contents: &str) -> Self {
+        let mut records = Vec::<MatchRecord>::new();
+
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            let record = match serde_json::from_str::<MatchRecord>(line) {
+                Ok(record) => record,
+                Err(err) => {
+                    error!("Skipped a malformed match record: {}", err);
+                    continue;
+                }
+            };
+
+            records.retain(|recorded| !recorded.is_same_match(&record));
+            records.push(record);
+        }
+
+        records.sort_by_key(|record| record.started_at);
+
+        MatchHistory { records }
+    }
+
+    /// Adds a match at the end of the history file.
+    pub fn append(path: &Path, record: &MatchRecord) -> amethyst::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        writeln!(file, "{}", serde_json::to_string(record)?)?;
+
+        Ok(())
+    }
+
+    pub fn records(&self) -> &[MatchRecord] {
+        &self.records
+    }
+
+    /// Statistics of the (human) players found in the history, most wins first. Only the matches
+    /// played to their end are won or lost.
+    pub fn player_stats(&self) -> Vec<PlayerStats> {
+        let mut stats = Vec::<PlayerStats>::new();
+
+        for record in self.records.iter() {
+            for player in record.players.iter().filter(|player| !player.cpu) {
+                let index = match stats.iter().position(|stats| stats.name == player.name) {
+                    Some(index) => index,
+                    None => {
+                        stats.push(PlayerStats {
+                            name: player.name.clone(),
+                            ..PlayerStats::default()
+                        });
+                        stats.len() - 1
+                    }
+                };
+                let player_stats = &mut stats[index];
+
+                player_stats.matches += 1;
+                player_stats.best_score = player_stats.best_score.max(player.score);
+                player_stats.longest_rally = player_stats.longest_rally.max(record.longest_rally);
+
+                if !record.finished {
+                    player_stats.abandoned += 1;
+                    continue;
+                }
+
+                // matches without a winner neither extend nor break the streaks
+                match record.winner.as_ref() {
+                    Some(winner) if *winner == player.name => {
+                        player_stats.wins += 1;
+                        player_stats.streak += 1;
+                        player_stats.best_streak =
+                            player_stats.best_streak.max(player_stats.streak);
+                    }
+                    Some(_) => {
+                        player_stats.losses += 1;
+                        player_stats.streak = 0;
+                    }
+                    None => {}
+                }
+            }
+        }
+
+        stats.sort_by(|a, b| b.wins.cmp(&a.wins).then_with(|| a.name.cmp(&b.name)));
+
+        stats
+    }
+
+    /// Elo ratings of the (human) players, worked out by replaying the finished matches: their
+    /// winner beats every other player of the match, each pairing rated on the ratings from
+    /// before the match.
+    pub fn ratings(&self) -> HashMap<String, f32> {
+        let mut ratings = HashMap::<String, f32>::new();
+
+        for record in self.records.iter().filter(|record| record.finished) {
+            let winner = match record.winner.as_ref() {
+                Some(winner) if record.player(winner).is_some() => winner,
+                _ => continue,
+            };
+
+            let rating = |name: &str| ratings.get(name).cloned().unwrap_or(INITIAL_RATING);
+            let winner_rating = rating(winner);
+            let mut changes = vec![(winner.clone(), 0.0)];
+
+            for loser in record
+                .players
+                .iter()
+                .filter(|player| !player.cpu && player.name != *winner)
+            {
+                let loser_rating = rating(&loser.name);
+                // chances the winner had to win, from the gap between the ratings
+                let expected = 1.0 / (1.0 + 10f32.powf((loser_rating - winner_rating) / 400.0));
+                let change = RATING_K_FACTOR * (1.0 - expected);
+
+                changes[0].1 += change;
+                changes.push((loser.name.clone(), -change));
+            }
+
+            for (name, change) in changes {
+                *ratings.entry(name).or_insert(INITIAL_RATING) += change;
+            }
+        }
+
+        ratings
+    }
+
+    /// Matches won by a player against an opponent, and won by the opponent against them
+    /// (abandoned matches left out).
+    pub fn head_to_head(&self, name: &str, opponent: &str) -> (u32, u32) {
+        self.records
+            .iter()
+            .filter(|record| {
+                record.finished
+                    && record.player(name).is_some()
+                    && record.player(opponent).is_some()
+            })
+            .fold((0, 0), |(won, lost), record| {
+                match record.winner.as_deref() {
+                    Some(winner) if winner == name => (won + 1, lost),
+                    Some(winner) if winner == opponent => (won, lost + 1),
+                    _ => (won, lost),
+                }
+            })
+    }
+}
+
+/// Path of the match history file.
+pub fn match_history_path(app_root: &Path) -> PathBuf {
+    app_root
+        .join("src")
+        .join("settings")
+        .join(USER_MATCH_HISTORY_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entities::Force,
+        settings::{GameMode, Handicaps, PlayerProfiles},
+    };
+
+    fn record(started_at: u64, scores: [u32; 2], winner: Option<&str>) -> MatchRecord {
+        MatchRecord {
+            id: started_at,
+            started_at,
+            rules: MatchRules {
+                mode: GameMode::Classic,
+                multi_ball: false,
+                power_ups: false,
+                level: None,
+                forces: vec![Force::Wind],
+                ring_players: 0,
+                handicaps: Handicaps::default(),
+                profiles: PlayerProfiles::default(),
+            },
+            players: vec![
+                PlayerRecord {
+                    name: "P1".to_string(),
+                    cpu: false,
+                    score: scores[0],
+                },
+                PlayerRecord {
+                    name: "P2".to_string(),
+                    cpu: false,
+                    score: scores[1],
+                },
+            ],
+            winner: winner.map(str::to_string),
+            finished: true,
+            duration: 90.0,
+            rallies: scores[0] + scores[1],
+            longest_rally: started_at as u32,
+            average_rally: 2.0,
+            seed: 42,
+        }
+    }
+
+    fn history(records: &[MatchRecord]) -> String {
+        records
+            .iter()
+            .map(|record| serde_json::to_string(record).unwrap() + "\n")
+            .collect()
+    }
+
+    #[test]
+    fn should_replace_earlier_record_of_resumed_match() {
+        // arrange
+        let contents = history(&[
+            record(2, [1, 0], Some("P1")),
+            record(1, [0, 2], Some("P2")),
+            record(2, [1, 3], Some("P2")),
+        ]) + "not a record\n";
+
+        // act
+        let history = MatchHistory::parse(&contents);
+
+        // assert
+        assert_eq!(
+            vec![record(1, [0, 2], Some("P2")), record(2, [1, 3], Some("P2"))],
+            history.records().to_vec()
+        );
+    }
+
+    #[test]
+    fn should_keep_matches_started_in_same_second() {
+        // arrange
+        let first = record(1, [5, 2], Some("P1"));
+        let second = MatchRecord {
+            id: 2,
+            ..record(1, [1, 5], Some("P2"))
+        };
+
+        // act
+        let history = MatchHistory::parse(&history(&[first.clone(), second.clone()]));
+
+        // assert
+        let stats = history.player_stats();
+
+        assert_eq!(vec![first, second], history.records().to_vec());
+        assert_eq!(
+            vec![(1, 1), (1, 1)],
+            stats
+                .iter()
+                .map(|stats| (stats.wins, stats.losses))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn should_count_wins_streaks_and_head_to_head() {
+        // arrange
+        let mut quit = record(6, [0, 3], Some("P2"));
+        quit.finished = false;
+
+        let history = MatchHistory::parse(&history(&[
+            record(1, [5, 2], Some("P1")),
+            record(2, [1, 5], Some("P2")),
+            record(3, [0, 0], None),
+            record(4, [5, 4], Some("P1")),
+            record(5, [7, 1], Some("P1")),
+            quit,
+        ]));
+
+        // act
+        let stats = history.player_stats();
+
+        // assert
+        assert_eq!(
+            PlayerStats {
+                name: "P1".to_string(),
+                matches: 6,
+                wins: 3,
+                losses: 1,
+                abandoned: 1,
+                streak: 2,
+                best_streak: 2,
+                best_score: 7,
+                longest_rally: 6,
+            },
+            stats[0]
+        );
+        assert_eq!("P2", stats[1].name);
+        assert_eq!((3, 1), history.head_to_head("P1", "P2"));
+    }
+
+    #[test]
+    fn should_rate_both_players_of_classic_match_won_on_points() {
+        // arrange
+        let history = MatchHistory::parse(&history(&[record(1, [11, 7], Some("P1"))]));
+
+        // act
+        let ratings = history.ratings();
+
+        // assert
+        assert_eq!(1216.0, ratings["P1"]);
+        assert_eq!(1184.0, ratings["P2"]);
+    }
+
+    #[test]
+    fn should_rate_players_on_finished_matches() {
+        // arrange
+        let mut quit = record(3, [4, 0], Some("P1"));
+        quit.finished = false;
+
+        let history = MatchHistory::parse(&history(&[
+            record(1, [5, 2], Some("P1")),
+            record(2, [1, 5], Some("P2")),
+            quit,
+        ]));
+
+        // act
+        let ratings = history.ratings();
+
+        // assert - even players trade 16 points, the lower rated one wins more back
+        assert_eq!(1198.53, (ratings["P1"] * 100.0).round() / 100.0);
+        assert_eq!(1201.47, (ratings["P2"] * 100.0).round() / 100.0);
+    }
+}
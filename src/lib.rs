@@ -0,0 +1,15 @@
+//! Pong's game logic, shared by the game (`pong`) and the headless game server (`pong-server`).
+
+pub mod audio;
+pub mod cli;
+pub mod controls;
+pub mod entities;
+pub mod history;
+pub mod net;
+pub mod rng;
+pub mod saved_match;
+pub mod settings;
+pub mod sprite_sheet;
+pub mod startup;
+pub mod state;
+pub mod systems;
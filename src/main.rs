@@ -1,23 +1,159 @@
-mod audio;
-mod entities;
-mod settings;
-mod sprite_sheet;
-mod startup;
-mod state;
-mod systems;
+use std::{env, path::PathBuf};
 
 use amethyst::{prelude::*, utils::application_root_dir};
-use startup::{build_game_config, setup_logger};
+use pong::{
+    cli::{parse_args, DEFAULT_PLAYER_NAME},
+    saved_match::{saved_match_path, SavedMatch},
+    settings::{
+        levels_dir, profiles_path, ControlsConfig, GameplayConfig, Level, Profile, Profiles,
+        CONTROLS_CONFIG_FILE, GAMEPLAY_CONFIG_FILE,
+    },
+    startup::{build_game_config, setup_logger},
+    state,
+};
 
 fn main() -> amethyst::Result<()> {
     let app_root = application_root_dir()?;
-    let assets_dir = app_root.join("assets");
-    let initial_state = state::Pong::default();
+    let cli_options = parse_args(env::args().skip(1))?;
+
+    let seed = cli_options.seed;
 
     setup_logger();
 
-    let game_config = build_game_config(&app_root)?;
-    let mut game = Application::new(assets_dir, initial_state, game_config)?;
+    if let Some(name) = cli_options.new_profile {
+        let path = profiles_path(&app_root);
+        let mut profiles = Profiles::load_or_default(&path);
+
+        if !profiles.add(Profile::new(&name)) {
+            return Err(amethyst::Error::from_string(format!(
+                "there's already a profile named {}",
+                name
+            )));
+        }
+
+        profiles.save(&path)?;
+    }
+
+    if cli_options.lobby {
+        let name = cli_options
+            .name
+            .unwrap_or_else(|| DEFAULT_PLAYER_NAME.to_string());
+
+        return run(&app_root, seed, state::Lobby::new(name));
+    }
+
+    if cli_options.practice {
+        return run(&app_root, seed, state::DrillSelect::default());
+    }
+
+    if cli_options.stats {
+        return run(&app_root, seed, state::Stats::default());
+    }
+
+    if cli_options.leaderboard {
+        return run(&app_root, seed, state::Leaderboard::default());
+    }
+
+    if cli_options.resume {
+        let saved_match = SavedMatch::load(&saved_match_path(&app_root))?
+            .ok_or_else(|| amethyst::Error::from_string("there's no saved match to resume"))?;
+        let initial_state = state::Pong::resume(saved_match);
+
+        return match cli_options.spectator_port {
+            Some(port) => run(&app_root, seed, initial_state.hosting_spectators(port)),
+            None => run(&app_root, seed, initial_state),
+        };
+    }
+
+    let initial_state = match (cli_options.netplay, cli_options.online) {
+        (Some(netplay_options), _) => state::Pong::netplay(netplay_options),
+        (None, Some(online_options)) => state::Pong::online(online_options),
+        (None, None) => match cli_options.ring_players {
+            Some(players) => state::Pong::ring(players),
+            None => state::Pong::local(cli_options.mode),
+        },
+    };
+    let initial_state = if cli_options.multi_ball {
+        initial_state.with_multi_ball()
+    } else {
+        initial_state
+    };
+    let initial_state = if cli_options.power_ups {
+        initial_state.with_power_ups()
+    } else {
+        initial_state
+    };
+    let initial_state = match cli_options.level {
+        Some(name) => {
+            let level = Level::load(levels_dir(&app_root).join(format!("{}.ron", name)))?;
+
+            initial_state.with_level(level)
+        }
+        None => initial_state,
+    };
+    let initial_state = if cli_options.forces.is_empty() {
+        initial_state
+    } else {
+        initial_state.with_forces(cli_options.forces)
+    };
+    let initial_state = match cli_options.spectator_port {
+        Some(port) => initial_state.hosting_spectators(port),
+        None => initial_state,
+    };
+
+    if cli_options.profiles {
+        let profile_select = state::ProfileSelect::new(initial_state);
+        let profile_select = if cli_options.handicaps {
+            profile_select.setting_handicaps()
+        } else {
+            profile_select
+        };
+
+        return if cli_options.select_level {
+            run(&app_root, seed, profile_select.selecting_level())
+        } else {
+            run(&app_root, seed, profile_select)
+        };
+    }
+
+    if cli_options.handicaps {
+        let handicap_select = state::HandicapSelect::new(initial_state);
+
+        return if cli_options.select_level {
+            run(&app_root, seed, handicap_select.selecting_level())
+        } else {
+            run(&app_root, seed, handicap_select)
+        };
+    }
+
+    if cli_options.select_level {
+        return run(&app_root, seed, state::LevelSelect::new(initial_state));
+    }
+
+    run(&app_root, seed, initial_state)
+}
+
+/// Loads the settings and runs the game, starting from the given state. The seed given on the
+/// command line overrides the configured one.
+fn run<S: SimpleState + 'static>(
+    app_root: &PathBuf,
+    seed: Option<u64>,
+    initial_state: S,
+) -> amethyst::Result<()> {
+    let assets_dir = app_root.join("assets");
+    let settings_dir = app_root.join("src").join("settings");
+    let controls_config = ControlsConfig::load(settings_dir.join(CONTROLS_CONFIG_FILE))?;
+    let mut gameplay_config = GameplayConfig::load(settings_dir.join(GAMEPLAY_CONFIG_FILE))?;
+
+    if seed.is_some() {
+        gameplay_config.seed = seed;
+    }
+
+    let game_config = build_game_config(app_root)?;
+    let mut game = Application::build(assets_dir, initial_state)?
+        .with_resource(controls_config) // per player analog settings
+        .with_resource(gameplay_config) // speeds, accelerations, etc.
+        .build(game_config)?;
 
     game.run();
 
@@ -0,0 +1,432 @@
+use std::{
+    collections::VecDeque,
+    io,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use amethyst::{
+    assets::Handle,
+    core::{Time, Transform},
+    ecs::{Entity, Join, World, WorldExt},
+    renderer::SpriteSheet,
+};
+use log::{error, info};
+
+use super::{
+    snapshot::PaddleSnapshot, ClientMessage, MatchSnapshot, MessageStream, PlayerInfo,
+    ServerMessage, TICK_SECONDS,
+};
+use crate::{
+    controls::{PaddleInput, PaddleInputs},
+    entities::{initialize_ball, Ball, Paddle, Side},
+    settings::{ControlsConfig, GameplayConfig, BALL_VELOCITY_X, BALL_VELOCITY_Y},
+    systems::move_paddle,
+};
+
+/// How far behind the newest snapshot the match is shown: enough to (almost) always have a
+/// later snapshot to interpolate towards, despite the network's jitter.
+const INTERPOLATION_DELAY_TICKS: f32 = 6.0;
+
+/// Game server (or game hosting spectators) to connect to, given on the command line.
+#[derive(Clone, PartialEq, Debug)]
+pub struct OnlineOptions {
+    pub server: SocketAddr,
+    pub name: String,
+    /// Only watch the match, even if a paddle is free.
+    pub spectate: bool,
+}
+
+/// Bandwidth measured over the last second, shown by the stats overlay.
+struct Bandwidth {
+    measured_at: Instant,
+    bytes_at_measure: u64,
+    bytes_per_second: u64,
+}
+
+/// Thin client of the game server: sends the local player's inputs and renders the received
+/// match state, interpolated between snapshots, with the local paddle predicted right away.
+pub struct ServerConnection {
+    stream: MessageStream,
+    welcomed: bool,
+    side: Option<Side>,
+    next_sequence: u32,
+    /// Inputs sent but not applied by the server yet: replayed over every snapshot received.
+    pending_inputs: VecDeque<(u32, PaddleInput)>,
+    predicted_paddle: Option<PaddleSnapshot>,
+    snapshots: VecDeque<(u32, MatchSnapshot)>,
+    render_tick: Option<f32>,
+    players: Vec<PlayerInfo>,
+    spectators: u32,
+    stats_visible: bool,
+    bandwidth: Bandwidth,
+    disconnected: bool,
+}
+
+impl ServerConnection {
+    pub fn connect(options: &OnlineOptions) -> io::Result<Self> {
+        let mut stream = MessageStream::connect(options.server)?;
+
+        stream.send(&ClientMessage::Join {
+            name: options.name.clone(),
+            spectate: options.spectate,
+        })?;
+
+        Ok(ServerConnection {
+            stream,
+            welcomed: false,
+            side: None,
+            next_sequence: 0,
+            pending_inputs: VecDeque::new(),
+            predicted_paddle: None,
+            snapshots: VecDeque::new(),
+            render_tick: None,
+            players: Vec::new(),
+            spectators: 0,
+            stats_visible: false,
+            bandwidth: Bandwidth {
+                measured_at: Instant::now(),
+                bytes_at_measure: 0,
+                bytes_per_second: 0,
+            },
+            disconnected: false,
+        })
+    }
+
+    /// Shows or hides the detailed connection stats in the overlay.
+    pub fn toggle_stats(&mut self) {
+        self.stats_visible = !self.stats_visible;
+    }
+
+    /// Overlay text: the connection status and who's playing (with their ping) and, when
+    /// toggled, the connection stats.
+    pub fn overlay_text(&self) -> String {
+        let players = self
+            .players
+            .iter()
+            .map(|player| {
+                let ping = match player.ping_ms {
+                    Some(ping_ms) => format!("{} MS", ping_ms),
+                    None => "LOCAL".to_string(),
+                };
+
+                format!("{} {}", player.name.to_uppercase(), ping)
+            })
+            .collect::<Vec<_>>()
+            .join(" VS ");
+
+        let mut text = format!("{}  {}", self.status(), players);
+
+        if self.stats_visible {
+            let tick = self.snapshots.back().map(|(tick, _)| *tick).unwrap_or(0);
+            let delay_ms = (INTERPOLATION_DELAY_TICKS * TICK_SECONDS * 1000.0) as u32;
+
+            text.push_str(&format!(
+                "\nTICK {}  DELAY {} MS  {:.1} KB/S  SPECTATORS {}",
+                tick,
+                delay_ms,
+                self.bandwidth.bytes_per_second as f32 / 1024.0,
+                self.spectators
+            ));
+        }
+
+        text
+    }
+
+    /// Connection status shown by the overlay.
+    fn status(&self) -> String {
+        if self.disconnected {
+            return "DISCONNECTED FROM SERVER".to_string();
+        }
+
+        match (self.welcomed, self.side) {
+            (false, _) => "CONNECTING".to_string(),
+            (true, Some(side)) => format!("PLAYING AS {}", side.player_label()),
+            (true, None) => "SPECTATING".to_string(),
+        }
+    }
+
+    /// Sends the local player's input and predicts its effect on their paddle. Called on every
+    /// fixed update, at the same rate as the server's ticks.
+    pub fn send_input(&mut self, world: &World) {
+        let side = match self.side {
+            Some(side) if !self.disconnected => side,
+            _ => return,
+        };
+
+        let input = world.read_resource::<PaddleInputs>().get(side);
+
+        self.next_sequence += 1;
+
+        if let Err(err) = self.stream.send(&ClientMessage::Input {
+            sequence: self.next_sequence,
+            input,
+        }) {
+            self.disconnect(err);
+            return;
+        }
+
+        self.pending_inputs.push_back((self.next_sequence, input));
+
+        if let Some(predicted) = self.predicted_paddle.take() {
+            self.predicted_paddle
+                .replace(predict_paddle(world, &predicted, &[input]));
+        }
+    }
+
+    /// Receives the server's snapshots and shows the match as it was a few ticks ago, except
+    /// for the local paddle which is shown where it's predicted to be. Called on every frame.
+    pub fn update(&mut self, world: &mut World, sprite_sheet_handle: Option<Handle<SpriteSheet>>) {
+        self.receive(world);
+        self.measure_bandwidth();
+
+        let delta_seconds = world.read_resource::<Time>().delta_seconds();
+
+        if let Some(snapshot) = self.interpolated_snapshot(delta_seconds) {
+            sync_ball_count(world, snapshot.balls.len(), sprite_sheet_handle);
+            snapshot.restore(world);
+        }
+
+        if let Some(predicted) = self.predicted_paddle.as_ref() {
+            let mut transform_storage = world.write_storage::<Transform>();
+            let mut paddle_storage = world.write_storage::<Paddle>();
+
+            for (paddle, transform) in (&mut paddle_storage, &mut transform_storage).join() {
+                if paddle.side == predicted.side {
+                    transform.set_translation_y(predicted.y);
+                    paddle.velocity = predicted.velocity;
+                }
+            }
+        }
+    }
+
+    fn receive(&mut self, world: &World) {
+        if self.disconnected {
+            return;
+        }
+
+        match self.stream.receive::<ServerMessage>() {
+            Ok(messages) => {
+                for message in messages {
+                    self.handle_message(world, message);
+                }
+            }
+            Err(err) => self.disconnect(err),
+        }
+    }
+
+    fn handle_message(&mut self, world: &World, message: ServerMessage) {
+        match message {
+            ServerMessage::Welcome { side } => {
+                self.welcomed = true;
+                self.side = side;
+
+                info!("Joined the game server: {}", self.status().to_lowercase());
+            }
+            ServerMessage::Snapshot {
+                tick,
+                snapshot,
+                last_inputs,
+            } => self.add_snapshot(world, tick, snapshot, &last_inputs),
+            ServerMessage::Delta {
+                tick,
+                delta,
+                last_inputs,
+            } => {
+                // deltas are relative to the previous snapshot received
+                let snapshot = match self.snapshots.back() {
+                    Some((_, previous)) => previous.apply_delta(&delta),
+                    None => {
+                        error!("Received a delta before any snapshot");
+                        return;
+                    }
+                };
+
+                self.add_snapshot(world, tick, snapshot, &last_inputs);
+            }
+            ServerMessage::Players {
+                players,
+                spectators,
+            } => {
+                self.players = players;
+                self.spectators = spectators;
+            }
+            ServerMessage::Ping { sent_at_ms } => {
+                if let Err(err) = self.stream.send(&ClientMessage::PingReply { sent_at_ms }) {
+                    self.disconnect(err);
+                }
+            }
+        }
+    }
+
+    fn add_snapshot(
+        &mut self,
+        world: &World,
+        tick: u32,
+        snapshot: MatchSnapshot,
+        last_inputs: &[(Side, u32)],
+    ) {
+        if let Some(side) = self.side {
+            self.reconcile(world, side, &snapshot, last_inputs);
+        }
+
+        self.snapshots.push_back((tick, snapshot));
+    }
+
+    fn measure_bandwidth(&mut self) {
+        let elapsed = self.bandwidth.measured_at.elapsed();
+
+        if elapsed >= Duration::from_secs(1) {
+            let received_bytes = self.stream.received_bytes();
+            let bytes = received_bytes - self.bandwidth.bytes_at_measure;
+
+            self.bandwidth = Bandwidth {
+                measured_at: Instant::now(),
+                bytes_at_measure: received_bytes,
+                bytes_per_second: (bytes as f32 / elapsed.as_secs_f32()) as u64,
+            };
+        }
+    }
+
+    /// Starts the prediction over from the server's paddle: the inputs the server already
+    /// applied are forgotten and the others are replayed on top of it.
+    fn reconcile(
+        &mut self,
+        world: &World,
+        side: Side,
+        snapshot: &MatchSnapshot,
+        last_inputs: &[(Side, u32)],
+    ) {
+        let last_input = last_inputs
+            .iter()
+            .find(|(input_side, _)| *input_side == side)
+            .map(|(_, sequence)| *sequence)
+            .unwrap_or(0);
+
+        self.pending_inputs
+            .retain(|(sequence, _)| *sequence > last_input);
+
+        if let Some(server_paddle) = snapshot.paddles.iter().find(|paddle| paddle.side == side) {
+            let pending_inputs = self
+                .pending_inputs
+                .iter()
+                .map(|(_, input)| *input)
+                .collect::<Vec<_>>();
+
+            self.predicted_paddle
+                .replace(predict_paddle(world, server_paddle, &pending_inputs));
+        }
+    }
+
+    /// Snapshot to render this frame: the render time follows the local clock and is kept
+    /// `INTERPOLATION_DELAY_TICKS` behind the newest snapshot.
+    fn interpolated_snapshot(&mut self, delta_seconds: f32) -> Option<MatchSnapshot> {
+        let newest_tick = self.snapshots.back()?.0 as f32;
+        let target_tick = newest_tick - INTERPOLATION_DELAY_TICKS;
+
+        // the local clock drifted too far from the server's (e.g. after a lag spike): jump
+        let render_tick = match self.render_tick {
+            Some(render_tick) => {
+                let advanced = render_tick + delta_seconds / TICK_SECONDS;
+
+                if (advanced - target_tick).abs() > INTERPOLATION_DELAY_TICKS {
+                    target_tick
+                } else {
+                    advanced.min(newest_tick)
+                }
+            }
+            None => target_tick,
+        };
+
+        self.render_tick.replace(render_tick);
+
+        // the snapshots before the one right before the render time aren't needed anymore
+        while self.snapshots.len() > 2 && self.snapshots[1].0 as f32 <= render_tick {
+            self.snapshots.pop_front();
+        }
+
+        let (from_tick, from) = &self.snapshots[0];
+
+        match self.snapshots.get(1) {
+            Some((to_tick, to)) => {
+                let t = (render_tick - *from_tick as f32) / (to_tick - from_tick) as f32;
+
+                Some(from.interpolate(to, t.clamp(0.0, 1.0)))
+            }
+            None => Some(from.clone()),
+        }
+    }
+
+    fn disconnect(&mut self, err: io::Error) {
+        if !self.disconnected {
+            error!("Lost connection to the game server: {}", err);
+        }
+
+        self.disconnected = true;
+    }
+}
+
+/// Applies the inputs to a paddle state the same way the server simulates them.
+fn predict_paddle(world: &World, from: &PaddleSnapshot, inputs: &[PaddleInput]) -> PaddleSnapshot {
+    let controls_config = world.read_resource::<ControlsConfig>();
+    let gameplay_config = world.read_resource::<GameplayConfig>();
+
+    let mut paddle = world
+        .read_storage::<Paddle>()
+        .join()
+        .find(|paddle| paddle.side == from.side)
+        .cloned()
+        .unwrap_or_else(|| Paddle::new(from.side));
+    let mut transform = Transform::default();
+
+    paddle.velocity = from.velocity;
+    transform.set_translation_y(from.y);
+
+    for input in inputs {
+        move_paddle(
+            &mut paddle,
+            &mut transform,
+            *input,
+            &controls_config,
+            &gameplay_config,
+            TICK_SECONDS,
+        );
+    }
+
+    PaddleSnapshot {
+        side: from.side,
+        y: transform.translation().y,
+        velocity: paddle.velocity,
+    }
+}
+
+/// Spawns or deletes ball entities so there's one per ball in the received snapshot.
+fn sync_ball_count(
+    world: &mut World,
+    count: usize,
+    sprite_sheet_handle: Option<Handle<SpriteSheet>>,
+) {
+    let balls = {
+        let entities = world.entities();
+        let ball_storage = world.read_storage::<Ball>();
+
+        (&entities, &ball_storage)
+            .join()
+            .map(|(entity, _)| entity)
+            .collect::<Vec<Entity>>()
+    };
+
+    for _ in balls.len()..count {
+        initialize_ball(
+            world,
+            [BALL_VELOCITY_X, BALL_VELOCITY_Y],
+            sprite_sheet_handle.clone(),
+        );
+    }
+
+    for ball in balls.into_iter().skip(count) {
+        if let Err(err) = world.delete_entity(ball) {
+            error!("Failed to delete ball: {}", err);
+        }
+    }
+}
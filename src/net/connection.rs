@@ -0,0 +1,134 @@
+use std::{
+    io,
+    net::{SocketAddr, TcpListener},
+};
+
+use log::{info, warn};
+
+use super::{ClientMessage, MatchSnapshot, MessageStream, ServerMessage};
+use crate::entities::Side;
+
+/// Longest player name shown to the other clients.
+const MAX_NAME_LENGTH: usize = 12;
+
+/// Connection to a client of the game server or of a game hosting spectators. The match state
+/// is sent in full once and then as deltas from the previous state sent.
+pub struct ClientConnection {
+    stream: MessageStream,
+    pub address: SocketAddr,
+    pub joined: bool,
+    pub name: String,
+    /// Paddle controlled by the client: None for spectators.
+    pub side: Option<Side>,
+    pub ping_ms: Option<u64>,
+    pub connected: bool,
+    last_sent: Option<MatchSnapshot>,
+}
+
+impl ClientConnection {
+    pub fn new(stream: MessageStream, address: SocketAddr) -> Self {
+        ClientConnection {
+            stream,
+            address,
+            joined: false,
+            name: String::new(),
+            side: None,
+            ping_ms: None,
+            connected: true,
+            last_sent: None,
+        }
+    }
+
+    /// Marks the client as joined under the (trimmed down) name it chose.
+    pub fn join(&mut self, name: &str, side: Option<Side>) {
+        self.joined = true;
+        self.side = side;
+        self.name = name.trim().chars().take(MAX_NAME_LENGTH).collect();
+
+        match side {
+            Some(side) => info!("{} joined as the {:?} player", self.address, side),
+            None => info!("{} joined as a spectator", self.address),
+        }
+    }
+
+    /// Messages received since the last call (none once disconnected).
+    pub fn receive(&mut self) -> Vec<ClientMessage> {
+        if !self.connected {
+            return Vec::new();
+        }
+
+        match self.stream.receive() {
+            Ok(messages) => messages,
+            Err(err) => {
+                self.disconnect(err);
+                Vec::new()
+            }
+        }
+    }
+
+    pub fn send(&mut self, message: &ServerMessage) {
+        if !self.connected {
+            return;
+        }
+
+        if let Err(err) = self.stream.send(message) {
+            self.disconnect(err);
+        }
+    }
+
+    /// Sends the match state: in full the first time, as a delta afterwards.
+    pub fn send_snapshot(
+        &mut self,
+        tick: u32,
+        snapshot: &MatchSnapshot,
+        last_inputs: Vec<(Side, u32)>,
+    ) {
+        let message = match self.last_sent.as_ref() {
+            Some(previous) => ServerMessage::Delta {
+                tick,
+                delta: snapshot.delta_from(previous),
+                last_inputs,
+            },
+            None => ServerMessage::Snapshot {
+                tick,
+                snapshot: snapshot.clone(),
+                last_inputs,
+            },
+        };
+
+        self.send(&message);
+        self.last_sent.replace(snapshot.clone());
+    }
+
+    fn disconnect(&mut self, err: io::Error) {
+        if self.connected {
+            info!("Client {} disconnected: {}", self.address, err);
+        }
+
+        self.connected = false;
+    }
+}
+
+/// Accepts every connection waiting on a non-blocking listener.
+pub fn accept_connections(listener: &TcpListener) -> Vec<ClientConnection> {
+    let mut connections = Vec::new();
+
+    loop {
+        match listener.accept() {
+            Ok((stream, address)) => match MessageStream::new(stream) {
+                Ok(stream) => {
+                    info!("Client connected from {}", address);
+                    connections.push(ClientConnection::new(stream, address));
+                }
+                Err(err) => warn!("Failed to set up connection from {}: {}", address, err),
+            },
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+            Err(err) => {
+                warn!("Failed to accept client: {}", err);
+                break;
+            }
+        }
+    }
+
+    connections
+}
@@ -0,0 +1,603 @@
+use std::{
+    io, mem,
+    net::{IpAddr, SocketAddr, TcpListener, UdpSocket},
+    time::{Duration, Instant},
+};
+
+use amethyst::ecs::{World, WorldExt};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use super::{snapshot::checksum_of, MessageStream, NetplayOptions, DEFAULT_INPUT_DELAY};
+use crate::{
+    entities::Side,
+    settings::{ControlsConfig, GameplayConfig},
+};
+
+/// UDP port the matches are advertised on.
+pub const LOBBY_DISCOVERY_PORT: u16 = 7770;
+
+const ADVERT_INTERVAL: Duration = Duration::from_secs(1);
+/// Matches that haven't been advertised for this long are removed from the list.
+const LISTING_TIMEOUT: Duration = Duration::from_secs(3);
+const MAX_DATAGRAM_SIZE: usize = 1024;
+
+/// Identifies a build and its game settings: players can only meet if both are the same,
+/// otherwise their simulations would diverge.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct BuildInfo {
+    pub version: String,
+    pub config_hash: u64,
+}
+
+impl BuildInfo {
+    /// This game's build, with the settings the match would be played with.
+    pub fn current(world: &World) -> Self {
+        let gameplay_config = world.read_resource::<GameplayConfig>();
+        let controls_config = world.read_resource::<ControlsConfig>();
+
+        BuildInfo {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            config_hash: checksum_of(&(
+                &*gameplay_config,
+                controls_config.mouse_max_speed, // mouse paddles are simulated too
+            )),
+        }
+    }
+
+    /// Explains why a player running the other build can't play with this one.
+    pub fn incompatibility(&self, other: &BuildInfo) -> Option<String> {
+        if self.version != other.version {
+            Some(format!(
+                "VERSION MISMATCH: {} HERE, {} THERE",
+                self.version, other.version
+            ))
+        } else if self.config_hash != other.config_hash {
+            Some("GAME SETTINGS DIFFER FROM THIS GAME'S".to_string())
+        } else {
+            None
+        }
+    }
+}
+
+/// Match advertised on the LAN by its host (one broadcast datagram per second).
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct LobbyAdvert {
+    pub name: String,
+    pub rules: String,
+    pub build: BuildInfo,
+    /// TCP port to join the match on.
+    pub port: u16,
+}
+
+/// Messages exchanged between a match's host and the player joining it (over TCP).
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub enum LobbyMessage {
+    Join {
+        name: String,
+        build: BuildInfo,
+        netplay_port: u16,
+    },
+    Accepted {
+        host_name: String,
+    },
+    Rejected {
+        reason: String,
+    },
+    Ready(bool),
+    /// The host started the match: play it over netplay with the host on the left.
+    Start {
+        netplay_port: u16,
+    },
+}
+
+/// Advertised match, as listed to the players browsing the lobby.
+#[derive(Clone, Debug)]
+pub struct MatchListing {
+    pub advert: LobbyAdvert,
+    pub host: SocketAddr,
+    last_seen: Instant,
+}
+
+/// Listens to the matches advertised on the LAN.
+pub struct LobbyBrowser {
+    socket: UdpSocket,
+    listings: Vec<MatchListing>,
+}
+
+impl LobbyBrowser {
+    pub fn bind() -> io::Result<Self> {
+        let socket = UdpSocket::bind(("0.0.0.0", LOBBY_DISCOVERY_PORT))?;
+        socket.set_nonblocking(true)?;
+
+        Ok(LobbyBrowser {
+            socket,
+            listings: Vec::new(),
+        })
+    }
+
+    pub fn listings(&self) -> &[MatchListing] {
+        &self.listings
+    }
+
+    /// Receives the adverts and forgets the matches no longer advertised. Returns whether the
+    /// list changed.
+    pub fn update(&mut self) -> bool {
+        let mut buffer = [0; MAX_DATAGRAM_SIZE];
+        let mut changed = false;
+        let now = Instant::now();
+
+        loop {
+            match self.socket.recv_from(&mut buffer) {
+                Ok((size, sender)) => {
+                    if let Ok(advert) = bincode::deserialize::<LobbyAdvert>(&buffer[..size]) {
+                        let host = SocketAddr::new(sender.ip(), advert.port);
+
+                        changed |= update_listing(&mut self.listings, advert, host, now);
+                    }
+                }
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    warn!("Failed to receive lobby advert: {}", err);
+                    break;
+                }
+            }
+        }
+
+        changed | expire_listings(&mut self.listings, now)
+    }
+}
+
+/// Adds or refreshes a listing, returning whether the list shown to the player changed.
+fn update_listing(
+    listings: &mut Vec<MatchListing>,
+    advert: LobbyAdvert,
+    host: SocketAddr,
+    now: Instant,
+) -> bool {
+    match listings.iter_mut().find(|listing| listing.host == host) {
+        Some(listing) => {
+            let changed = listing.advert != advert;
+
+            listing.advert = advert;
+            listing.last_seen = now;
+
+            changed
+        }
+        None => {
+            listings.push(MatchListing {
+                advert,
+                host,
+                last_seen: now,
+            });
+
+            true
+        }
+    }
+}
+
+fn expire_listings(listings: &mut Vec<MatchListing>, now: Instant) -> bool {
+    let count = listings.len();
+
+    listings.retain(|listing| now.duration_since(listing.last_seen) < LISTING_TIMEOUT);
+
+    listings.len() != count
+}
+
+/// Player who joined a hosted match.
+struct LobbyGuest {
+    stream: MessageStream,
+    name: String,
+    ready: bool,
+    netplay_addr: SocketAddr,
+}
+
+impl LobbyGuest {
+    fn handle_messages(&mut self, messages: impl IntoIterator<Item = LobbyMessage>) {
+        for message in messages {
+            if let LobbyMessage::Ready(ready) = message {
+                self.ready = ready;
+            }
+        }
+    }
+}
+
+/// Hosts a match: advertises it on the LAN and waits for a player to join and get ready.
+pub struct LobbyHost {
+    advert: LobbyAdvert,
+    broadcast_socket: UdpSocket,
+    listener: TcpListener,
+    last_advert_at: Option<Instant>,
+    /// Connections that didn't ask to join yet.
+    joining: Vec<MessageStream>,
+    guest: Option<LobbyGuest>,
+}
+
+impl LobbyHost {
+    pub fn bind(name: &str, rules: String, build: BuildInfo) -> io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", 0))?;
+        listener.set_nonblocking(true)?;
+
+        let broadcast_socket = UdpSocket::bind(("0.0.0.0", 0))?;
+        broadcast_socket.set_broadcast(true)?;
+
+        let advert = LobbyAdvert {
+            name: name.to_string(),
+            rules,
+            build,
+            port: listener.local_addr()?.port(),
+        };
+
+        Ok(LobbyHost {
+            advert,
+            broadcast_socket,
+            listener,
+            last_advert_at: None,
+            joining: Vec::new(),
+            guest: None,
+        })
+    }
+
+    /// Name of the player who joined and whether they're ready.
+    pub fn guest(&self) -> Option<(&str, bool)> {
+        self.guest
+            .as_ref()
+            .map(|guest| (guest.name.as_str(), guest.ready))
+    }
+
+    /// Advertises the match and handles the joining player's messages. Called on every frame.
+    pub fn update(&mut self) {
+        let advert_due = self
+            .last_advert_at
+            .map_or(true, |sent_at| sent_at.elapsed() >= ADVERT_INTERVAL);
+
+        if advert_due {
+            self.advertise();
+        }
+
+        self.accept_players();
+
+        for mut stream in mem::take(&mut self.joining) {
+            let mut messages = match stream.receive::<LobbyMessage>() {
+                Ok(messages) => messages.into_iter(),
+                Err(err) => {
+                    warn!("Lost a player joining the lobby: {}", err);
+                    continue;
+                }
+            };
+
+            match messages.next() {
+                Some(LobbyMessage::Join {
+                    name,
+                    build,
+                    netplay_port,
+                }) => {
+                    // the player may have got ready right after joining, in the same read
+                    if self.handle_join(stream, name, build, netplay_port) {
+                        if let Some(guest) = self.guest.as_mut() {
+                            guest.handle_messages(messages);
+                        }
+                    }
+                }
+                Some(_) => {} // not a player: dropped
+                None => self.joining.push(stream),
+            }
+        }
+
+        let mut left = false;
+
+        if let Some(guest) = self.guest.as_mut() {
+            match guest.stream.receive::<LobbyMessage>() {
+                Ok(messages) => guest.handle_messages(messages),
+                Err(err) => {
+                    info!("{} left the lobby: {}", guest.name, err);
+                    left = true;
+                }
+            }
+        }
+
+        if left {
+            self.guest = None;
+        }
+    }
+
+    /// Starts the match once the joining player is ready, returning the netplay settings to
+    /// play it with.
+    pub fn start(&mut self) -> io::Result<Option<NetplayOptions>> {
+        let guest = match self.guest.as_mut() {
+            Some(guest) if guest.ready => guest,
+            _ => return Ok(None),
+        };
+
+        let netplay_port = free_udp_port()?;
+
+        guest.stream.send(&LobbyMessage::Start { netplay_port })?;
+
+        Ok(Some(NetplayOptions {
+            local_port: netplay_port,
+            peer: guest.netplay_addr,
+            local_side: Side::Left,
+            input_delay: DEFAULT_INPUT_DELAY,
+        }))
+    }
+
+    fn advertise(&mut self) {
+        let advert = bincode::serialize(&self.advert).expect("adverts are always serializable");
+
+        if let Err(err) = self
+            .broadcast_socket
+            .send_to(&advert, ("255.255.255.255", LOBBY_DISCOVERY_PORT))
+        {
+            warn!("Failed to advertise match: {}", err);
+        }
+
+        self.last_advert_at.replace(Instant::now());
+    }
+
+    fn accept_players(&mut self) {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, address)) => match MessageStream::new(stream) {
+                    Ok(stream) => self.joining.push(stream),
+                    Err(err) => warn!("Failed to set up connection from {}: {}", address, err),
+                },
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    warn!("Failed to accept player: {}", err);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Accepts or refuses a player asking to join, telling them why they were refused. Returns
+    /// whether they joined.
+    fn handle_join(
+        &mut self,
+        mut stream: MessageStream,
+        name: String,
+        build: BuildInfo,
+        netplay_port: u16,
+    ) -> bool {
+        let rejection = if self.guest.is_some() {
+            Some("MATCH IS FULL".to_string())
+        } else {
+            self.advert.build.incompatibility(&build)
+        };
+
+        if let Some(reason) = rejection {
+            info!("Refused {} in the lobby: {}", name, reason);
+
+            if let Err(err) = stream.send(&LobbyMessage::Rejected { reason }) {
+                warn!("Failed to reply to {}: {}", name, err);
+            }
+
+            return false;
+        }
+
+        let accepted = LobbyMessage::Accepted {
+            host_name: self.advert.name.clone(),
+        };
+
+        let netplay_addr = match stream.send(&accepted).and_then(|_| stream.peer_addr()) {
+            Ok(address) => SocketAddr::new(address.ip(), netplay_port),
+            Err(err) => {
+                warn!("Lost {} joining the lobby: {}", name, err);
+                return false;
+            }
+        };
+
+        info!("{} joined the lobby", name);
+
+        self.guest.replace(LobbyGuest {
+            stream,
+            name,
+            ready: false,
+            netplay_addr,
+        });
+
+        true
+    }
+}
+
+/// Connection of a player to the match they joined.
+pub struct LobbyMembership {
+    stream: MessageStream,
+    host_ip: IpAddr,
+    netplay_port: u16,
+    host_name: Option<String>,
+    ready: bool,
+}
+
+/// What happened to a player waiting in a hosted match.
+pub enum LobbyEvent {
+    Rejected(String),
+    Left(String),
+    Start(NetplayOptions),
+}
+
+impl LobbyMembership {
+    /// Asks the host of a listed match to join it.
+    pub fn join(listing: &MatchListing, name: &str, build: BuildInfo) -> io::Result<Self> {
+        let mut stream = MessageStream::connect(listing.host)?;
+        let netplay_port = free_udp_port()?;
+
+        stream.send(&LobbyMessage::Join {
+            name: name.to_string(),
+            build,
+            netplay_port,
+        })?;
+
+        Ok(LobbyMembership {
+            stream,
+            host_ip: listing.host.ip(),
+            netplay_port,
+            host_name: None,
+            ready: false,
+        })
+    }
+
+    /// Host's name, once they accepted the player.
+    pub fn host_name(&self) -> Option<&str> {
+        self.host_name.as_deref()
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.ready
+    }
+
+    pub fn set_ready(&mut self, ready: bool) -> io::Result<()> {
+        self.ready = ready;
+        self.stream.send(&LobbyMessage::Ready(ready))
+    }
+
+    /// Handles the host's messages. Called on every frame.
+    pub fn update(&mut self) -> Option<LobbyEvent> {
+        let messages = match self.stream.receive::<LobbyMessage>() {
+            Ok(messages) => messages,
+            Err(_) => return Some(LobbyEvent::Left("THE HOST LEFT".to_string())),
+        };
+
+        for message in messages {
+            match message {
+                LobbyMessage::Accepted { host_name } => {
+                    self.host_name.replace(host_name);
+                }
+                LobbyMessage::Rejected { reason } => return Some(LobbyEvent::Rejected(reason)),
+                LobbyMessage::Start { netplay_port } => {
+                    return Some(LobbyEvent::Start(NetplayOptions {
+                        local_port: self.netplay_port,
+                        peer: SocketAddr::new(self.host_ip, netplay_port),
+                        local_side: Side::Right,
+                        input_delay: DEFAULT_INPUT_DELAY,
+                    }))
+                }
+                _ => {}
+            }
+        }
+
+        None
+    }
+}
+
+/// Port for the netplay session, picked by the system. It's released right away: the session
+/// binds it again once the match starts.
+fn free_udp_port() -> io::Result<u16> {
+    Ok(UdpSocket::bind(("0.0.0.0", 0))?.local_addr()?.port())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn build(version: &str, config_hash: u64) -> BuildInfo {
+        BuildInfo {
+            version: version.to_string(),
+            config_hash,
+        }
+    }
+
+    fn advert(name: &str) -> LobbyAdvert {
+        LobbyAdvert {
+            name: name.to_string(),
+            rules: "CLASSIC".to_string(),
+            build: build("0.1.0", 42),
+            port: 9000,
+        }
+    }
+
+    #[test]
+    fn should_refuse_different_version() {
+        // arrange
+        let here = build("0.1.0", 42);
+        let there = build("0.2.0", 42);
+
+        // act
+        let incompatibility = here.incompatibility(&there);
+
+        // assert
+        assert_eq!(
+            Some("VERSION MISMATCH: 0.1.0 HERE, 0.2.0 THERE".to_string()),
+            incompatibility
+        );
+    }
+
+    #[test]
+    fn should_refuse_different_settings() {
+        // arrange
+        let here = build("0.1.0", 42);
+        let there = build("0.1.0", 43);
+
+        // act
+        let incompatibility = here.incompatibility(&there);
+
+        // assert
+        assert_eq!(true, incompatibility.is_some());
+        assert_eq!(None, here.incompatibility(&build("0.1.0", 42)));
+    }
+
+    #[test]
+    fn should_handle_ready_sent_right_after_join() {
+        // arrange - both messages are waiting for the host's first read
+        let mut host = LobbyHost::bind("ADA", "CLASSIC".to_string(), build("0.1.0", 42)).unwrap();
+        let mut player = MessageStream::connect(("127.0.0.1", host.advert.port)).unwrap();
+
+        player
+            .send(&LobbyMessage::Join {
+                name: "GRACE".to_string(),
+                build: build("0.1.0", 42),
+                netplay_port: 9001,
+            })
+            .unwrap();
+        player.send(&LobbyMessage::Ready(true)).unwrap();
+        thread::sleep(Duration::from_millis(100));
+
+        // act
+        for _ in 0..50 {
+            host.update();
+
+            if host.guest().is_some() {
+                break;
+            }
+
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        // assert
+        assert_eq!(Some(("GRACE", true)), host.guest());
+    }
+
+    #[test]
+    fn should_only_report_change_when_advert_is_new_or_different() {
+        // arrange
+        let mut listings = Vec::new();
+        let host = "192.168.0.10:9000".parse().unwrap();
+        let now = Instant::now();
+
+        // act
+        let first = update_listing(&mut listings, advert("ADA"), host, now);
+        let repeated = update_listing(&mut listings, advert("ADA"), host, now);
+        let renamed = update_listing(&mut listings, advert("GRACE"), host, now);
+
+        // assert
+        assert_eq!((true, false, true), (first, repeated, renamed));
+        assert_eq!(1, listings.len());
+    }
+
+    #[test]
+    fn should_expire_matches_no_longer_advertised() {
+        // arrange
+        let mut listings = Vec::new();
+        let host = "192.168.0.10:9000".parse().unwrap();
+        let seen_at = Instant::now();
+        update_listing(&mut listings, advert("ADA"), host, seen_at);
+
+        // act
+        let expired = expire_listings(&mut listings, seen_at + LISTING_TIMEOUT);
+
+        // assert
+        assert_eq!(true, expired);
+        assert_eq!(true, listings.is_empty());
+    }
+}
@@ -0,0 +1,20 @@
+mod client;
+mod connection;
+mod lobby;
+mod protocol;
+mod rollback;
+mod server;
+mod session;
+mod snapshot;
+mod spectator;
+mod stream;
+
+pub use client::{OnlineOptions, ServerConnection};
+pub use lobby::{BuildInfo, LobbyBrowser, LobbyEvent, LobbyHost, LobbyMembership, MatchListing};
+pub use protocol::{ClientMessage, NetMessage, PlayerInfo, ServerMessage};
+pub use rollback::RollbackSession;
+pub use server::{GameServer, DEFAULT_SERVER_PORT};
+pub use session::{NetplayOptions, NetplaySession, DEFAULT_INPUT_DELAY, TICK_SECONDS};
+pub use snapshot::MatchSnapshot;
+pub use spectator::SpectatorHost;
+pub use stream::MessageStream;
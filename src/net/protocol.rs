@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+
+use super::{snapshot::SnapshotDelta, MatchSnapshot};
+use crate::{controls::PaddleInput, entities::Side};
+
+/// Messages exchanged by two netplay peers (one message per UDP datagram).
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub enum NetMessage {
+    /// Sender's inputs of the ticks starting at `first_tick`, resent until acknowledged, and
+    /// how many of the receiver's inputs the sender has (every tick before `received_until`).
+    Inputs {
+        first_tick: u32,
+        inputs: Vec<PaddleInput>,
+        received_until: u32,
+    },
+    /// Round trip time probe, echoed back as a `PingReply`.
+    Ping {
+        sent_at_ms: u64,
+    },
+    PingReply {
+        sent_at_ms: u64,
+    },
+    /// Checksum of the match state at the start of a confirmed tick.
+    Checksum {
+        tick: u32,
+        checksum: u64,
+    },
+}
+
+impl NetMessage {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("net messages are always serializable")
+    }
+
+    /// Decodes a datagram, returning None for anything that isn't a valid message.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        bincode::deserialize(bytes).ok()
+    }
+}
+
+/// Messages sent by the game server's clients over TCP.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub enum ClientMessage {
+    /// First message after connecting: asks for a free paddle, or only to watch the match.
+    Join {
+        name: String,
+        spectate: bool,
+    },
+    /// Player's input for one server tick, numbered so snapshots can acknowledge it.
+    Input {
+        sequence: u32,
+        input: PaddleInput,
+    },
+    PingReply {
+        sent_at_ms: u64,
+    },
+}
+
+/// Who's playing, shown to every client (spectators included).
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct PlayerInfo {
+    pub side: Side,
+    pub name: String,
+    /// Round trip time between the player and the server (None when playing on the host).
+    pub ping_ms: Option<u64>,
+}
+
+/// Messages sent by the game server over TCP.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub enum ServerMessage {
+    /// Reply to `Join` with the paddle given to the client (None for spectators).
+    Welcome { side: Option<Side> },
+    /// Full match state after a server tick and the last input applied to each player's
+    /// paddle. Sent once to each client: the following ticks are sent as deltas.
+    Snapshot {
+        tick: u32,
+        snapshot: MatchSnapshot,
+        last_inputs: Vec<(Side, u32)>,
+    },
+    /// Match state after a server tick, as changes from the previous one sent.
+    Delta {
+        tick: u32,
+        delta: SnapshotDelta,
+        last_inputs: Vec<(Side, u32)>,
+    },
+    /// Sent when someone joins or leaves and periodically, to refresh the pings.
+    Players {
+        players: Vec<PlayerInfo>,
+        spectators: u32,
+    },
+    /// Round trip time probe, echoed back as a `PingReply`.
+    Ping { sent_at_ms: u64 },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_decode_encoded_message() {
+        // arrange
+        let message = NetMessage::Inputs {
+            first_tick: 12,
+            inputs: vec![PaddleInput::Axis(-0.5), PaddleInput::Target(42.0)],
+            received_until: 10,
+        };
+
+        // act
+        let decoded = NetMessage::from_bytes(&message.to_bytes());
+
+        // assert
+        assert_eq!(Some(message), decoded);
+    }
+
+    #[test]
+    fn should_ignore_invalid_datagram() {
+        // arrange
+        let garbage = [255, 255, 255, 255, 1];
+
+        // act
+        let decoded = NetMessage::from_bytes(&garbage);
+
+        // assert
+        assert_eq!(None, decoded);
+    }
+}
@@ -0,0 +1,355 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::controls::PaddleInput;
+
+/// How many ticks the simulation may run ahead of the last confirmed remote input.
+pub const MAX_PREDICTION_TICKS: u32 = 8;
+
+/// Checksums are exchanged for every tick that is a multiple of this interval.
+pub const CHECKSUM_INTERVAL_TICKS: u32 = 30;
+
+/// How many ticks of snapshots and inputs are kept around for rollbacks and checksums.
+const HISTORY_TICKS: u32 = 64;
+
+/// Bookkeeping of a GGPO style rollback session: inputs of both players per tick, prediction
+/// of the remote player's missing inputs, snapshots to roll back to and checksum comparison.
+/// The snapshot type is generic so the logic doesn't depend on the ECS world.
+pub struct RollbackSession<S> {
+    input_delay: u32,
+    current_tick: u32,
+    local_inputs: HashMap<u32, PaddleInput>,
+    remote_inputs: HashMap<u32, PaddleInput>,
+    predicted_inputs: HashMap<u32, PaddleInput>,
+    remote_received_until: u32, // every remote input of the ticks before this one is known
+    remote_ack: u32,            // the peer knows every local input of the ticks before this one
+    rollback_from: Option<u32>,
+    snapshots: VecDeque<(u32, S)>,
+    next_checksum_tick: u32,
+    local_checksums: HashMap<u32, u64>,
+    remote_checksums: HashMap<u32, u64>,
+}
+
+impl<S> RollbackSession<S> {
+    /// Local inputs are applied `input_delay` ticks after being read, which hides some latency.
+    pub fn new(input_delay: u32) -> Self {
+        // the first ticks happen before any (delayed) local input is scheduled
+        let local_inputs = (0..input_delay)
+            .map(|tick| (tick, PaddleInput::default()))
+            .collect();
+
+        RollbackSession {
+            input_delay,
+            current_tick: 0,
+            local_inputs,
+            remote_inputs: HashMap::new(),
+            predicted_inputs: HashMap::new(),
+            remote_received_until: 0,
+            remote_ack: 0,
+            rollback_from: None,
+            snapshots: VecDeque::new(),
+            next_checksum_tick: CHECKSUM_INTERVAL_TICKS,
+            local_checksums: HashMap::new(),
+            remote_checksums: HashMap::new(),
+        }
+    }
+
+    /// Next tick to be simulated.
+    pub fn current_tick(&self) -> u32 {
+        self.current_tick
+    }
+
+    /// Whether the simulation may advance without going too far ahead of the remote player.
+    pub fn can_advance(&self) -> bool {
+        self.current_tick < self.remote_received_until + MAX_PREDICTION_TICKS
+    }
+
+    /// Schedules the local input read this tick. Returns the tick it applies to.
+    pub fn add_local_input(&mut self, input: PaddleInput) -> u32 {
+        let tick = self.current_tick + self.input_delay;
+
+        self.local_inputs.insert(tick, input);
+
+        tick
+    }
+
+    /// Stores an input received from the peer. A rollback is requested when it differs from
+    /// the prediction used to simulate that tick.
+    pub fn add_remote_input(&mut self, tick: u32, input: PaddleInput) {
+        if tick < self.remote_received_until || self.remote_inputs.contains_key(&tick) {
+            return; // duplicated (resent) input
+        }
+
+        self.remote_inputs.insert(tick, input);
+
+        while self.remote_inputs.contains_key(&self.remote_received_until) {
+            self.remote_received_until += 1;
+        }
+
+        if let Some(predicted) = self.predicted_inputs.remove(&tick) {
+            if predicted != input {
+                let rollback_from = self.rollback_from.map_or(tick, |from| from.min(tick));
+
+                self.rollback_from.replace(rollback_from);
+            }
+        }
+    }
+
+    /// Inputs of the (local, remote) players for a tick. A missing remote input is predicted by
+    /// repeating the last one received, which is right most of the time.
+    pub fn inputs_for(&mut self, tick: u32) -> (PaddleInput, PaddleInput) {
+        let local_input = self.local_inputs.get(&tick).cloned().unwrap_or_default();
+
+        let remote_input = match self.remote_inputs.get(&tick) {
+            Some(input) => *input,
+            None => {
+                let prediction = self
+                    .remote_received_until
+                    .checked_sub(1)
+                    .and_then(|last_tick| self.remote_inputs.get(&last_tick))
+                    .cloned()
+                    .unwrap_or_default();
+
+                self.predicted_inputs.insert(tick, prediction);
+
+                prediction
+            }
+        };
+
+        (local_input, remote_input)
+    }
+
+    /// Marks the current tick as simulated.
+    pub fn finish_tick(&mut self) {
+        self.current_tick += 1;
+
+        // forget what can't be needed anymore: rollbacks never go past the confirmed inputs
+        let oldest_tick = self.current_tick.saturating_sub(HISTORY_TICKS);
+        let oldest_local = oldest_tick.min(self.remote_ack);
+        let oldest_remote = oldest_tick.min(self.remote_received_until.saturating_sub(1));
+
+        self.local_inputs.retain(|tick, _| *tick >= oldest_local);
+        self.remote_inputs.retain(|tick, _| *tick >= oldest_remote);
+    }
+
+    /// Earliest tick that must be simulated again because of a misprediction, if any.
+    pub fn take_rollback(&mut self) -> Option<u32> {
+        self.rollback_from.take()
+    }
+
+    /// Rewinds the session to the given tick. Returns the tick it was at, which is where the
+    /// re-simulation should stop.
+    pub fn rewind(&mut self, tick: u32) -> u32 {
+        std::mem::replace(&mut self.current_tick, tick)
+    }
+
+    /// Stores the state at the start of a tick, replacing any snapshot of that tick or later.
+    pub fn save_snapshot(&mut self, tick: u32, snapshot: S) {
+        while self
+            .snapshots
+            .back()
+            .map_or(false, |(saved_tick, _)| *saved_tick >= tick)
+        {
+            self.snapshots.pop_back();
+        }
+
+        self.snapshots.push_back((tick, snapshot));
+
+        if self.snapshots.len() > HISTORY_TICKS as usize {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// State at the start of a tick, if it is still in the history.
+    pub fn snapshot(&self, tick: u32) -> Option<&S> {
+        self.snapshots
+            .iter()
+            .find(|(saved_tick, _)| *saved_tick == tick)
+            .map(|(_, snapshot)| snapshot)
+    }
+
+    /// Local inputs the peer hasn't acknowledged yet: the first tick and the following inputs.
+    pub fn unacknowledged_local_inputs(&self) -> (u32, Vec<PaddleInput>) {
+        let last_tick = self.current_tick + self.input_delay;
+        let inputs = (self.remote_ack..last_tick)
+            .take(HISTORY_TICKS as usize)
+            .map(|tick| self.local_inputs.get(&tick).cloned().unwrap_or_default())
+            .collect();
+
+        (self.remote_ack, inputs)
+    }
+
+    /// Number of contiguous remote inputs received, sent back to the peer as an ack.
+    pub fn remote_received_until(&self) -> u32 {
+        self.remote_received_until
+    }
+
+    /// The peer has received every local input before the given tick.
+    pub fn acknowledge(&mut self, received_until: u32) {
+        self.remote_ack = self.remote_ack.max(received_until);
+    }
+
+    /// Next tick whose (confirmed) starting state should be checksummed, if one is due. Both
+    /// peers produce the same sequence of ticks.
+    pub fn next_checksum_tick(&mut self) -> Option<u32> {
+        let tick = self.next_checksum_tick;
+
+        // the state at the start of the tick only depends on confirmed inputs and was simulated
+        if tick <= self.remote_received_until && tick < self.current_tick {
+            self.next_checksum_tick += CHECKSUM_INTERVAL_TICKS;
+
+            Some(tick)
+        } else {
+            None
+        }
+    }
+
+    /// Records the local checksum of a tick. Returns the tick if the peer's checksum differs.
+    pub fn add_local_checksum(&mut self, tick: u32, checksum: u64) -> Option<u32> {
+        self.local_checksums.insert(tick, checksum);
+        self.compare_checksums(tick)
+    }
+
+    /// Records the peer's checksum of a tick. Returns the tick if the local checksum differs.
+    pub fn add_remote_checksum(&mut self, tick: u32, checksum: u64) -> Option<u32> {
+        self.remote_checksums.insert(tick, checksum);
+        self.compare_checksums(tick)
+    }
+
+    fn compare_checksums(&mut self, tick: u32) -> Option<u32> {
+        let local = self.local_checksums.get(&tick).cloned()?;
+        let remote = self.remote_checksums.get(&tick).cloned()?;
+
+        self.local_checksums.remove(&tick);
+        self.remote_checksums.remove(&tick);
+
+        if local == remote {
+            None
+        } else {
+            Some(tick)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Simulates ticks without any game state.
+    fn advance(session: &mut RollbackSession<u32>, ticks: u32) {
+        for _ in 0..ticks {
+            let tick = session.current_tick();
+
+            session.save_snapshot(tick, tick);
+            session.inputs_for(tick);
+            session.finish_tick();
+        }
+    }
+
+    #[test]
+    fn should_schedule_local_input_after_input_delay() {
+        // arrange
+        let mut session = RollbackSession::<u32>::new(2);
+
+        // act
+        let tick = session.add_local_input(PaddleInput::Axis(1.0));
+
+        // assert
+        assert_eq!(2, tick);
+        assert_eq!(PaddleInput::default(), session.inputs_for(0).0);
+        assert_eq!(PaddleInput::Axis(1.0), session.inputs_for(2).0);
+    }
+
+    #[test]
+    fn should_predict_remote_input_by_repeating_last_one() {
+        // arrange
+        let mut session = RollbackSession::<u32>::new(0);
+        session.add_remote_input(0, PaddleInput::Axis(-1.0));
+
+        // act
+        let (_, remote_input) = session.inputs_for(3);
+
+        // assert
+        assert_eq!(PaddleInput::Axis(-1.0), remote_input);
+    }
+
+    #[test]
+    fn should_request_rollback_on_misprediction() {
+        // arrange
+        let mut session = RollbackSession::<u32>::new(0);
+        advance(&mut session, 4); // remote inputs predicted as "no movement"
+
+        // act
+        session.add_remote_input(0, PaddleInput::default());
+        session.add_remote_input(1, PaddleInput::default());
+        session.add_remote_input(2, PaddleInput::Axis(1.0));
+        session.add_remote_input(3, PaddleInput::Axis(1.0));
+
+        // assert
+        assert_eq!(Some(2), session.take_rollback());
+        assert_eq!(None, session.take_rollback());
+        assert_eq!(Some(&2), session.snapshot(2));
+    }
+
+    #[test]
+    fn should_not_request_rollback_on_right_prediction() {
+        // arrange
+        let mut session = RollbackSession::<u32>::new(0);
+        advance(&mut session, 2);
+
+        // act
+        session.add_remote_input(0, PaddleInput::default());
+        session.add_remote_input(1, PaddleInput::default());
+
+        // assert
+        assert_eq!(None, session.take_rollback());
+    }
+
+    #[test]
+    fn should_stop_advancing_past_prediction_window() {
+        // arrange
+        let mut session = RollbackSession::<u32>::new(0);
+
+        // act
+        advance(&mut session, MAX_PREDICTION_TICKS);
+
+        // assert
+        assert_eq!(false, session.can_advance());
+
+        session.add_remote_input(0, PaddleInput::default());
+        assert_eq!(true, session.can_advance());
+    }
+
+    #[test]
+    fn should_replace_snapshots_when_resimulating() {
+        // arrange
+        let mut session = RollbackSession::<u32>::new(0);
+        advance(&mut session, 5);
+
+        // act
+        let until = session.rewind(2);
+        session.save_snapshot(2, 20);
+
+        // assert
+        assert_eq!(5, until);
+        assert_eq!(Some(&20), session.snapshot(2));
+        assert_eq!(None, session.snapshot(3));
+    }
+
+    #[test]
+    fn should_detect_desync_from_checksums() {
+        // arrange
+        let mut session = RollbackSession::<u32>::new(0);
+
+        // act
+        let matching = session
+            .add_local_checksum(30, 7)
+            .or(session.add_remote_checksum(30, 7));
+        let desync = session
+            .add_local_checksum(60, 7)
+            .or(session.add_remote_checksum(60, 8));
+
+        // assert
+        assert_eq!(None, matching);
+        assert_eq!(Some(60), desync);
+    }
+}
@@ -0,0 +1,437 @@
+use std::{
+    collections::VecDeque,
+    io,
+    net::{SocketAddr, TcpListener},
+    thread,
+    time::{Duration, Instant},
+};
+
+use amethyst::{
+    core::Time,
+    ecs::{Dispatcher, World, WorldExt},
+};
+use log::info;
+
+use super::{
+    connection::{accept_connections, ClientConnection},
+    ClientMessage, MatchSnapshot, PlayerInfo, ServerMessage, TICK_SECONDS,
+};
+use crate::{
+    controls::{PaddleInput, PaddleInputs},
+    entities::{initialize_ball, initialize_paddles, Scoreboard, Side},
+    settings::{
+        ControlsConfig, GameplayConfig, Lineup, BALL_SPAWN_DELAY, BALL_VELOCITY_X, BALL_VELOCITY_Y,
+    },
+    startup::build_simulation_dispatcher,
+};
+
+/// Port the game server listens on when none is given.
+pub const DEFAULT_SERVER_PORT: u16 = 7777;
+
+/// Ticks between pings, which also refresh the players' info sent to everyone.
+pub const PING_INTERVAL_TICKS: u32 = 60;
+
+/// Inputs received ahead of the server's ticks are kept up to this many: beyond that the
+/// oldest are dropped so a lagging player catches up.
+const MAX_QUEUED_INPUTS: usize = 4;
+
+const PLAYER_SIDES: [Side; 2] = [Side::Left, Side::Right];
+
+/// Seconds the result of a match stays on screen before the next one starts.
+const REMATCH_DELAY: f32 = 5.0;
+
+/// Client of the game server, with the inputs it sent that weren't applied yet.
+struct ServerClient {
+    connection: ClientConnection,
+    queued_inputs: VecDeque<(u32, PaddleInput)>,
+    last_input: u32,
+}
+
+/// Authoritative game server: runs the match simulation headlessly at a fixed tick rate,
+/// applies the inputs sent by the two players and streams the resulting state to every client.
+pub struct GameServer {
+    listener: TcpListener,
+    clients: Vec<ServerClient>,
+    world: World,
+    dispatcher: Dispatcher<'static, 'static>,
+    tick: u32,
+    started_at: Instant,
+    ball_spawn_timer: Option<f32>,
+    ball_spawned: bool,
+    rematch_timer: Option<f32>, // seconds before the next match, once one ended
+    players_changed: bool,
+}
+
+impl GameServer {
+    pub fn bind(
+        port: u16,
+        controls_config: ControlsConfig,
+        gameplay_config: GameplayConfig,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+
+        let mut world = World::new();
+        let mut time = Time::default();
+        time.set_delta_seconds(TICK_SECONDS); // every tick is simulated with the same timestep
+
+        world.insert(time);
+        world.insert(controls_config);
+        world.insert(gameplay_config);
+
+        // also registers the components and inserts the default resources (inputs, scores...)
+        let dispatcher = build_simulation_dispatcher(&mut world);
+
+        initialize_paddles(&mut world, None, &Lineup::single(&PLAYER_SIDES));
+
+        Ok(GameServer {
+            listener,
+            clients: Vec::new(),
+            world,
+            dispatcher,
+            tick: 0,
+            started_at: Instant::now(),
+            ball_spawn_timer: None,
+            ball_spawned: false,
+            rematch_timer: None,
+            players_changed: false,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Runs the server forever, one tick every `TICK_SECONDS`.
+    pub fn run(&mut self) {
+        let tick_duration = Duration::from_secs_f32(TICK_SECONDS);
+
+        loop {
+            let tick_started_at = Instant::now();
+
+            self.tick();
+
+            if let Some(remaining) = tick_duration.checked_sub(tick_started_at.elapsed()) {
+                thread::sleep(remaining);
+            }
+        }
+    }
+
+    /// Accepts new clients, applies the players' inputs, advances the match by one tick and
+    /// sends the result to everyone.
+    pub fn tick(&mut self) {
+        for connection in accept_connections(&self.listener) {
+            self.clients.push(ServerClient {
+                connection,
+                queued_inputs: VecDeque::new(),
+                last_input: 0,
+            });
+        }
+
+        self.receive_messages();
+        self.apply_inputs();
+        self.update_ball_spawn();
+
+        self.dispatcher.dispatch(&self.world);
+        self.world.maintain();
+
+        self.update_match_end();
+
+        if self.tick % PING_INTERVAL_TICKS == 0 {
+            self.send_pings();
+            self.players_changed = true;
+        }
+
+        self.broadcast_snapshot();
+        self.remove_disconnected_clients();
+
+        if self.players_changed {
+            self.broadcast_players();
+            self.players_changed = false;
+        }
+
+        self.tick += 1;
+    }
+
+    fn receive_messages(&mut self) {
+        for index in 0..self.clients.len() {
+            for message in self.clients[index].connection.receive() {
+                self.handle_message(index, message);
+            }
+        }
+    }
+
+    fn handle_message(&mut self, index: usize, message: ClientMessage) {
+        match message {
+            ClientMessage::Join { name, spectate } => {
+                if self.clients[index].connection.joined {
+                    return;
+                }
+
+                let side = if spectate { None } else { self.free_side() };
+                let connection = &mut self.clients[index].connection;
+
+                connection.join(&name, side);
+                connection.send(&ServerMessage::Welcome { side });
+
+                self.players_changed = true;
+            }
+            ClientMessage::Input { sequence, input } => {
+                let client = &mut self.clients[index];
+
+                // spectators can't affect the match
+                if client.connection.side.is_some() {
+                    client.queued_inputs.push_back((sequence, input));
+
+                    while client.queued_inputs.len() > MAX_QUEUED_INPUTS {
+                        client.queued_inputs.pop_front();
+                    }
+                }
+            }
+            ClientMessage::PingReply { sent_at_ms } => {
+                let ping_ms = self.elapsed_ms().saturating_sub(sent_at_ms);
+
+                self.clients[index].connection.ping_ms = Some(ping_ms);
+            }
+        }
+    }
+
+    /// Applies one queued input per player and tick, like the client predicted it. A player
+    /// whose input didn't arrive in time keeps their previous input.
+    fn apply_inputs(&mut self) {
+        let mut paddle_inputs = self.world.write_resource::<PaddleInputs>();
+
+        for client in self.clients.iter_mut() {
+            if let (Some(side), Some((sequence, input))) =
+                (client.connection.side, client.queued_inputs.pop_front())
+            {
+                paddle_inputs.set(side, input);
+                client.last_input = sequence;
+            }
+        }
+    }
+
+    /// Serves the ball once both players are in.
+    fn update_ball_spawn(&mut self) {
+        if self.ball_spawned {
+            return;
+        }
+
+        let players_ready = PLAYER_SIDES.iter().all(|side| self.player(*side).is_some());
+
+        if !players_ready {
+            self.ball_spawn_timer = None;
+            return;
+        }
+
+        let ball_timer = self.ball_spawn_timer.unwrap_or(BALL_SPAWN_DELAY) - TICK_SECONDS;
+
+        if ball_timer <= 0.0 {
+            info!("Both players are in: serving");
+
+            initialize_ball(&mut self.world, [BALL_VELOCITY_X, BALL_VELOCITY_Y], None);
+            self.ball_spawned = true;
+        } else {
+            self.ball_spawn_timer.replace(ball_timer);
+        }
+    }
+
+    /// Once a match ended (its end is sent to the clients with the snapshots), starts the next
+    /// one after a delay: the scores are reset and the ball is served again.
+    fn update_match_end(&mut self) {
+        if !self.world.read_resource::<Scoreboard>().is_finished() {
+            return;
+        }
+
+        let rematch_timer = match self.rematch_timer {
+            Some(timer) => timer - TICK_SECONDS,
+            None => {
+                info!(
+                    "Match over: the next one starts in {} seconds",
+                    REMATCH_DELAY
+                );
+                REMATCH_DELAY
+            }
+        };
+
+        if rematch_timer <= 0.0 {
+            self.world.insert(Scoreboard::default());
+            self.ball_spawned = false;
+            self.rematch_timer = None;
+        } else {
+            self.rematch_timer.replace(rematch_timer);
+        }
+    }
+
+    fn send_pings(&mut self) {
+        let message = ServerMessage::Ping {
+            sent_at_ms: self.elapsed_ms(),
+        };
+
+        for client in self.clients.iter_mut() {
+            client.connection.send(&message);
+        }
+    }
+
+    fn broadcast_snapshot(&mut self) {
+        let snapshot = MatchSnapshot::capture(&self.world);
+        let last_inputs = self
+            .clients
+            .iter()
+            .filter_map(|client| client.connection.side.map(|side| (side, client.last_input)))
+            .collect::<Vec<_>>();
+
+        for client in self.clients.iter_mut() {
+            if client.connection.joined {
+                client
+                    .connection
+                    .send_snapshot(self.tick, &snapshot, last_inputs.clone());
+            }
+        }
+    }
+
+    fn broadcast_players(&mut self) {
+        let players = PLAYER_SIDES
+            .iter()
+            .filter_map(|side| {
+                self.player(*side).map(|connection| PlayerInfo {
+                    side: *side,
+                    name: connection.name.clone(),
+                    ping_ms: connection.ping_ms,
+                })
+            })
+            .collect();
+        let spectators = self
+            .clients
+            .iter()
+            .filter(|client| client.connection.joined && client.connection.side.is_none())
+            .count() as u32;
+
+        let message = ServerMessage::Players {
+            players,
+            spectators,
+        };
+
+        for client in self.clients.iter_mut() {
+            if client.connection.joined {
+                client.connection.send(&message);
+            }
+        }
+    }
+
+    /// Connection of the player controlling a paddle, if any.
+    fn player(&self, side: Side) -> Option<&ClientConnection> {
+        self.clients
+            .iter()
+            .map(|client| &client.connection)
+            .find(|connection| connection.connected && connection.side == Some(side))
+    }
+
+    fn free_side(&self) -> Option<Side> {
+        PLAYER_SIDES
+            .iter()
+            .cloned()
+            .find(|side| self.player(*side).is_none())
+    }
+
+    /// Forgets the disconnected clients: their paddles stop and can be taken by the next player
+    /// to join.
+    fn remove_disconnected_clients(&mut self) {
+        let mut paddle_inputs = self.world.write_resource::<PaddleInputs>();
+
+        for client in self
+            .clients
+            .iter()
+            .filter(|client| !client.connection.connected)
+        {
+            if let Some(side) = client.connection.side {
+                paddle_inputs.set(side, PaddleInput::default());
+            }
+
+            self.players_changed = true;
+        }
+
+        self.clients.retain(|client| client.connection.connected);
+    }
+
+    fn elapsed_ms(&self) -> u64 {
+        self.started_at.elapsed().as_millis() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst::{core::Transform, ecs::Join};
+
+    use super::*;
+    use crate::entities::Ball;
+
+    /// Server playing to a single point, with a ball about to go into the left goal.
+    fn server_at_match_point() -> GameServer {
+        let gameplay_config = GameplayConfig {
+            points_to_win: Some(1),
+            ..GameplayConfig::default()
+        };
+        let mut server = GameServer::bind(0, ControlsConfig::default(), gameplay_config).unwrap();
+
+        initialize_ball(&mut server.world, [-BALL_VELOCITY_X, 0.0], None);
+        server.ball_spawned = true;
+
+        // below the left paddle, so nothing stops it
+        for (_, transform) in (
+            &server.world.read_storage::<Ball>(),
+            &mut server.world.write_storage::<Transform>(),
+        )
+            .join()
+        {
+            transform.set_translation_xyz(1.0, 10.0, 0.0);
+        }
+
+        server
+    }
+
+    #[test]
+    fn should_end_points_limited_match_and_report_it() {
+        // arrange
+        let mut server = server_at_match_point();
+
+        // act
+        server.tick();
+
+        // assert - the snapshots sent to the clients carry the match's end
+        let snapshot = MatchSnapshot::capture(&server.world);
+
+        assert_eq!(true, snapshot.finished);
+        assert_eq!(true, snapshot.balls.is_empty());
+        assert_eq!(
+            1,
+            server
+                .world
+                .read_resource::<Scoreboard>()
+                .score(Side::Right)
+        );
+    }
+
+    #[test]
+    fn should_start_next_match_after_rematch_delay() {
+        // arrange
+        let mut server = server_at_match_point();
+        server.tick();
+
+        // act
+        for _ in 0..=(REMATCH_DELAY / TICK_SECONDS).ceil() as u32 {
+            server.tick();
+        }
+
+        // assert
+        assert_eq!(false, MatchSnapshot::capture(&server.world).finished);
+        assert_eq!(
+            0,
+            server
+                .world
+                .read_resource::<Scoreboard>()
+                .score(Side::Right)
+        );
+        assert_eq!(false, server.ball_spawned);
+    }
+}
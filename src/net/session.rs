@@ -0,0 +1,271 @@
+use std::{
+    io,
+    net::{SocketAddr, UdpSocket},
+    time::Instant,
+};
+
+use amethyst::{
+    core::Time,
+    ecs::{Dispatcher, World, WorldExt},
+};
+use log::{error, warn};
+
+use super::{MatchSnapshot, NetMessage, RollbackSession};
+use crate::{controls::PaddleInputs, entities::Side, startup::build_simulation_dispatcher};
+
+/// Duration of a simulation tick: netplay sessions and game server clients are advanced on
+/// every fixed update and the game server ticks at the same rate.
+pub const TICK_SECONDS: f32 = 1.0 / 60.0;
+
+/// Ticks between reading a local input and applying it, unless given on the command line.
+pub const DEFAULT_INPUT_DELAY: u32 = 2;
+
+const PING_INTERVAL_UPDATES: u32 = 30;
+const MAX_DATAGRAM_SIZE: usize = 2048;
+
+/// Netplay settings given on the command line.
+#[derive(Clone, PartialEq, Debug)]
+pub struct NetplayOptions {
+    pub local_port: u16,
+    pub peer: SocketAddr,
+    pub local_side: Side,
+    /// Ticks between reading a local input and applying it.
+    pub input_delay: u32,
+}
+
+/// Connection statistics shown by the netplay overlay.
+#[derive(Default, Debug)]
+pub struct NetStats {
+    pub ping_ms: Option<u64>,
+    pub rollback_frames: u32,
+    pub waiting_for_peer: bool,
+    pub desync_tick: Option<u32>,
+}
+
+impl NetStats {
+    pub fn summary(&self) -> String {
+        let ping = match self.ping_ms {
+            Some(ping_ms) => format!("PING {} MS", ping_ms),
+            None => "PING -".to_string(),
+        };
+        let mut summary = format!("{}  ROLLBACK {}", ping, self.rollback_frames);
+
+        if self.waiting_for_peer {
+            summary.push_str("  WAITING");
+        }
+
+        if let Some(tick) = self.desync_tick {
+            summary.push_str(&format!("  DESYNC AT {}", tick));
+        }
+
+        summary
+    }
+}
+
+/// Peer to peer match over UDP with rollback netcode: both peers exchange their inputs every
+/// tick, simulate ahead with predicted remote inputs and re-simulate from a snapshot whenever
+/// a prediction turns out to be wrong.
+pub struct NetplaySession {
+    socket: UdpSocket,
+    local_side: Side,
+    remote_side: Side,
+    rollback: RollbackSession<MatchSnapshot>,
+    dispatcher: Dispatcher<'static, 'static>,
+    started_at: Instant,
+    updates: u32,
+    stats: NetStats,
+}
+
+impl NetplaySession {
+    pub fn new(options: &NetplayOptions, world: &mut World) -> io::Result<Self> {
+        let socket = UdpSocket::bind(("0.0.0.0", options.local_port))?;
+
+        socket.connect(options.peer)?;
+        socket.set_nonblocking(true)?;
+
+        Ok(NetplaySession {
+            socket,
+            local_side: options.local_side,
+            remote_side: options.local_side.opponent(),
+            rollback: RollbackSession::new(options.input_delay),
+            dispatcher: build_simulation_dispatcher(world),
+            started_at: Instant::now(),
+            updates: 0,
+            stats: NetStats::default(),
+        })
+    }
+
+    pub fn stats(&self) -> &NetStats {
+        &self.stats
+    }
+
+    /// Advances the match by (at most) one tick. Called on every fixed update.
+    pub fn advance(&mut self, world: &mut World) {
+        self.receive();
+
+        if let Some(tick) = self.rollback.take_rollback() {
+            self.resimulate_from(tick, world);
+        }
+
+        self.exchange_checksums();
+
+        // don't run too far ahead of the peer: wait for its inputs instead
+        self.stats.waiting_for_peer = !self.rollback.can_advance();
+
+        if !self.stats.waiting_for_peer {
+            let local_input = world.read_resource::<PaddleInputs>().get(self.local_side);
+
+            self.rollback.add_local_input(local_input);
+            self.simulate_tick(world);
+        }
+
+        self.send_inputs();
+
+        if self.updates % PING_INTERVAL_UPDATES == 0 {
+            self.send(&NetMessage::Ping {
+                sent_at_ms: self.elapsed_ms(),
+            });
+        }
+
+        self.updates += 1;
+    }
+
+    /// Runs the simulation systems for the current tick with both players' inputs.
+    fn simulate_tick(&mut self, world: &mut World) {
+        let tick = self.rollback.current_tick();
+
+        self.rollback
+            .save_snapshot(tick, MatchSnapshot::capture(world));
+
+        let (local_input, remote_input) = self.rollback.inputs_for(tick);
+
+        {
+            let mut paddle_inputs = world.write_resource::<PaddleInputs>();
+
+            paddle_inputs.set(self.local_side, local_input);
+            paddle_inputs.set(self.remote_side, remote_input);
+        }
+
+        // the systems see a fixed timestep, whatever the framerate is
+        let frame_delta = world.read_resource::<Time>().delta_real_time();
+
+        world
+            .write_resource::<Time>()
+            .set_delta_seconds(TICK_SECONDS);
+        self.dispatcher.dispatch(world);
+        world.write_resource::<Time>().set_delta_time(frame_delta);
+
+        self.rollback.finish_tick();
+    }
+
+    /// Restores the snapshot of a mispredicted tick and simulates again up to the present.
+    fn resimulate_from(&mut self, tick: u32, world: &mut World) {
+        let snapshot = match self.rollback.snapshot(tick) {
+            Some(snapshot) => snapshot.clone(),
+            None => {
+                error!("No snapshot to roll back to tick {}", tick);
+                return;
+            }
+        };
+
+        snapshot.restore(world);
+
+        let present_tick = self.rollback.rewind(tick);
+
+        while self.rollback.current_tick() < present_tick {
+            self.simulate_tick(world);
+        }
+
+        self.stats.rollback_frames = present_tick - tick;
+    }
+
+    /// Sends the checksums of newly confirmed ticks and compares them with the peer's.
+    fn exchange_checksums(&mut self) {
+        while let Some(tick) = self.rollback.next_checksum_tick() {
+            let checksum = match self.rollback.snapshot(tick) {
+                Some(snapshot) => snapshot.checksum(),
+                None => continue,
+            };
+
+            if let Some(desync_tick) = self.rollback.add_local_checksum(tick, checksum) {
+                self.report_desync(desync_tick);
+            }
+
+            self.send(&NetMessage::Checksum { tick, checksum });
+        }
+    }
+
+    fn receive(&mut self) {
+        let mut buffer = [0; MAX_DATAGRAM_SIZE];
+
+        loop {
+            match self.socket.recv(&mut buffer) {
+                Ok(size) => {
+                    if let Some(message) = NetMessage::from_bytes(&buffer[..size]) {
+                        self.handle_message(message);
+                    }
+                }
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                // e.g. connection refused while the peer isn't running yet
+                Err(_) => break,
+            }
+        }
+    }
+
+    fn handle_message(&mut self, message: NetMessage) {
+        match message {
+            NetMessage::Inputs {
+                first_tick,
+                inputs,
+                received_until,
+            } => {
+                for (offset, input) in inputs.into_iter().enumerate() {
+                    self.rollback
+                        .add_remote_input(first_tick + offset as u32, input);
+                }
+
+                self.rollback.acknowledge(received_until);
+            }
+            NetMessage::Ping { sent_at_ms } => self.send(&NetMessage::PingReply { sent_at_ms }),
+            NetMessage::PingReply { sent_at_ms } => {
+                self.stats.ping_ms = Some(self.elapsed_ms().saturating_sub(sent_at_ms));
+            }
+            NetMessage::Checksum { tick, checksum } => {
+                if let Some(desync_tick) = self.rollback.add_remote_checksum(tick, checksum) {
+                    self.report_desync(desync_tick);
+                }
+            }
+        }
+    }
+
+    fn send_inputs(&self) {
+        let (first_tick, inputs) = self.rollback.unacknowledged_local_inputs();
+
+        self.send(&NetMessage::Inputs {
+            first_tick,
+            inputs,
+            received_until: self.rollback.remote_received_until(),
+        });
+    }
+
+    fn send(&self, message: &NetMessage) {
+        // datagrams are lost all the time anyway: inputs are resent until acknowledged
+        if let Err(err) = self.socket.send(&message.to_bytes()) {
+            if err.kind() != io::ErrorKind::ConnectionRefused {
+                warn!("Failed to send netplay message: {}", err);
+            }
+        }
+    }
+
+    fn report_desync(&mut self, tick: u32) {
+        if self.stats.desync_tick.is_none() {
+            error!("Netplay desync detected at tick {}", tick);
+        }
+
+        self.stats.desync_tick.get_or_insert(tick);
+    }
+
+    fn elapsed_ms(&self) -> u64 {
+        self.started_at.elapsed().as_millis() as u64
+    }
+}
@@ -0,0 +1,358 @@
+use amethyst::{
+    core::Transform,
+    ecs::{Join, World, WorldExt},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::entities::{Ball, Paddle, Scoreboard, Side};
+
+/// Entities that moved further than this between two snapshots jumped (e.g. the ball being
+/// served again) and aren't interpolated.
+const MAX_INTERPOLATION_DISTANCE: f32 = 20.0;
+
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct BallSnapshot {
+    pub position: [f32; 2],
+    pub velocity: [f32; 2],
+    pub radius: f32,
+    pub spin: f32,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct PaddleSnapshot {
+    pub side: Side,
+    pub y: f32,
+    pub velocity: f32,
+}
+
+/// Copy of everything the match simulation depends on, used to roll the world back.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct MatchSnapshot {
+    pub balls: Vec<BallSnapshot>,
+    pub paddles: Vec<PaddleSnapshot>,
+    pub scores: Vec<(Side, u32)>,
+    /// The match ended (e.g. a player reached the points to win).
+    #[serde(default)]
+    pub finished: bool,
+}
+
+/// What changed since the previous snapshot sent over the same (reliable, ordered) stream.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct SnapshotDelta {
+    /// Every ball, if any of them changed: they're moving most of the time anyway.
+    pub balls: Option<Vec<BallSnapshot>>,
+    /// Only the paddles that changed.
+    pub paddles: Vec<PaddleSnapshot>,
+    pub scores: Option<Vec<(Side, u32)>>,
+    #[serde(default)]
+    pub finished: Option<bool>,
+}
+
+impl MatchSnapshot {
+    /// Copies the balls, paddles, scores and whether the match ended out of the world.
+    pub fn capture(world: &World) -> Self {
+        let transform_storage = world.read_storage::<Transform>();
+        let ball_storage = world.read_storage::<Ball>();
+        let paddle_storage = world.read_storage::<Paddle>();
+        let scoreboard = world.read_resource::<Scoreboard>();
+
+        let balls = (&ball_storage, &transform_storage)
+            .join()
+            .map(|(ball, transform)| BallSnapshot {
+                position: [transform.translation().x, transform.translation().y],
+                velocity: ball.velocity,
+                radius: ball.radius,
+                spin: ball.spin,
+            })
+            .collect();
+
+        let paddles = (&paddle_storage, &transform_storage)
+            .join()
+            .map(|(paddle, transform)| PaddleSnapshot {
+                side: paddle.side,
+                y: transform.translation().y,
+                velocity: paddle.velocity,
+            })
+            .collect();
+
+        let scores = [Side::Left, Side::Right]
+            .iter()
+            .map(|side| (*side, scoreboard.score(*side)))
+            .collect();
+
+        MatchSnapshot {
+            balls,
+            paddles,
+            scores,
+            finished: scoreboard.is_finished(),
+        }
+    }
+
+    /// Writes the snapshot back into the world's existing balls, paddles and scoreboard.
+    pub fn restore(&self, world: &mut World) {
+        let mut transform_storage = world.write_storage::<Transform>();
+        let mut ball_storage = world.write_storage::<Ball>();
+        let mut paddle_storage = world.write_storage::<Paddle>();
+
+        // entities are joined in the same order they were captured in
+        for ((ball, transform), snapshot) in (&mut ball_storage, &mut transform_storage)
+            .join()
+            .zip(self.balls.iter())
+        {
+            transform.set_translation_x(snapshot.position[0]);
+            transform.set_translation_y(snapshot.position[1]);
+            ball.velocity = snapshot.velocity;
+            ball.radius = snapshot.radius;
+            ball.spin = snapshot.spin;
+        }
+
+        for (paddle, transform) in (&mut paddle_storage, &mut transform_storage).join() {
+            if let Some(snapshot) = self.paddles.iter().find(|p| p.side == paddle.side) {
+                transform.set_translation_y(snapshot.y);
+                paddle.velocity = snapshot.velocity;
+            }
+        }
+
+        let mut scoreboard = world.write_resource::<Scoreboard>();
+
+        for (side, score) in self.scores.iter() {
+            scoreboard.set_score(*side, *score);
+        }
+
+        scoreboard.set_finished(self.finished);
+    }
+
+    /// Changes from a previous snapshot to this one.
+    pub fn delta_from(&self, previous: &MatchSnapshot) -> SnapshotDelta {
+        SnapshotDelta {
+            balls: if self.balls != previous.balls {
+                Some(self.balls.clone())
+            } else {
+                None
+            },
+            paddles: self
+                .paddles
+                .iter()
+                .filter(|paddle| !previous.paddles.contains(paddle))
+                .cloned()
+                .collect(),
+            scores: if self.scores != previous.scores {
+                Some(self.scores.clone())
+            } else {
+                None
+            },
+            finished: if self.finished != previous.finished {
+                Some(self.finished)
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Snapshot resulting from applying the changes to this (previous) snapshot.
+    pub fn apply_delta(&self, delta: &SnapshotDelta) -> MatchSnapshot {
+        let mut snapshot = self.clone();
+
+        if let Some(balls) = delta.balls.as_ref() {
+            snapshot.balls = balls.clone();
+        }
+
+        for changed in delta.paddles.iter() {
+            match snapshot.paddles.iter_mut().find(|p| p.side == changed.side) {
+                Some(paddle) => *paddle = changed.clone(),
+                None => snapshot.paddles.push(changed.clone()),
+            }
+        }
+
+        if let Some(scores) = delta.scores.as_ref() {
+            snapshot.scores = scores.clone();
+        }
+
+        if let Some(finished) = delta.finished {
+            snapshot.finished = finished;
+        }
+
+        snapshot
+    }
+
+    /// Blends this snapshot with a later one (`t` from 0.0 to 1.0) to render between server
+    /// ticks. Whatever can't be blended (scores, the match's end, balls that jumped or only exist
+    /// in one of the snapshots) is taken from the nearest snapshot.
+    pub fn interpolate(&self, later: &MatchSnapshot, t: f32) -> MatchSnapshot {
+        let nearest = if t < 0.5 { self } else { later };
+
+        let balls = if self.balls.len() == later.balls.len() {
+            self.balls
+                .iter()
+                .zip(later.balls.iter())
+                .zip(nearest.balls.iter())
+                .map(|((from, to), nearest_ball)| {
+                    let jumped = (0..2).any(|axis| {
+                        (to.position[axis] - from.position[axis]).abs() > MAX_INTERPOLATION_DISTANCE
+                    });
+
+                    if jumped {
+                        nearest_ball.clone()
+                    } else {
+                        BallSnapshot {
+                            position: [
+                                lerp(from.position[0], to.position[0], t),
+                                lerp(from.position[1], to.position[1], t),
+                            ],
+                            ..nearest_ball.clone()
+                        }
+                    }
+                })
+                .collect()
+        } else {
+            nearest.balls.clone()
+        };
+
+        let paddles = later
+            .paddles
+            .iter()
+            .map(
+                |to| match self.paddles.iter().find(|from| from.side == to.side) {
+                    Some(from) => PaddleSnapshot {
+                        y: lerp(from.y, to.y, t),
+                        ..to.clone()
+                    },
+                    None => to.clone(),
+                },
+            )
+            .collect();
+
+        MatchSnapshot {
+            balls,
+            paddles,
+            scores: nearest.scores.clone(),
+            finished: nearest.finished,
+        }
+    }
+
+    /// Hash of the snapshot, compared between peers to detect desyncs.
+    pub fn checksum(&self) -> u64 {
+        checksum_of(self)
+    }
+}
+
+/// FNV-1a hash of a value's bincode encoding.
+pub fn checksum_of<T: Serialize>(value: &T) -> u64 {
+    let bytes = bincode::serialize(value).expect("value should be serializable");
+
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot_with_ball_at(x: f32) -> MatchSnapshot {
+        MatchSnapshot {
+            balls: vec![BallSnapshot {
+                position: [x, 50.0],
+                velocity: [75.0, 50.0],
+                radius: 2.0,
+                spin: 0.0,
+            }],
+            paddles: vec![PaddleSnapshot {
+                side: Side::Left,
+                y: 50.0,
+                velocity: 0.0,
+            }],
+            scores: vec![(Side::Left, 1), (Side::Right, 0)],
+            finished: false,
+        }
+    }
+
+    #[test]
+    fn should_have_same_checksum_for_same_state() {
+        // arrange
+        let snapshot = snapshot_with_ball_at(10.0);
+
+        // act
+        let checksum = snapshot.checksum();
+
+        // assert
+        assert_eq!(snapshot_with_ball_at(10.0).checksum(), checksum);
+    }
+
+    #[test]
+    fn should_have_different_checksum_for_different_state() {
+        // arrange
+        let snapshot = snapshot_with_ball_at(10.0);
+
+        // act
+        let checksum = snapshot.checksum();
+
+        // assert
+        assert_ne!(snapshot_with_ball_at(10.001).checksum(), checksum);
+    }
+
+    #[test]
+    fn should_interpolate_ball_and_paddles() {
+        // arrange
+        let from = snapshot_with_ball_at(10.0);
+        let mut to = snapshot_with_ball_at(14.0);
+        to.paddles[0].y = 60.0;
+
+        // act
+        let interpolated = from.interpolate(&to, 0.25);
+
+        // assert
+        assert_eq!([11.0, 50.0], interpolated.balls[0].position);
+        assert_eq!(52.5, interpolated.paddles[0].y);
+    }
+
+    #[test]
+    fn should_not_interpolate_ball_served_again() {
+        // arrange
+        let from = snapshot_with_ball_at(99.0);
+        let to = snapshot_with_ball_at(50.0); // scored and back in the center
+
+        // act
+        let before_serve = from.interpolate(&to, 0.25);
+        let after_serve = from.interpolate(&to, 0.75);
+
+        // assert
+        assert_eq!([99.0, 50.0], before_serve.balls[0].position);
+        assert_eq!([50.0, 50.0], after_serve.balls[0].position);
+    }
+
+    #[test]
+    fn should_only_send_changes_in_delta() {
+        // arrange
+        let previous = snapshot_with_ball_at(10.0);
+        let current = snapshot_with_ball_at(11.0); // same paddles and scores
+
+        // act
+        let delta = current.delta_from(&previous);
+
+        // assert
+        assert_eq!(Some(current.balls.clone()), delta.balls);
+        assert_eq!(true, delta.paddles.is_empty());
+        assert_eq!(None, delta.scores);
+    }
+
+    #[test]
+    fn should_rebuild_snapshot_from_delta() {
+        // arrange
+        let previous = snapshot_with_ball_at(10.0);
+        let mut current = snapshot_with_ball_at(11.0);
+        current.paddles[0].y = 42.0;
+        current.scores = vec![(Side::Left, 2), (Side::Right, 0)];
+        current.finished = true;
+
+        // act
+        let rebuilt = previous.apply_delta(&current.delta_from(&previous));
+
+        // assert
+        assert_eq!(current, rebuilt);
+    }
+}
@@ -0,0 +1,105 @@
+use std::{io, net::TcpListener, time::Instant};
+
+use amethyst::ecs::World;
+
+use super::{
+    connection::{accept_connections, ClientConnection},
+    server::PING_INTERVAL_TICKS,
+    ClientMessage, MatchSnapshot, PlayerInfo, ServerMessage,
+};
+use crate::entities::Side;
+
+/// Streams a match played on this game (locally or over netplay) to spectators, who connect to
+/// it the same way they connect to the game server. Spectators only ever receive: whatever
+/// they send besides joining is ignored.
+pub struct SpectatorHost {
+    listener: TcpListener,
+    spectators: Vec<ClientConnection>,
+    tick: u32,
+    started_at: Instant,
+}
+
+impl SpectatorHost {
+    pub fn bind(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+
+        Ok(SpectatorHost {
+            listener,
+            spectators: Vec::new(),
+            tick: 0,
+            started_at: Instant::now(),
+        })
+    }
+
+    /// Accepts new spectators and sends everyone the match as it is now. Called on every fixed
+    /// update.
+    pub fn update(&mut self, world: &World) {
+        self.spectators.extend(accept_connections(&self.listener));
+
+        let mut players_changed = self.tick % PING_INTERVAL_TICKS == 0;
+        let elapsed_ms = self.started_at.elapsed().as_millis() as u64;
+
+        for spectator in self.spectators.iter_mut() {
+            for message in spectator.receive() {
+                match message {
+                    ClientMessage::Join { name, .. } if !spectator.joined => {
+                        spectator.join(&name, None);
+                        spectator.send(&ServerMessage::Welcome { side: None });
+                        players_changed = true;
+                    }
+                    ClientMessage::PingReply { sent_at_ms } => {
+                        spectator.ping_ms = Some(elapsed_ms.saturating_sub(sent_at_ms));
+                    }
+                    _ => {}
+                }
+            }
+
+            if self.tick % PING_INTERVAL_TICKS == 0 {
+                spectator.send(&ServerMessage::Ping {
+                    sent_at_ms: elapsed_ms,
+                });
+            }
+        }
+
+        let snapshot = MatchSnapshot::capture(world);
+
+        for spectator in self.spectators.iter_mut().filter(|s| s.joined) {
+            spectator.send_snapshot(self.tick, &snapshot, Vec::new());
+        }
+
+        if self.spectators.iter().any(|spectator| !spectator.connected) {
+            self.spectators.retain(|spectator| spectator.connected);
+            players_changed = true;
+        }
+
+        if players_changed {
+            self.broadcast_players();
+        }
+
+        self.tick += 1;
+    }
+
+    /// Both players are on this game: they have no ping.
+    fn broadcast_players(&mut self) {
+        let message = ServerMessage::Players {
+            players: vec![
+                PlayerInfo {
+                    side: Side::Left,
+                    name: "P1".to_string(),
+                    ping_ms: None,
+                },
+                PlayerInfo {
+                    side: Side::Right,
+                    name: "P2".to_string(),
+                    ping_ms: None,
+                },
+            ],
+            spectators: self.spectators.iter().filter(|s| s.joined).count() as u32,
+        };
+
+        for spectator in self.spectators.iter_mut().filter(|s| s.joined) {
+            spectator.send(&message);
+        }
+    }
+}
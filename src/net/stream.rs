@@ -0,0 +1,188 @@
+use std::{
+    error::Error,
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpStream, ToSocketAddrs},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+const LENGTH_PREFIX_SIZE: usize = 4;
+const READ_CHUNK_SIZE: usize = 4096;
+
+/// Larger frames are a protocol error instead of something to buffer.
+const MAX_FRAME_SIZE: usize = 64 * 1024;
+/// A peer that doesn't read its messages is dropped instead of buffering them forever.
+const MAX_UNSENT_SIZE: usize = 1024 * 1024;
+
+/// Non-blocking TCP connection exchanging bincode messages, each one prefixed by its length.
+pub struct MessageStream {
+    stream: TcpStream,
+    received: Vec<u8>,
+    unsent: Vec<u8>,
+    received_bytes: u64,
+}
+
+impl MessageStream {
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?; // small messages every tick: don't wait to batch them
+
+        Ok(MessageStream {
+            stream,
+            received: Vec::new(),
+            unsent: Vec::new(),
+            received_bytes: 0,
+        })
+    }
+
+    pub fn connect<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        Self::new(TcpStream::connect(address)?)
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.stream.peer_addr()
+    }
+
+    /// Bytes received since the connection was opened.
+    pub fn received_bytes(&self) -> u64 {
+        self.received_bytes
+    }
+
+    /// Queues a message and sends as much of the queue as the socket accepts right now.
+    pub fn send<T: Serialize>(&mut self, message: &T) -> io::Result<()> {
+        let payload = bincode::serialize(message).map_err(invalid_data)?;
+
+        self.unsent.extend(encode_frame(&payload));
+
+        if self.unsent.len() > MAX_UNSENT_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "peer isn't reading its messages",
+            ));
+        }
+
+        self.flush()
+    }
+
+    /// Sends what's left of the queued messages without blocking.
+    pub fn flush(&mut self) -> io::Result<()> {
+        while !self.unsent.is_empty() {
+            match self.stream.write(&self.unsent) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(size) => {
+                    self.unsent.drain(..size);
+                }
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns every complete message received so far. An error means the connection is gone:
+    /// closed by the peer or sending something that isn't a message.
+    pub fn receive<T: DeserializeOwned>(&mut self) -> io::Result<Vec<T>> {
+        let mut buffer = [0; READ_CHUNK_SIZE];
+
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::ConnectionAborted,
+                        "connection closed",
+                    ))
+                }
+                Ok(size) => {
+                    self.received.extend_from_slice(&buffer[..size]);
+                    self.received_bytes += size as u64;
+                }
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) => return Err(err),
+            }
+        }
+
+        decode_frames(&mut self.received)?
+            .iter()
+            .map(|frame| bincode::deserialize(frame).map_err(invalid_data))
+            .collect()
+    }
+}
+
+fn encode_frame(payload: &[u8]) -> Vec<u8> {
+    let mut frame = (payload.len() as u32).to_le_bytes().to_vec();
+
+    frame.extend_from_slice(payload);
+
+    frame
+}
+
+/// Takes the complete frames out of the buffer, leaving a partially received one in it.
+fn decode_frames(buffer: &mut Vec<u8>) -> io::Result<Vec<Vec<u8>>> {
+    let mut frames = Vec::new();
+    let mut start = 0;
+
+    while buffer.len() - start >= LENGTH_PREFIX_SIZE {
+        let mut prefix = [0; LENGTH_PREFIX_SIZE];
+        prefix.copy_from_slice(&buffer[start..start + LENGTH_PREFIX_SIZE]);
+
+        let length = u32::from_le_bytes(prefix) as usize;
+
+        if length > MAX_FRAME_SIZE {
+            return Err(invalid_data("frame too large"));
+        }
+
+        let end = start + LENGTH_PREFIX_SIZE + length;
+
+        if buffer.len() < end {
+            break;
+        }
+
+        frames.push(buffer[start + LENGTH_PREFIX_SIZE..end].to_vec());
+        start = end;
+    }
+
+    buffer.drain(..start);
+
+    Ok(frames)
+}
+
+fn invalid_data<E: Into<Box<dyn Error + Send + Sync>>>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_decode_frames_received_in_pieces() {
+        // arrange
+        let mut stream_bytes = encode_frame(b"ball");
+        stream_bytes.extend(encode_frame(b"paddle"));
+
+        let mut buffer = stream_bytes[..7].to_vec(); // first frame is 8 bytes long
+
+        // act
+        let first_frames = decode_frames(&mut buffer).unwrap();
+        buffer.extend_from_slice(&stream_bytes[7..]);
+        let second_frames = decode_frames(&mut buffer).unwrap();
+
+        // assert
+        assert_eq!(0, first_frames.len());
+        assert_eq!(vec![b"ball".to_vec(), b"paddle".to_vec()], second_frames);
+        assert_eq!(true, buffer.is_empty());
+    }
+
+    #[test]
+    fn should_reject_oversized_frame() {
+        // arrange
+        let mut buffer = ((MAX_FRAME_SIZE + 1) as u32).to_le_bytes().to_vec();
+
+        // act
+        let frames = decode_frames(&mut buffer);
+
+        // assert
+        assert_eq!(true, frames.is_err());
+    }
+}
@@ -0,0 +1,195 @@
+//! Random numbers of the matches.
+//!
+//! Every random decision (power-up spawns, brick drops, launcher shots, ...) draws from the
+//! `GameRng` resource, seeded once per match, so a match can be played again from its seed.
+//! Systems must never use `rand::thread_rng` (or `rand::random`) directly: each subsystem draws
+//! from its own stream instead, so one drawing more numbers doesn't change what the others draw.
+
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use amethyst::{prelude::WorldExt, shred::World};
+use log::info;
+use rand::{Error, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::settings::GameplayConfig;
+
+/// Increment of the SplitMix64 generator (the golden ratio).
+const GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Subsystems drawing random numbers, each from its own stream.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Deserialize, Serialize)]
+pub enum RngStream {
+    /// Power-up pickups spawned in the arena.
+    PowerUps,
+    /// Power-ups dropped by broken bricks.
+    BrickDrops,
+    /// Shots fired by the practice launcher.
+    Launcher,
+}
+
+impl RngStream {
+    fn id(self) -> u64 {
+        match self {
+            RngStream::PowerUps => 1,
+            RngStream::BrickDrops => 2,
+            RngStream::Launcher => 3,
+        }
+    }
+}
+
+/// Random number generator of a match, seeded at its start (see `initialize_rng`). Its streams
+/// are forked from the seed the first time they're drawn from.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct GameRng {
+    seed: u64,
+    streams: HashMap<RngStream, StreamRng>,
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        GameRng::new(clock_seed())
+    }
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        GameRng {
+            seed,
+            streams: HashMap::new(),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Random numbers of a subsystem.
+    pub fn stream(&mut self, stream: RngStream) -> &mut StreamRng {
+        let seed = self.seed;
+
+        self.streams
+            .entry(stream)
+            .or_insert_with(|| StreamRng::fork(seed, stream))
+    }
+}
+
+/// Stream of random numbers of a subsystem (a SplitMix64 generator, whose whole state is a
+/// single number).
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct StreamRng {
+    state: u64,
+}
+
+impl StreamRng {
+    /// Stream of the given subsystem, starting from a state derived from the match seed.
+    fn fork(seed: u64, stream: RngStream) -> Self {
+        let mut parent = StreamRng {
+            state: seed ^ stream.id().wrapping_mul(GAMMA),
+        };
+
+        StreamRng {
+            state: parent.next_u64(),
+        }
+    }
+}
+
+impl RngCore for StreamRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(GAMMA);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Seeds the random numbers of a match starting: with the configured seed, if any, or a new
+/// one otherwise. The seed is logged so the match can be played again.
+pub fn initialize_rng(world: &mut World) {
+    let seed = world
+        .read_resource::<GameplayConfig>()
+        .seed
+        .unwrap_or_else(clock_seed);
+
+    info!("Match seed: {}", seed);
+
+    world.insert(GameRng::new(seed));
+}
+
+/// Seed taken from the system clock.
+fn clock_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+
+    #[test]
+    fn should_draw_same_numbers_from_same_seed() {
+        // arrange
+        let mut rng = GameRng::new(42);
+        let mut replay = GameRng::new(42);
+
+        // act
+        let drawn = (0..8)
+            .map(|_| rng.stream(RngStream::PowerUps).gen_range(0, 100))
+            .collect::<Vec<u32>>();
+        let replayed = (0..8)
+            .map(|_| replay.stream(RngStream::PowerUps).gen_range(0, 100))
+            .collect::<Vec<u32>>();
+
+        // assert
+        assert_eq!(drawn, replayed);
+        assert_ne!(
+            GameRng::new(43).stream(RngStream::PowerUps).next_u64(),
+            GameRng::new(42).stream(RngStream::PowerUps).next_u64()
+        );
+    }
+
+    #[test]
+    fn should_keep_streams_independent_of_each_other() {
+        // arrange
+        let mut rng = GameRng::new(7);
+        let mut busy_rng = GameRng::new(7);
+
+        for _ in 0..10 {
+            busy_rng.stream(RngStream::PowerUps).next_u64();
+        }
+
+        // act
+        let drop = rng.stream(RngStream::BrickDrops).next_u64();
+        let busy_drop = busy_rng.stream(RngStream::BrickDrops).next_u64();
+
+        // assert
+        assert_eq!(drop, busy_drop);
+        assert_ne!(drop, rng.stream(RngStream::Launcher).next_u64());
+    }
+}
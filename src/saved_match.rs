@@ -0,0 +1,476 @@
+//! Match state saved to a file, to resume a local match after quitting it.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use amethyst::{
+    assets::Handle,
+    core::{math::Vector3, Transform},
+    ecs::{Entity, Join},
+    prelude::{Builder, WorldExt},
+    renderer::{SpriteRender, SpriteSheet},
+    shred::World,
+};
+use log::error;
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    entities::{
+        restore_ring_lives, spawn_brick, Ball, Brick, Force, MatchStats, Paddle, PaddleDriver,
+        Ring, Scoreboard, Side,
+    },
+    rng::GameRng,
+    settings::{GameMode, Handicaps, Level, PlayerProfiles, USER_SAVED_MATCH_FILE},
+};
+
+/// Rules a match was started with, which set up its arena and paddles again when it's resumed.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct MatchRules {
+    pub mode: GameMode,
+    pub multi_ball: bool,
+    pub power_ups: bool,
+    pub level: Option<Level>,
+    pub forces: Vec<Force>,
+    pub ring_players: usize,
+    pub handicaps: Handicaps,
+    #[serde(default)]
+    pub profiles: PlayerProfiles,
+}
+
+/// Position, rotation (around the z axis, in radians) and scale of an entity.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct TransformSnapshot {
+    pub translation: [f32; 3],
+    pub rotation: f32,
+    pub scale: [f32; 3],
+}
+
+impl TransformSnapshot {
+    pub fn capture(transform: &Transform) -> Self {
+        let translation = transform.translation();
+        let scale = transform.scale();
+
+        TransformSnapshot {
+            translation: [translation.x, translation.y, translation.z],
+            rotation: transform.rotation().euler_angles().2,
+            scale: [scale.x, scale.y, scale.z],
+        }
+    }
+
+    pub fn restore(&self, transform: &mut Transform) {
+        let [x, y, z] = self.translation;
+
+        transform.set_translation_xyz(x, y, z);
+        transform.set_rotation_2d(self.rotation);
+        transform.set_scale(Vector3::new(self.scale[0], self.scale[1], self.scale[2]));
+    }
+}
+
+/// State of a match in progress: its rules, balls, paddles, scores (and the ring players' lives),
+/// breakout bricks, serve timer, random numbers and statistics. Pickups, obstacles and active
+/// power-ups start over when it's resumed.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct SavedMatch {
+    pub rules: MatchRules,
+    pub balls: Vec<(Ball, TransformSnapshot)>,
+    pub paddles: Vec<(Paddle, TransformSnapshot)>,
+    pub scoreboard: Scoreboard,
+    /// Lives left of the players seated around the ring, in ring matches.
+    #[serde(default)]
+    pub ring_lives: Option<Vec<u32>>,
+    /// Bricks left (with their hit points) in breakout matches.
+    #[serde(default)]
+    pub bricks: Option<Vec<(Brick, TransformSnapshot)>>,
+    /// Seconds left before the ball is served, if it's waiting to be.
+    pub ball_spawn_timer: Option<f32>,
+    pub rng: GameRng,
+    #[serde(default)]
+    pub stats: MatchStats,
+}
+
+impl SavedMatch {
+    /// Copies the match state out of the world.
+    pub fn capture(world: &World, rules: MatchRules, ball_spawn_timer: Option<f32>) -> Self {
+        let transform_storage = world.read_storage::<Transform>();
+        let ball_storage = world.read_storage::<Ball>();
+        let paddle_storage = world.read_storage::<Paddle>();
+
+        let balls = (&ball_storage, &transform_storage)
+            .join()
+            .map(|(ball, transform)| (ball.clone(), TransformSnapshot::capture(transform)))
+            .collect();
+
+        let paddles = (&paddle_storage, &transform_storage)
+            .join()
+            .map(|(paddle, transform)| (paddle.clone(), TransformSnapshot::capture(transform)))
+            .collect();
+
+        let bricks = if rules.mode == GameMode::Breakout {
+            let brick_storage = world.read_storage::<Brick>();
+
+            Some(
+                (&brick_storage, &transform_storage)
+                    .join()
+                    .map(|(brick, transform)| {
+                        (brick.clone(), TransformSnapshot::capture(transform))
+                    })
+                    .collect(),
+            )
+        } else {
+            None
+        };
+
+        SavedMatch {
+            rules,
+            balls,
+            paddles,
+            scoreboard: world.read_resource::<Scoreboard>().clone(),
+            ring_lives: world.try_fetch::<Ring>().map(|ring| ring.lives()),
+            bricks,
+            ball_spawn_timer,
+            rng: world.read_resource::<GameRng>().clone(),
+            stats: world
+                .try_fetch::<MatchStats>()
+                .map(|stats| stats.clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Writes the match state into a world set up with the same rules: the balls are replaced
+    /// by the saved ones (and so are the bricks), the paddles are moved back where they were and
+    /// the paddles of the players eliminated before the match was saved leave again, their ring
+    /// goals turned back into walls. Returns the serve timer.
+    pub fn restore(
+        &self,
+        world: &mut World,
+        sprite_sheet_handle: Option<Handle<SpriteSheet>>,
+    ) -> Option<f32> {
+        let balls = {
+            let entities = world.entities();
+            let ball_storage = world.read_storage::<Ball>();
+
+            (&entities, &ball_storage)
+                .join()
+                .map(|(entity, _)| entity)
+                .collect::<Vec<Entity>>()
+        };
+
+        if let Err(err) = world.delete_entities(&balls) {
+            error!("Failed to delete the balls: {}", err);
+        }
+
+        for (ball, snapshot) in self.balls.iter() {
+            let mut transform = Transform::default();
+
+            snapshot.restore(&mut transform);
+
+            let mut ball_builder = world.create_entity().with(ball.clone()).with(transform);
+
+            if let Some(handle) = sprite_sheet_handle.clone() {
+                ball_builder = ball_builder.with(SpriteRender::new(handle, 1));
+            }
+
+            ball_builder.build();
+        }
+
+        let left_paddles = {
+            let entities = world.entities();
+            let mut transform_storage = world.write_storage::<Transform>();
+            let mut paddle_storage = world.write_storage::<Paddle>();
+            let mut restored = vec![false; self.paddles.len()];
+            let mut left_paddles = Vec::new();
+
+            // paddles are told apart by their team, driver and ring seat (doubles teammates
+            // driven alike are matched in the order they were captured in)
+            for (entity, paddle, transform) in
+                (&entities, &mut paddle_storage, &mut transform_storage).join()
+            {
+                let saved = self.paddles.iter().enumerate().find(|(index, (saved, _))| {
+                    !restored[*index] && paddle_key(saved) == paddle_key(paddle)
+                });
+
+                match saved {
+                    Some((index, (saved, snapshot))) => {
+                        restored[index] = true;
+                        *paddle = saved.clone();
+                        snapshot.restore(transform);
+                    }
+                    None => left_paddles.push(entity),
+                }
+            }
+
+            left_paddles
+        };
+
+        if let Err(err) = world.delete_entities(&left_paddles) {
+            error!("Failed to delete the eliminated players' paddles: {}", err);
+        }
+
+        if let Some(bricks) = self.bricks.as_ref() {
+            let rebuilt = {
+                let entities = world.entities();
+                let brick_storage = world.read_storage::<Brick>();
+
+                (&entities, &brick_storage)
+                    .join()
+                    .map(|(entity, _)| entity)
+                    .collect::<Vec<Entity>>()
+            };
+
+            if let Err(err) = world.delete_entities(&rebuilt) {
+                error!("Failed to delete the bricks: {}", err);
+            }
+
+            for (brick, snapshot) in bricks.iter() {
+                let mut transform = Transform::default();
+
+                snapshot.restore(&mut transform);
+                spawn_brick(world, brick.clone(), transform, sprite_sheet_handle.clone());
+            }
+        }
+
+        if let Some(lives) = self.ring_lives.as_ref() {
+            if world.has_value::<Ring>() {
+                restore_ring_lives(world, lives);
+            }
+        }
+
+        world.insert(self.scoreboard.clone());
+        world.insert(self.rng.clone());
+        world.insert(self.stats.clone());
+
+        self.ball_spawn_timer
+    }
+
+    /// Loads the saved match, if there's one.
+    pub fn load(path: &Path) -> amethyst::Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let saved = ron::de::from_str(&fs::read_to_string(path)?)?;
+
+        Ok(Some(saved))
+    }
+
+    pub fn save(&self, path: &Path) -> amethyst::Result<()> {
+        fs::write(
+            path,
+            ron::ser::to_string_pretty(self, PrettyConfig::default())?,
+        )?;
+
+        Ok(())
+    }
+}
+
+/// What tells a paddle apart from the others of its match.
+fn paddle_key(paddle: &Paddle) -> (Side, PaddleDriver, Option<usize>) {
+    (
+        paddle.side,
+        paddle.driver,
+        paddle.arc.as_ref().map(|arc| arc.seat),
+    )
+}
+
+/// Path of the saved match file.
+pub fn saved_match_path(app_root: &Path) -> PathBuf {
+    app_root
+        .join("src")
+        .join("settings")
+        .join(USER_SAVED_MATCH_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amethyst::renderer::resources::Tint;
+
+    fn rules() -> MatchRules {
+        MatchRules {
+            mode: GameMode::Classic,
+            multi_ball: false,
+            power_ups: false,
+            level: None,
+            forces: vec![Force::Gravity],
+            ring_players: 0,
+            handicaps: Handicaps::default(),
+            profiles: PlayerProfiles::default(),
+        }
+    }
+
+    fn world_with_paddles(sides: &[Side]) -> World {
+        let mut world = World::new();
+
+        world.register::<Ball>();
+        world.register::<Paddle>();
+        world.register::<Transform>();
+        world.insert(Scoreboard::default());
+        world.insert(GameRng::new(42));
+
+        for side in sides.iter() {
+            world
+                .create_entity()
+                .with(Paddle::new(*side))
+                .with(Transform::default())
+                .build();
+        }
+
+        world
+    }
+
+    #[test]
+    fn should_restore_saved_match_into_new_world() {
+        // arrange
+        let mut world = world_with_paddles(&[Side::Left, Side::Right]);
+        let mut ball = Ball::new([75.0, -50.0], 2.0);
+        ball.last_hit_by = Some(Side::Right);
+
+        let mut ball_transform = Transform::default();
+        ball_transform.set_translation_xyz(30.0, 60.0, 0.0);
+        world
+            .create_entity()
+            .with(ball)
+            .with(ball_transform)
+            .build();
+
+        for (paddle, transform) in (
+            &mut world.write_storage::<Paddle>(),
+            &mut world.write_storage::<Transform>(),
+        )
+            .join()
+        {
+            paddle.velocity = 20.0;
+            transform.set_translation_y(70.0);
+        }
+
+        world
+            .write_resource::<Scoreboard>()
+            .set_score(Side::Left, 3);
+
+        let saved = SavedMatch::capture(&world, rules(), Some(1.5));
+        let contents = ron::ser::to_string(&saved).unwrap();
+
+        // act
+        let loaded = ron::de::from_str::<SavedMatch>(&contents).unwrap();
+        let mut resumed = world_with_paddles(&[Side::Left, Side::Right]);
+        let ball_spawn_timer = loaded.restore(&mut resumed, None);
+
+        // assert
+        assert_eq!(saved, loaded);
+        assert_eq!(Some(1.5), ball_spawn_timer);
+        assert_eq!(saved, SavedMatch::capture(&resumed, rules(), Some(1.5)));
+        assert_eq!(3, resumed.read_resource::<Scoreboard>().score(Side::Left));
+    }
+
+    #[test]
+    fn should_restore_paddles_in_their_seats_without_eliminated_ones() {
+        // arrange - four-player match where the right player is out: their paddle left
+        let mut world = world_with_paddles(&Side::ALL);
+        let right_paddle = (&world.entities(), &world.read_storage::<Paddle>())
+            .join()
+            .find(|(_, paddle)| paddle.side == Side::Right)
+            .map(|(entity, _)| entity)
+            .unwrap();
+        world.delete_entity(right_paddle).unwrap();
+
+        for (paddle, transform) in (
+            &mut world.write_storage::<Paddle>(),
+            &mut world.write_storage::<Transform>(),
+        )
+            .join()
+        {
+            let seat = Side::ALL
+                .iter()
+                .position(|side| *side == paddle.side)
+                .unwrap();
+
+            paddle.velocity = seat as f32;
+            transform.set_translation_x(seat as f32 * 10.0);
+        }
+
+        let saved = SavedMatch::capture(&world, rules(), None);
+
+        // act
+        let mut resumed = world_with_paddles(&Side::ALL);
+        saved.restore(&mut resumed, None);
+        resumed.maintain();
+
+        // assert
+        let paddles = (
+            &resumed.read_storage::<Paddle>(),
+            &resumed.read_storage::<Transform>(),
+        )
+            .join()
+            .map(|(paddle, transform)| (paddle.side, paddle.velocity, transform.translation().x))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                (Side::Left, 0.0, 0.0),
+                (Side::Top, 2.0, 20.0),
+                (Side::Bottom, 3.0, 30.0),
+            ],
+            paddles
+        );
+    }
+
+    #[test]
+    fn should_restore_bricks_left_in_breakout_match() {
+        // arrange
+        let breakout = MatchRules {
+            mode: GameMode::Breakout,
+            ..rules()
+        };
+        let brick = Brick {
+            hit_points: 2,
+            max_hit_points: 2,
+            width: 4.0,
+            height: 10.0,
+            last_hit_by: None,
+        };
+        let world_with_bricks = |count: usize| {
+            let mut world = world_with_paddles(&[Side::Left, Side::Right]);
+            world.register::<Brick>();
+            world.register::<Tint>();
+
+            for index in 0..count {
+                let mut transform = Transform::default();
+                transform.set_translation_x(index as f32 * 10.0);
+                spawn_brick(&mut world, brick.clone(), transform, None);
+            }
+
+            world
+        };
+
+        // one of three bricks was broken and another one cracked
+        let world = world_with_bricks(2);
+        for brick in (&mut world.write_storage::<Brick>()).join().take(1) {
+            brick.hit_points = 1;
+            brick.last_hit_by = Some(Side::Left);
+        }
+
+        let saved = SavedMatch::capture(&world, breakout.clone(), None);
+
+        // act
+        let mut resumed = world_with_bricks(3);
+        saved.restore(&mut resumed, None);
+        resumed.maintain();
+
+        // assert - the restored bricks are new entities, joined in any order
+        let bricks = |saved: SavedMatch| {
+            let mut bricks = saved.bricks.unwrap_or_default();
+            bricks
+                .sort_by(|(_, a), (_, b)| a.translation[0].partial_cmp(&b.translation[0]).unwrap());
+            bricks
+        };
+
+        assert_eq!(2, saved.bricks.as_ref().map_or(0, Vec::len));
+        assert_eq!(
+            bricks(saved),
+            bricks(SavedMatch::capture(&resumed, breakout, None))
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::entities::Side;
+
+/// How a player drives their paddle.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub enum ControlScheme {
+    /// Up/down keys or a controller (stick or D-pad).
+    Keyboard,
+    /// The paddle follows the mouse cursor's height.
+    Mouse,
+}
+
+impl Default for ControlScheme {
+    fn default() -> Self {
+        ControlScheme::Keyboard
+    }
+}
+
+/// Input settings of a single player.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PlayerControls {
+    #[serde(default)]
+    pub scheme: ControlScheme,
+    /// Stick deflection (0.0 - 1.0) below which the stick is considered centered.
+    pub dead_zone: f32,
+    /// Multiplier applied to the stick deflection after the dead zone.
+    pub sensitivity: f32,
+}
+
+impl Default for PlayerControls {
+    fn default() -> Self {
+        PlayerControls {
+            scheme: ControlScheme::Keyboard,
+            dead_zone: 0.15,
+            sensitivity: 1.0,
+        }
+    }
+}
+
+/// Per player control settings loaded from controls.ron.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ControlsConfig {
+    #[serde(default)]
+    pub players: HashMap<Side, PlayerControls>,
+    /// Fastest a mouse driven paddle can move (arena units per second), so that following the
+    /// cursor isn't an unfair advantage over keyboard players.
+    #[serde(default = "default_mouse_max_speed")]
+    pub mouse_max_speed: f32,
+}
+
+impl Default for ControlsConfig {
+    fn default() -> Self {
+        ControlsConfig {
+            players: HashMap::new(),
+            mouse_max_speed: default_mouse_max_speed(),
+        }
+    }
+}
+
+fn default_mouse_max_speed() -> f32 {
+    90.0
+}
+
+impl ControlsConfig {
+    /// Settings of the given player, falling back to the defaults when not configured.
+    pub fn player(&self, side: Side) -> PlayerControls {
+        self.players.get(&side).cloned().unwrap_or_default()
+    }
+}
@@ -0,0 +1,438 @@
+use serde::{Deserialize, Serialize};
+
+use super::{GameMode, Lineup};
+use crate::entities::{PaddleDriver, PowerUpKind, Side};
+
+/// Paddle movement tuning. Speeds are in arena units per second.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PaddleConfig {
+    pub max_speed: f32,
+    /// Speed gained per second while a direction is held.
+    pub acceleration: f32,
+    /// Speed lost per second when the input is released or reversed.
+    pub deceleration: f32,
+}
+
+impl Default for PaddleConfig {
+    fn default() -> Self {
+        PaddleConfig {
+            max_speed: 75.0,
+            acceleration: 600.0,
+            deceleration: 800.0,
+        }
+    }
+}
+
+/// How a four-player match is scored.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub enum FourPlayerScoring {
+    /// Conceding a goal costs a life: players without lives are out and their edge becomes a
+    /// wall. The last player standing wins.
+    Lives,
+    /// The last player to hit the ball scores when it goes into someone else's goal.
+    Points,
+}
+
+/// Four-player mode rules.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FourPlayerConfig {
+    pub scoring: FourPlayerScoring,
+    /// Lives each player starts with (lives scoring only).
+    pub lives: u32,
+}
+
+impl Default for FourPlayerConfig {
+    fn default() -> Self {
+        FourPlayerConfig {
+            scoring: FourPlayerScoring::Lives,
+            lives: 3,
+        }
+    }
+}
+
+/// Multi-ball rules: extra balls are served on a timer and/or after a number of paddle hits.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MultiBallConfig {
+    /// Seconds between extra balls, if they're served on a timer.
+    pub spawn_interval: Option<f32>,
+    /// Paddle hits between extra balls, if they're served after hits.
+    pub hits_per_ball: Option<u32>,
+    /// Most balls in play at once.
+    pub max_balls: usize,
+    /// Whether the balls bounce off each other.
+    pub ball_collisions: bool,
+}
+
+impl Default for MultiBallConfig {
+    fn default() -> Self {
+        MultiBallConfig {
+            spawn_interval: Some(10.0),
+            hits_per_ball: None,
+            max_balls: 4,
+            ball_collisions: true,
+        }
+    }
+}
+
+/// Squash (single-player practice against the wall) rules.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SquashConfig {
+    pub lives: u32,
+    /// Ball speed multiplier applied on every return.
+    pub speed_up: f32,
+    /// Speed the returns stop speeding the ball up at, in arena units per second.
+    pub max_speed: f32,
+}
+
+impl Default for SquashConfig {
+    fn default() -> Self {
+        SquashConfig {
+            lives: 3,
+            speed_up: 1.05,
+            max_speed: 200.0,
+        }
+    }
+}
+
+/// Breakout rules and brick field. The columns are mirrored on both halves of the arena,
+/// leaving the middle clear for the serves.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BreakoutConfig {
+    /// Hits needed to break the bricks of each column, from the middle of the arena outwards.
+    pub columns: Vec<u32>,
+    /// Bricks per column.
+    pub rows: usize,
+    pub points_per_brick: u32,
+    /// Chances (0.0 to 1.0) of a broken brick dropping a power-up.
+    pub drop_chance: f32,
+    /// Points ending the match, if any: otherwise it ends once the bricks are cleared.
+    pub target_points: Option<u32>,
+}
+
+impl Default for BreakoutConfig {
+    fn default() -> Self {
+        BreakoutConfig {
+            columns: vec![2, 1, 1],
+            rows: 8,
+            points_per_brick: 1,
+            drop_chance: 0.25,
+            target_points: Some(30),
+        }
+    }
+}
+
+/// What claiming a power-up the player already has does.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub enum Stacking {
+    /// Restarts its timer.
+    Refresh,
+    /// Restarts its timer and makes it stronger, up to `max_stacks` times.
+    Stack,
+}
+
+/// Row of the power-ups table.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PowerUpRule {
+    pub kind: PowerUpKind,
+    /// Chances of being picked, relative to the other power-ups' weights.
+    pub weight: u32,
+    /// Seconds the effect lasts (ignored by instant power-ups such as multi-ball).
+    pub duration: f32,
+    pub stacking: Stacking,
+    pub max_stacks: u32,
+}
+
+/// Power-up pickups: how often they appear and what they do.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PowerUpConfig {
+    /// Seconds between pickups.
+    pub spawn_interval: f32,
+    /// Most pickups waiting in the arena at once.
+    pub max_pickups: usize,
+    pub rules: Vec<PowerUpRule>,
+}
+
+impl Default for PowerUpConfig {
+    fn default() -> Self {
+        let rule = |kind, weight, duration, stacking, max_stacks| PowerUpRule {
+            kind,
+            weight,
+            duration,
+            stacking,
+            max_stacks,
+        };
+
+        PowerUpConfig {
+            spawn_interval: 8.0,
+            max_pickups: 2,
+            rules: vec![
+                rule(PowerUpKind::EnlargePaddle, 3, 10.0, Stacking::Stack, 2),
+                rule(PowerUpKind::ShrinkPaddle, 3, 10.0, Stacking::Stack, 2),
+                rule(PowerUpKind::SpeedBall, 2, 8.0, Stacking::Refresh, 1),
+                rule(PowerUpKind::SlowBall, 2, 8.0, Stacking::Refresh, 1),
+                rule(PowerUpKind::MultiBall, 1, 0.0, Stacking::Refresh, 1),
+                rule(PowerUpKind::StickyPaddle, 2, 10.0, Stacking::Refresh, 1),
+                rule(PowerUpKind::ShieldWall, 1, 6.0, Stacking::Refresh, 1),
+                rule(PowerUpKind::ReverseControls, 2, 6.0, Stacking::Refresh, 1),
+            ],
+        }
+    }
+}
+
+/// Practice drill: shots fired by the launcher at the player's paddle, each one at a random
+/// angle and speed within the drill's ranges.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct Drill {
+    /// Shown by the drill select menu.
+    pub name: String,
+    /// Angles of the shots from the horizontal, in degrees, randomly upwards or downwards.
+    pub min_angle: f32,
+    pub max_angle: f32,
+    /// Speeds of the shots, in arena units per second.
+    pub min_speed: f32,
+    pub max_speed: f32,
+    pub shots: u32,
+}
+
+/// Zone of the wall facing the player that returns should be aimed at.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct TargetZone {
+    /// Height of the zone's center, in arena units.
+    pub center: f32,
+    pub size: f32,
+    pub points: u32,
+}
+
+/// Practice drills and the target zones the returns are scored against.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PracticeConfig {
+    /// Seconds between a shot ending and the launcher firing the next one.
+    pub launch_delay: f32,
+    pub drills: Vec<Drill>,
+    pub target_zones: Vec<TargetZone>,
+}
+
+impl Default for PracticeConfig {
+    fn default() -> Self {
+        PracticeConfig {
+            launch_delay: 1.0,
+            drills: vec![Drill {
+                name: "RANDOM".to_string(),
+                min_angle: 0.0,
+                max_angle: 60.0,
+                min_speed: 60.0,
+                max_speed: 150.0,
+                shots: 20,
+            }],
+            target_zones: vec![TargetZone {
+                center: 50.0,
+                size: 20.0,
+                points: 1,
+            }],
+        }
+    }
+}
+
+/// Ball spin imparted by moving paddles: it curves the ball's path (Magnus effect) and changes
+/// its wall bounces.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SpinConfig {
+    /// Modes the balls spin in.
+    pub modes: Vec<GameMode>,
+    /// Spin (radians per second) given per unit of paddle speed at impact.
+    pub transfer: f32,
+    pub max_spin: f32,
+    /// Radians per second the ball's direction turns per unit of spin.
+    pub curve: f32,
+    /// Share of the spin lost per second.
+    pub damping: f32,
+    /// Share of the spin turned into sideways speed (and lost) on wall bounces.
+    pub wall_grip: f32,
+}
+
+impl SpinConfig {
+    pub fn is_enabled(&self, mode: GameMode) -> bool {
+        self.modes.contains(&mode)
+    }
+}
+
+impl Default for SpinConfig {
+    fn default() -> Self {
+        SpinConfig {
+            modes: vec![
+                GameMode::Classic,
+                GameMode::FourPlayer,
+                GameMode::Doubles,
+                GameMode::Squash,
+                GameMode::Breakout,
+                GameMode::Practice,
+                GameMode::Ring,
+            ],
+            transfer: 0.04,
+            max_spin: 6.0,
+            curve: 0.15,
+            damping: 0.5,
+            wall_grip: 0.3,
+        }
+    }
+}
+
+/// Wind gusts blowing along x, alternating direction from one gust to the next.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct WindConfig {
+    /// Peak acceleration of a gust, in arena units per second squared.
+    pub strength: f32,
+    /// Seconds between the start of two gusts.
+    pub interval: f32,
+    /// Seconds a gust lasts.
+    pub gust: f32,
+}
+
+/// Arena physics turned on with `--gravity`, `--wind` and `--drag`. Accelerations are in arena
+/// units per second squared.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PhysicsConfig {
+    /// Pulls the ball down.
+    pub gravity: f32,
+    pub wind: WindConfig,
+    /// Share of the speed above the serve speed lost per second.
+    pub drag: f32,
+    /// Seconds of the balls' predicted path shown while any of the physics is on, if any.
+    pub preview_seconds: Option<f32>,
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        PhysicsConfig {
+            gravity: 40.0,
+            wind: WindConfig {
+                strength: 30.0,
+                interval: 8.0,
+                gust: 3.0,
+            },
+            drag: 0.3,
+            preview_seconds: Some(1.0),
+        }
+    }
+}
+
+/// Round arena played by 2 to 6 players, each defending an arc of its boundary.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RingConfig {
+    /// Radius of the arena's boundary, in arena units.
+    pub radius: f32,
+    /// Angle each goal spans, in degrees (at most the angle between two players).
+    pub goal_arc: f32,
+    /// Goals conceded before a player is out.
+    pub lives: u32,
+    /// Who drives the paddle of each player (P1 to P6), the missing ones are driven by the CPU.
+    pub seats: Vec<PaddleDriver>,
+}
+
+impl Default for RingConfig {
+    fn default() -> Self {
+        RingConfig {
+            radius: 46.0,
+            goal_arc: 50.0,
+            lives: 3,
+            seats: vec![
+                PaddleDriver::Player(Side::Left),
+                PaddleDriver::Player(Side::Right),
+            ],
+        }
+    }
+}
+
+/// Air-hockey matches: mallets, puck and goal mouths. Sizes are in arena units and speeds in
+/// arena units per second.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AirHockeyConfig {
+    pub mallet_radius: f32,
+    /// Distance from their goal line the mallets start at.
+    pub mallet_depth: f32,
+    /// Width of the goal mouths, centered on the end walls.
+    pub goal_width: f32,
+    /// Share of the puck's speed lost per second.
+    pub friction: f32,
+    /// Fastest the mallets can send the puck.
+    pub max_puck_speed: f32,
+}
+
+impl Default for AirHockeyConfig {
+    fn default() -> Self {
+        AirHockeyConfig {
+            mallet_radius: 5.0,
+            mallet_depth: 15.0,
+            goal_width: 30.0,
+            friction: 0.3,
+            max_puck_speed: 200.0,
+        }
+    }
+}
+
+/// Gameplay tuning loaded from gameplay.ron.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GameplayConfig {
+    #[serde(default)]
+    pub paddle: PaddleConfig,
+    /// Points winning the matches played for points (classic, doubles, air-hockey and
+    /// four-player matches with points), if any: otherwise they go on until they're quit.
+    /// Netplay matches always go on.
+    #[serde(default)]
+    pub points_to_win: Option<u32>,
+    #[serde(default)]
+    pub four_player: FourPlayerConfig,
+    /// Paddles of both teams in doubles matches.
+    #[serde(default = "Lineup::doubles")]
+    pub doubles: Lineup,
+    #[serde(default)]
+    pub multi_ball: MultiBallConfig,
+    #[serde(default)]
+    pub power_ups: PowerUpConfig,
+    #[serde(default)]
+    pub squash: SquashConfig,
+    #[serde(default)]
+    pub breakout: BreakoutConfig,
+    #[serde(default)]
+    pub practice: PracticeConfig,
+    #[serde(default)]
+    pub spin: SpinConfig,
+    #[serde(default)]
+    pub physics: PhysicsConfig,
+    #[serde(default)]
+    pub ring: RingConfig,
+    #[serde(default)]
+    pub air_hockey: AirHockeyConfig,
+    /// Seed of the random numbers of every match, to play them again (None: a new seed for
+    /// each match).
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+impl Default for GameplayConfig {
+    fn default() -> Self {
+        GameplayConfig {
+            paddle: PaddleConfig::default(),
+            points_to_win: None,
+            four_player: FourPlayerConfig::default(),
+            doubles: Lineup::doubles(),
+            multi_ball: MultiBallConfig::default(),
+            power_ups: PowerUpConfig::default(),
+            squash: SquashConfig::default(),
+            breakout: BreakoutConfig::default(),
+            practice: PracticeConfig::default(),
+            spin: SpinConfig::default(),
+            physics: PhysicsConfig::default(),
+            ring: RingConfig::default(),
+            air_hockey: AirHockeyConfig::default(),
+            seed: None,
+        }
+    }
+}
+
+impl GameplayConfig {
+    /// Short description of the rules, e.g. for the matches listed in the LAN lobby.
+    pub fn summary(&self) -> String {
+        format!("PADDLE SPEED {}", self.paddle.max_speed)
+    }
+}
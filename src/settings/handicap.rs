@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::PaddleConfig;
+use crate::entities::Side;
+
+/// Paddle length multipliers offered by the handicaps menu.
+const PADDLE_LENGTHS: [f32; 5] = [0.5, 0.75, 1.0, 1.5, 2.0];
+/// Paddle speed multipliers offered by the handicaps menu.
+const PADDLE_SPEEDS: [f32; 5] = [0.5, 0.75, 1.0, 1.25, 1.5];
+/// Assistance strengths offered by the handicaps menu.
+const ASSIST_LEVELS: [f32; 5] = [0.0, 0.25, 0.5, 0.75, 1.0];
+/// Most points a player can start a match with.
+const MAX_STARTING_POINTS: u32 = 5;
+
+/// A setting of the handicaps, as listed by the handicaps menu.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HandicapOption {
+    PaddleLength,
+    PaddleSpeed,
+    StartingPoints,
+    Assist,
+}
+
+impl HandicapOption {
+    /// Every option, in the order shown by the handicaps menu.
+    pub const ALL: [HandicapOption; 4] = [
+        HandicapOption::PaddleLength,
+        HandicapOption::PaddleSpeed,
+        HandicapOption::StartingPoints,
+        HandicapOption::Assist,
+    ];
+}
+
+/// Per player handicap evening out matches between players of different levels. It's kept by
+/// the paddles the player drives.
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize, Serialize)]
+pub struct Handicap {
+    /// Multiplier of the paddle's length.
+    pub paddle_length: f32,
+    /// Multiplier of the paddle's max speed, acceleration and deceleration.
+    pub paddle_speed: f32,
+    /// Points the player starts the match with.
+    pub starting_points: u32,
+    /// How strongly the paddle is nudged towards where the ball will reach it, from 0.0 (not at
+    /// all) to 1.0 (as hard as the CPU moves its own paddles).
+    pub assist: f32,
+}
+
+impl Default for Handicap {
+    fn default() -> Self {
+        Handicap {
+            paddle_length: 1.0,
+            paddle_speed: 1.0,
+            starting_points: 0,
+            assist: 0.0,
+        }
+    }
+}
+
+impl Handicap {
+    /// Paddle movement tuning scaled by the speed multiplier.
+    pub fn paddle_config(&self, config: &PaddleConfig) -> PaddleConfig {
+        PaddleConfig {
+            max_speed: config.max_speed * self.paddle_speed,
+            acceleration: config.acceleration * self.paddle_speed,
+            deceleration: config.deceleration * self.paddle_speed,
+        }
+    }
+
+    /// Switches an option to its next value, back to the first one after the last.
+    pub fn cycle(&mut self, option: HandicapOption) {
+        match option {
+            HandicapOption::PaddleLength => {
+                self.paddle_length = next_step(&PADDLE_LENGTHS, self.paddle_length)
+            }
+            HandicapOption::PaddleSpeed => {
+                self.paddle_speed = next_step(&PADDLE_SPEEDS, self.paddle_speed)
+            }
+            HandicapOption::StartingPoints => {
+                self.starting_points = (self.starting_points + 1) % (MAX_STARTING_POINTS + 1)
+            }
+            HandicapOption::Assist => self.assist = next_step(&ASSIST_LEVELS, self.assist),
+        }
+    }
+
+    /// Name and value of an option shown by the handicaps menu (e.g. "LENGTH X1.5").
+    pub fn label(&self, option: HandicapOption) -> String {
+        match option {
+            HandicapOption::PaddleLength => format!("LENGTH X{}", self.paddle_length),
+            HandicapOption::PaddleSpeed => format!("SPEED X{}", self.paddle_speed),
+            HandicapOption::StartingPoints => format!("STARTING POINTS {}", self.starting_points),
+            HandicapOption::Assist if self.assist == 0.0 => "ASSIST OFF".to_string(),
+            HandicapOption::Assist => format!("ASSIST {}%", (self.assist * 100.0).round()),
+        }
+    }
+}
+
+/// Handicaps of the players (by the side of their controls). Players without one play
+/// unhandicapped.
+#[derive(Clone, Default, PartialEq, Debug, Deserialize, Serialize)]
+pub struct Handicaps {
+    handicaps: HashMap<Side, Handicap>,
+}
+
+impl Handicaps {
+    pub fn get(&self, player: Side) -> Handicap {
+        self.handicaps.get(&player).cloned().unwrap_or_default()
+    }
+
+    pub fn get_mut(&mut self, player: Side) -> &mut Handicap {
+        self.handicaps.entry(player).or_default()
+    }
+}
+
+/// Step following the given value, or the first step after the last one.
+fn next_step(steps: &[f32], value: f32) -> f32 {
+    steps
+        .iter()
+        .cloned()
+        .find(|step| *step > value + f32::EPSILON)
+        .unwrap_or(steps[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_cycle_handicap_options_back_to_first_value() {
+        // arrange
+        let mut handicap = Handicap {
+            paddle_length: 2.0,
+            starting_points: 4,
+            ..Handicap::default()
+        };
+
+        // act
+        handicap.cycle(HandicapOption::PaddleLength);
+        handicap.cycle(HandicapOption::PaddleSpeed);
+        handicap.cycle(HandicapOption::StartingPoints);
+        handicap.cycle(HandicapOption::StartingPoints);
+
+        // assert
+        assert_eq!(0.5, handicap.paddle_length);
+        assert_eq!(1.25, handicap.paddle_speed);
+        assert_eq!(0, handicap.starting_points);
+        assert_eq!("ASSIST OFF", handicap.label(HandicapOption::Assist));
+    }
+
+    #[test]
+    fn should_scale_paddle_movement_by_speed_handicap() {
+        // arrange
+        let handicap = Handicap {
+            paddle_speed: 0.5,
+            ..Handicap::default()
+        };
+
+        // act
+        let config = handicap.paddle_config(&PaddleConfig::default());
+
+        // assert
+        assert_eq!(37.5, config.max_speed);
+        assert_eq!(300.0, config.acceleration);
+        assert_eq!(400.0, config.deceleration);
+    }
+}
@@ -0,0 +1,50 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use amethyst::config::Config;
+use log::error;
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use super::USER_HIGH_SCORES_FILE;
+
+/// Best results of the single-player modes, saved between sessions.
+#[derive(Clone, Default, PartialEq, Debug, Deserialize, Serialize)]
+pub struct HighScores {
+    /// Longest squash streak.
+    #[serde(default)]
+    pub squash: u32,
+}
+
+impl HighScores {
+    /// Loads the saved high scores. There are none until a first record is saved.
+    pub fn load_or_default(path: &Path) -> Self {
+        if !path.exists() {
+            return HighScores::default();
+        }
+
+        HighScores::load(path).unwrap_or_else(|err| {
+            error!("Failed to load the high scores: {}", err);
+            HighScores::default()
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> amethyst::Result<()> {
+        fs::write(
+            path,
+            ron::ser::to_string_pretty(self, PrettyConfig::default())?,
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Path of the high scores file.
+pub fn high_scores_path(app_root: &Path) -> PathBuf {
+    app_root
+        .join("src")
+        .join("settings")
+        .join(USER_HIGH_SCORES_FILE)
+}
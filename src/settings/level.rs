@@ -0,0 +1,138 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use amethyst::config::Config;
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use super::{ARENA_HEIGHT, ARENA_WIDTH, LEVELS_DIR};
+use crate::entities::{ObstacleShape, Side};
+
+/// Back and forth movement of an obstacle, from its position to `to` and back.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct ObstaclePath {
+    pub to: [f32; 2],
+    /// Seconds to go there and back.
+    pub period: f32,
+}
+
+/// Obstacle placed in the arena. Positions are the obstacle's center, in arena units.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct ObstacleLayout {
+    pub shape: ObstacleShape,
+    pub position: [f32; 2],
+    /// Static when not given.
+    #[serde(default)]
+    pub path: Option<ObstaclePath>,
+}
+
+/// Pair of portals: a ball going into one of them comes out of the other one.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct PortalLayout {
+    pub a: [f32; 2],
+    pub b: [f32; 2],
+    pub radius: f32,
+}
+
+/// Arena layout loaded from a level file (see src/settings/levels).
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct Level {
+    /// Shown by the level select menu.
+    pub name: String,
+    /// Width of the goals' opening, centered on their edge. The rest of the edge is a wall.
+    /// Goals span the whole edge when not given.
+    #[serde(default)]
+    pub goal_size: Option<f32>,
+    #[serde(default)]
+    pub obstacles: Vec<ObstacleLayout>,
+    #[serde(default)]
+    pub portals: Vec<PortalLayout>,
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Level {
+            name: "EMPTY ARENA".to_string(),
+            goal_size: None,
+            obstacles: Vec::new(),
+            portals: Vec::new(),
+        }
+    }
+}
+
+impl Level {
+    /// Whether a position on (or beyond) a goal's edge lies within the goal's opening.
+    pub fn is_within_goal(&self, goal: Side, position: [f32; 2]) -> bool {
+        let goal_size = match self.goal_size {
+            Some(goal_size) => goal_size,
+            None => return true,
+        };
+
+        let along_edge = 1 - goal.axis();
+        let edge_center = [ARENA_WIDTH, ARENA_HEIGHT][along_edge] * 0.5;
+
+        (position[along_edge] - edge_center).abs() <= goal_size * 0.5
+    }
+}
+
+/// Directory of the level files.
+pub fn levels_dir(app_root: &Path) -> PathBuf {
+    app_root.join("src").join("settings").join(LEVELS_DIR)
+}
+
+/// Loads every level of the directory, sorted by file name. Levels that fail to load are
+/// skipped.
+pub fn load_levels(dir: &Path) -> Vec<Level> {
+    let mut paths = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .map_or(false, |extension| extension == "ron")
+            })
+            .collect::<Vec<_>>(),
+        Err(err) => {
+            error!("Failed to list the levels in {}: {}", dir.display(), err);
+            Vec::new()
+        }
+    };
+
+    paths.sort();
+
+    paths
+        .iter()
+        .filter_map(|path| match Level::load(path) {
+            Ok(level) => Some(level),
+            Err(err) => {
+                error!("Failed to load the level {}: {}", path.display(), err);
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_only_score_within_the_goal_opening() {
+        // arrange
+        let level = Level {
+            goal_size: Some(40.0), // 30 <= opening <= 70
+            ..Level::default()
+        };
+
+        // act
+        let within = level.is_within_goal(Side::Left, [0.0, 65.0]);
+        let beside = level.is_within_goal(Side::Left, [0.0, 75.0]);
+        let within_top = level.is_within_goal(Side::Top, [40.0, ARENA_HEIGHT]);
+
+        // assert
+        assert_eq!(true, within);
+        assert_eq!(false, beside);
+        assert_eq!(true, within_top);
+    }
+}
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+use super::PADDLE_WIDTH;
+use crate::entities::{PaddleDriver, Side};
+
+/// Where a paddle plays and who drives it.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct PaddlePlacement {
+    /// Team the paddle plays for: it defends this side's goal.
+    pub side: Side,
+    /// Distance from the team's goal line to the center of the paddle.
+    pub depth: f32,
+    pub driver: PaddleDriver,
+}
+
+/// Paddles of every team taking part in a match.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct Lineup {
+    pub paddles: Vec<PaddlePlacement>,
+}
+
+impl Lineup {
+    /// One paddle per side on its goal line, driven by that side's player.
+    pub fn single(sides: &[Side]) -> Self {
+        Lineup {
+            paddles: sides
+                .iter()
+                .map(|side| PaddlePlacement {
+                    side: *side,
+                    depth: PADDLE_WIDTH * 0.5,
+                    driver: PaddleDriver::Player(*side),
+                })
+                .collect(),
+        }
+    }
+
+    /// Two paddles per team: the players at the back and the CPU in the forward court.
+    pub fn doubles() -> Self {
+        let mut lineup = Lineup::single(&[Side::Left, Side::Right]);
+
+        for side in &[Side::Left, Side::Right] {
+            lineup.paddles.push(PaddlePlacement {
+                side: *side,
+                depth: 30.0,
+                driver: PaddleDriver::Cpu,
+            });
+        }
+
+        lineup
+    }
+}
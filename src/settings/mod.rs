@@ -1,5 +1,27 @@
 //! Settings variables used throughout the project.
 
+mod controls;
+mod gameplay;
+mod handicap;
+mod high_scores;
+mod level;
+mod lineup;
+mod mode;
+mod profiles;
+
+pub use controls::{ControlScheme, ControlsConfig, PlayerControls};
+pub use gameplay::{
+    AirHockeyConfig, BreakoutConfig, Drill, FourPlayerConfig, FourPlayerScoring, GameplayConfig,
+    MultiBallConfig, PaddleConfig, PhysicsConfig, PowerUpConfig, PowerUpRule, PracticeConfig,
+    RingConfig, SpinConfig, SquashConfig, Stacking, TargetZone, WindConfig,
+};
+pub use handicap::{Handicap, HandicapOption, Handicaps};
+pub use high_scores::{high_scores_path, HighScores};
+pub use level::{levels_dir, load_levels, Level, ObstacleLayout, ObstaclePath, PortalLayout};
+pub use lineup::{Lineup, PaddlePlacement};
+pub use mode::GameMode;
+pub use profiles::{profiles_path, PlayerProfiles, Profile, Profiles};
+
 // Gameplay
 pub const BALL_SPAWN_DELAY: f32 = 2.0;
 
@@ -19,10 +41,24 @@ pub const BALL_VELOCITY_X: f32 = 75.0;
 pub const BALL_VELOCITY_Y: f32 = 50.0;
 pub const BALL_RADIUS: f32 = 2.0;
 
+// Power-up pickups
+pub const POWER_UP_RADIUS: f32 = 3.0;
+
 // Paddle definitions
 pub const PADDLE_WIDTH: f32 = 4.0;
 pub const PADDLE_HEIGHT: f32 = 16.0;
 
+// Settings files (relative to src/settings)
+pub const CONTROLS_CONFIG_FILE: &str = "controls.ron";
+pub const GAMEPLAY_CONFIG_FILE: &str = "gameplay.ron";
+pub const KEY_BINDINGS_FILE: &str = "key_bindings.ron";
+pub const USER_KEY_BINDINGS_FILE: &str = "user_key_bindings.ron";
+pub const LEVELS_DIR: &str = "levels";
+pub const USER_HIGH_SCORES_FILE: &str = "user_high_scores.ron";
+pub const USER_SAVED_MATCH_FILE: &str = "user_saved_match.ron";
+pub const USER_MATCH_HISTORY_FILE: &str = "user_match_history.jsonl";
+pub const USER_PROFILES_FILE: &str = "user_profiles.ron";
+
 // Assets paths
 pub const SQUARE_FONT_PATH: &str = "fonts/square.ttf";
 pub const SPRITE_SHEET_PATH: &str = "textures/spritesheet.png";
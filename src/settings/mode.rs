@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+use super::{GameplayConfig, Lineup};
+use crate::entities::Side;
+
+/// Which paddles take part in a match.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub enum GameMode {
+    /// Left against right, the top and bottom edges are walls.
+    Classic,
+    /// A paddle (and a goal) on each of the four edges.
+    FourPlayer,
+    /// Left against right with two paddles per team, sharing the team's score.
+    Doubles,
+    /// Single player on the left against the wall (practice).
+    Squash,
+    /// Left against right with a field of bricks to break in the middle of the arena.
+    Breakout,
+    /// Single player on the left returning the launcher's shots (training drills).
+    Practice,
+    /// Two to six players around a round arena, each defending an arc of its boundary.
+    Ring,
+    /// Left against right with round mallets moving freely within their half and a sliding
+    /// puck, scoring through goal mouths in the middle of the end walls.
+    AirHockey,
+}
+
+impl Default for GameMode {
+    fn default() -> Self {
+        GameMode::Classic
+    }
+}
+
+impl GameMode {
+    /// Sides with a paddle and a goal at the start of a match (none in ring matches, whose
+    /// players are seated around the arena instead).
+    pub fn sides(self) -> &'static [Side] {
+        match self {
+            GameMode::Classic | GameMode::Doubles | GameMode::Breakout | GameMode::AirHockey => {
+                &[Side::Left, Side::Right]
+            }
+            GameMode::FourPlayer => &Side::ALL,
+            GameMode::Squash | GameMode::Practice => &[Side::Left],
+            GameMode::Ring => &[],
+        }
+    }
+
+    /// Paddles taking part in a match.
+    pub fn lineup(self, gameplay_config: &GameplayConfig) -> Lineup {
+        match self {
+            GameMode::Doubles => gameplay_config.doubles.clone(),
+            _ => Lineup::single(self.sides()),
+        }
+    }
+}
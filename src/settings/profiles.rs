@@ -0,0 +1,161 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use amethyst::config::Config;
+use log::error;
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use super::{ControlScheme, Lineup, USER_PROFILES_FILE};
+use crate::entities::{PaddleDriver, Side};
+
+/// A local player's profile, picked before a match. Its rating is worked out from the match
+/// history.
+#[derive(Clone, PartialEq, Debug, Deserialize, Serialize)]
+pub struct Profile {
+    pub name: String,
+    /// Color of the player's name and score.
+    #[serde(default = "default_color")]
+    pub color: [f32; 4],
+    /// Scheme the player drives their paddles with, instead of the one of controls.ron.
+    #[serde(default)]
+    pub controls: Option<ControlScheme>,
+}
+
+impl Profile {
+    /// Profile with the default color and controls. Names are shown in capitals.
+    pub fn new(name: &str) -> Self {
+        Profile {
+            name: name.to_uppercase(),
+            color: default_color(),
+            controls: None,
+        }
+    }
+}
+
+fn default_color() -> [f32; 4] {
+    [1., 1., 1., 1.]
+}
+
+/// The local players' profiles, saved between sessions.
+#[derive(Clone, Default, PartialEq, Debug, Deserialize, Serialize)]
+pub struct Profiles {
+    #[serde(default)]
+    pub profiles: Vec<Profile>,
+}
+
+impl Profiles {
+    /// Loads the saved profiles. There are none until a first one is created.
+    pub fn load_or_default(path: &Path) -> Self {
+        if !path.exists() {
+            return Profiles::default();
+        }
+
+        Profiles::load(path).unwrap_or_else(|err| {
+            error!("Failed to load the profiles: {}", err);
+            Profiles::default()
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> amethyst::Result<()> {
+        fs::write(
+            path,
+            ron::ser::to_string_pretty(self, PrettyConfig::default())?,
+        )?;
+
+        Ok(())
+    }
+
+    /// Adds a profile, unless there's already one with its name. Returns whether it was added.
+    pub fn add(&mut self, profile: Profile) -> bool {
+        if self
+            .profiles
+            .iter()
+            .any(|existing| existing.name == profile.name)
+        {
+            return false;
+        }
+
+        self.profiles.push(profile);
+
+        true
+    }
+}
+
+/// Profiles picked by the players of a match, by the side of their controls. Players without
+/// one play under their label (e.g. P1).
+#[derive(Clone, Default, PartialEq, Debug, Deserialize, Serialize)]
+pub struct PlayerProfiles {
+    profiles: HashMap<Side, Profile>,
+}
+
+impl PlayerProfiles {
+    pub fn get(&self, player: Side) -> Option<&Profile> {
+        self.profiles.get(&player)
+    }
+
+    pub fn set(&mut self, player: Side, profile: Option<Profile>) {
+        match profile {
+            Some(profile) => self.profiles.insert(player, profile),
+            None => self.profiles.remove(&player),
+        };
+    }
+
+    /// Profile of whoever drives a paddle, if it's a player who picked one.
+    pub fn driver_profile(&self, driver: PaddleDriver) -> Option<&Profile> {
+        match driver {
+            PaddleDriver::Player(player) => self.get(player),
+            PaddleDriver::Cpu => None,
+        }
+    }
+
+    /// Profile of the first player with one driving a paddle of the team.
+    pub fn team_profile(&self, lineup: &Lineup, team: Side) -> Option<&Profile> {
+        lineup
+            .paddles
+            .iter()
+            .filter(|placement| placement.side == team)
+            .find_map(|placement| self.driver_profile(placement.driver))
+    }
+
+    /// Name shown for a team: its player's profile name, or its label.
+    pub fn team_name(&self, lineup: &Lineup, team: Side) -> String {
+        self.team_profile(lineup, team).map_or_else(
+            || team.player_label().to_string(),
+            |profile| profile.name.clone(),
+        )
+    }
+}
+
+/// Path of the profiles file.
+pub fn profiles_path(app_root: &Path) -> PathBuf {
+    app_root
+        .join("src")
+        .join("settings")
+        .join(USER_PROFILES_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_name_teams_after_their_players_profiles() {
+        // arrange
+        let mut profiles = PlayerProfiles::default();
+        profiles.set(Side::Left, Some(Profile::new("ada")));
+
+        let lineup = Lineup::doubles();
+
+        // act
+        let left = profiles.team_name(&lineup, Side::Left);
+        let right = profiles.team_name(&lineup, Side::Right);
+
+        // assert
+        assert_eq!("ADA", left);
+        assert_eq!("P2", right);
+    }
+}
@@ -2,15 +2,16 @@ use std::path::PathBuf;
 
 use amethyst::{
     audio::{AudioBundle, DjSystemDesc},
-    core::TransformBundle,
-    input::{InputBundle, StringBindings},
+    core::{ArcThreadPool, SystemExt, TransformBundle},
+    ecs::{Dispatcher, DispatcherBuilder, World, WorldExt},
+    input::{InputBundle, SdlEventsSystemDesc, StringBindings},
     renderer::{types::DefaultBackend, RenderFlat2D, RenderToWindow, RenderingBundle},
     start_logger,
     ui::{RenderUi, UiBundle},
     GameDataBuilder,
 };
 
-use crate::{audio::Music, systems};
+use crate::{audio::Music, controls::key_bindings_path, state::RunState, systems};
 
 /// Builds the game configuration by pluging bundles and systems to it.
 pub fn build_game_config(
@@ -18,10 +19,7 @@ pub fn build_game_config(
 ) -> amethyst::Result<GameDataBuilder<'static, 'static>> {
     // paths based on app root path
     let display_config_path = app_root.join("src").join("settings").join("display.ron");
-    let key_bindings_path = app_root
-        .join("src")
-        .join("settings")
-        .join("key_bindings.ron");
+    let key_bindings_path = key_bindings_path(app_root);
 
     // input handler: parameter type determines how the axes/actions are read
     let input_bundle =
@@ -42,13 +40,87 @@ pub fn build_game_config(
         .with_bundle(input_bundle)? // bundle for reading inputs
         .with_bundle(UiBundle::<StringBindings>::new())? // UiBundle MUST match InputHandler type!
         .with_bundle(AudioBundle::default())? // audio and sfx ECS components
-        .with(systems::PaddleSystem, "paddle_system", &["input_system"])
-        .with(systems::BallSystem, "ball_system", &[])
-        .with(systems::ScoreSystem, "score_system", &["ball_system"])
+        .with_thread_local_desc(SdlEventsSystemDesc::<StringBindings>::default()) // controllers
+        .with_system_desc(
+            systems::ControllerSystemDesc::default(),
+            "controller_system",
+            &["input_system"],
+        )
         .with(
-            systems::CollisionSystem,
+            systems::PaddleInputSystem,
+            "paddle_input_system",
+            &["input_system", "controller_system"],
+        )
+        // gameplay systems only run while the game is not paused
+        .with(
+            systems::PaddleSystem.pausable(RunState::Running),
+            "paddle_system",
+            &["paddle_input_system"],
+        )
+        .with(
+            systems::BallSystem.pausable(RunState::Running),
+            "ball_system",
+            &[],
+        )
+        .with(
+            systems::TrajectorySystem.pausable(RunState::Running),
+            "trajectory_system",
+            &["ball_system"],
+        )
+        .with(
+            systems::ObstacleSystem.pausable(RunState::Running),
+            "obstacle_system",
+            &[],
+        )
+        .with(
+            systems::PortalSystem.pausable(RunState::Running),
+            "portal_system",
+            &["ball_system"],
+        )
+        .with(
+            systems::ScoreSystem.pausable(RunState::Running),
+            "score_system",
+            &["portal_system"],
+        )
+        .with(
+            systems::PracticeSystem.pausable(RunState::Running),
+            "practice_system",
+            &["portal_system"],
+        )
+        .with(
+            systems::RingSystem.pausable(RunState::Running),
+            "ring_system",
+            &["portal_system"],
+        )
+        .with(
+            systems::ScoreTextSystem,
+            "score_text_system",
+            &["score_system"],
+        )
+        .with(
+            systems::CollisionSystem.pausable(RunState::Running),
             "collision_system",
-            &["paddle_system", "ball_system"],
+            &["paddle_system", "portal_system", "obstacle_system"],
+        )
+        .with(
+            systems::BreakoutSystem.pausable(RunState::Running),
+            "breakout_system",
+            &["score_system", "collision_system"],
+        )
+        .with(
+            systems::MultiBallSystem.pausable(RunState::Running),
+            "multi_ball_system",
+            &["score_system", "collision_system", "ring_system"],
+        )
+        .with(
+            systems::PowerUpSystem.pausable(RunState::Running),
+            "power_up_system",
+            &[
+                "score_system",
+                "collision_system",
+                "breakout_system",
+                "multi_ball_system",
+            ],
         )
         .with_system_desc(
             DjSystemDesc::new(|music: &mut Music| music.tracks.next()),
@@ -59,6 +131,33 @@ pub fn build_game_config(
     Ok(game_config)
 }
 
+/// Builds a dispatcher with only the match simulation systems, used to step the simulation
+/// outside of the game loop (e.g. netplay re-simulating mispredicted ticks or the headless
+/// game server, which has no game loop at all).
+pub fn build_simulation_dispatcher(world: &mut World) -> Dispatcher<'static, 'static> {
+    let mut builder = DispatcherBuilder::new();
+
+    // shares the game's thread pool, if there's a game running
+    if let Some(thread_pool) = world.try_fetch::<ArcThreadPool>() {
+        builder = builder.with_pool(thread_pool.clone());
+    }
+
+    let mut dispatcher = builder
+        .with(systems::PaddleSystem, "paddle_system", &[])
+        .with(systems::BallSystem, "ball_system", &[])
+        .with(systems::ScoreSystem, "score_system", &["ball_system"])
+        .with(
+            systems::CollisionSystem,
+            "collision_system",
+            &["paddle_system", "ball_system"],
+        )
+        .build();
+
+    dispatcher.setup(world);
+
+    dispatcher
+}
+
 /// Configures Amethyst's logger.
 pub fn setup_logger() {
     // builds loger with default values
@@ -0,0 +1,194 @@
+use amethyst::{
+    input::{Button, InputEvent, InputHandler, StringBindings, VirtualKeyCode},
+    prelude::*,
+    utils::application_root_dir,
+    SimpleState, StateEvent,
+};
+use log::error;
+
+use crate::{
+    controls::{user_key_bindings_path, BindingSlot, KeyBindings, BINDING_SLOTS},
+    entities::{initialize_menu, menu_command, Menu, MenuCommand},
+};
+
+const RESET_ITEM: usize = BINDING_SLOTS.len();
+const BACK_ITEM: usize = BINDING_SLOTS.len() + 1;
+
+/// Controls screen: lets each player rebind their up/down/pause buttons. Bindings are applied
+/// to the running `InputHandler` and saved to the user's key bindings file when leaving.
+#[derive(Default)]
+pub struct Controls {
+    key_bindings: KeyBindings,
+    menu: Option<Menu>,
+    capturing: Option<BindingSlot>,
+}
+
+impl Controls {
+    fn slot_text(&self, slot: BindingSlot) -> String {
+        format!(
+            "{}: {}",
+            slot.label(),
+            button_label(self.key_bindings.button(slot))
+        )
+    }
+
+    fn refresh_items(&self, world: &mut World) {
+        if let Some(menu) = self.menu.as_ref() {
+            for (index, slot) in BINDING_SLOTS.iter().enumerate() {
+                menu.set_item_text(world, index, self.slot_text(*slot));
+            }
+        }
+    }
+
+    fn set_footer(&self, world: &mut World, text: String) {
+        if let Some(menu) = self.menu.as_ref() {
+            menu.set_footer(world, text);
+        }
+    }
+
+    /// Assigns the captured button to the slot being edited, reporting duplicates.
+    fn capture(&mut self, world: &mut World, slot: BindingSlot, button: Button) {
+        self.capturing = None;
+
+        if button == Button::Key(VirtualKeyCode::Escape) {
+            self.set_footer(world, String::new());
+        } else if let Err(conflict) = self.key_bindings.bind(slot, button) {
+            let message = format!(
+                "{} IS ALREADY BOUND TO {}",
+                button_label(conflict.button),
+                conflict.bound_to.label()
+            );
+
+            self.set_footer(world, message);
+        } else {
+            self.set_footer(world, String::new());
+        }
+
+        self.refresh_items(world);
+    }
+
+    /// Applies the bindings to the running game and writes them to the user bindings file.
+    fn save(&self, world: &mut World) {
+        match self.key_bindings.to_bindings() {
+            Ok(bindings) => {
+                world
+                    .write_resource::<InputHandler<StringBindings>>()
+                    .bindings = bindings
+            }
+            Err(err) => error!("Failed to apply key bindings: {}", err),
+        }
+
+        let saved = application_root_dir()
+            .map_err(amethyst::Error::from)
+            .and_then(|app_root| self.key_bindings.save(&user_key_bindings_path(&app_root)));
+
+        if let Err(err) = saved {
+            error!("Failed to save key bindings: {}", err);
+        }
+    }
+}
+
+impl SimpleState for Controls {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+
+        self.key_bindings = KeyBindings::from_bindings(
+            &world
+                .read_resource::<InputHandler<StringBindings>>()
+                .bindings,
+        );
+
+        let mut items = BINDING_SLOTS
+            .iter()
+            .map(|slot| self.slot_text(*slot))
+            .collect::<Vec<_>>();
+        items.push("RESET DEFAULTS".to_string());
+        items.push("SAVE AND BACK".to_string());
+
+        self.menu
+            .replace(initialize_menu(world, "CONTROLS", &items));
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if let Some(menu) = self.menu.take() {
+            menu.delete(data.world);
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        let world = data.world;
+
+        // while waiting for a new binding, the next key/button pressed is the binding
+        if let Some(slot) = self.capturing {
+            match event {
+                // keys are also reported by scan code: only the key code is bound
+                StateEvent::Input(InputEvent::ButtonPressed(Button::ScanCode(_))) => {}
+                StateEvent::Input(InputEvent::ButtonPressed(button)) => {
+                    self.capture(world, slot, button)
+                }
+                _ => {}
+            }
+
+            return Trans::None;
+        }
+
+        let selected = match self.menu.as_mut() {
+            Some(menu) => match menu_command(&event) {
+                Some(MenuCommand::Previous) => {
+                    menu.select_previous(world);
+                    None
+                }
+                Some(MenuCommand::Next) => {
+                    menu.select_next(world);
+                    None
+                }
+                Some(MenuCommand::Confirm) => Some(menu.selected()),
+                Some(MenuCommand::Back) => Some(BACK_ITEM),
+                None => None,
+            },
+            None => None,
+        };
+
+        match selected {
+            Some(RESET_ITEM) => {
+                self.key_bindings = KeyBindings::default();
+                self.set_footer(world, "DEFAULTS RESTORED".to_string());
+                self.refresh_items(world);
+            }
+            Some(BACK_ITEM) => {
+                self.save(world);
+
+                return Trans::Pop;
+            }
+            Some(index) => {
+                let slot = BINDING_SLOTS[index];
+
+                self.capturing.replace(slot);
+                self.set_footer(world, "PRESS ESCAPE TO CANCEL".to_string());
+
+                if let Some(menu) = self.menu.as_ref() {
+                    menu.set_item_text(world, index, format!("{}: PRESS A KEY", slot.label()));
+                }
+            }
+            None => {}
+        }
+
+        Trans::None
+    }
+}
+
+/// Short name of a button for the controls screen.
+fn button_label(button: Button) -> String {
+    match button {
+        Button::Key(key) => format!("{:?}", key).to_uppercase(),
+        Button::Controller(controller_id, controller_button) => {
+            format!("PAD{} {:?}", controller_id + 1, controller_button).to_uppercase()
+        }
+        Button::Mouse(mouse_button) => format!("MOUSE {:?}", mouse_button).to_uppercase(),
+        other => format!("{:?}", other).to_uppercase(),
+    }
+}
@@ -0,0 +1,77 @@
+use amethyst::{prelude::*, SimpleState, StateEvent};
+
+use super::Practice;
+use crate::{
+    entities::{initialize_menu, menu_command, Menu, MenuCommand},
+    settings::{Drill, GameplayConfig},
+};
+
+/// Drill select menu shown before a practice session: the drills of gameplay.ron, then quit.
+#[derive(Default)]
+pub struct DrillSelect {
+    drills: Vec<Drill>,
+    menu: Option<Menu>,
+}
+
+impl SimpleState for DrillSelect {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+
+        self.drills = world
+            .read_resource::<GameplayConfig>()
+            .practice
+            .drills
+            .clone();
+
+        let mut items = self
+            .drills
+            .iter()
+            .map(|drill| drill.name.to_uppercase())
+            .collect::<Vec<_>>();
+        items.push("QUIT".to_string());
+
+        self.menu
+            .replace(initialize_menu(world, "SELECT DRILL", &items));
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if let Some(menu) = self.menu.take() {
+            menu.delete(data.world);
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        let world = data.world;
+        let menu = match self.menu.as_mut() {
+            Some(menu) => menu,
+            None => return Trans::None,
+        };
+
+        let selected = match menu_command(&event) {
+            Some(MenuCommand::Previous) => {
+                menu.select_previous(world);
+                return Trans::None;
+            }
+            Some(MenuCommand::Next) => {
+                menu.select_next(world);
+                return Trans::None;
+            }
+            Some(MenuCommand::Confirm) => menu.selected(),
+            Some(MenuCommand::Back) => return Trans::Quit,
+            None => return Trans::None,
+        };
+
+        // the last item quits
+        if selected >= self.drills.len() {
+            return Trans::Quit;
+        }
+
+        let drill = self.drills.swap_remove(selected);
+
+        Trans::Switch(Box::new(Practice::new(drill)))
+    }
+}
@@ -0,0 +1,135 @@
+use amethyst::{prelude::*, SimpleState, StateEvent};
+
+use super::{LevelSelect, Pong};
+use crate::{
+    entities::{initialize_menu, menu_command, Menu, MenuCommand, Side},
+    settings::{GameplayConfig, HandicapOption, Handicaps},
+};
+
+/// Handicaps menu shown before a local match: each player's handicap options, changed to their
+/// next value when selected, then start and quit.
+pub struct HandicapSelect {
+    next: Option<Pong>,
+    select_level: bool,
+    players: Vec<Side>,
+    handicaps: Handicaps,
+    menu: Option<Menu>,
+}
+
+impl HandicapSelect {
+    /// Starts the given match once the handicaps are set.
+    pub fn new(next: Pong) -> Self {
+        HandicapSelect {
+            next: Some(next),
+            select_level: false,
+            players: Vec::new(),
+            handicaps: Handicaps::default(),
+            menu: None,
+        }
+    }
+
+    /// Shows the level select menu once the handicaps are set, before starting the match.
+    pub fn selecting_level(self) -> Self {
+        HandicapSelect {
+            select_level: true,
+            ..self
+        }
+    }
+
+    /// Player and option of a menu item, None for start and quit.
+    fn item(&self, index: usize) -> Option<(Side, HandicapOption)> {
+        let options = HandicapOption::ALL.len();
+        let player = self.players.get(index / options)?;
+
+        Some((*player, HandicapOption::ALL[index % options]))
+    }
+
+    fn item_text(&self, player: Side, option: HandicapOption) -> String {
+        format!(
+            "{} {}",
+            player.player_label(),
+            self.handicaps.get(player).label(option)
+        )
+    }
+}
+
+impl SimpleState for HandicapSelect {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+
+        if let Some(next) = self.next.as_ref() {
+            self.players = next.players(&world.read_resource::<GameplayConfig>());
+        }
+
+        let mut items = Vec::new();
+
+        for player in self.players.iter() {
+            for option in HandicapOption::ALL.iter() {
+                items.push(self.item_text(*player, *option));
+            }
+        }
+        items.push("START".to_string());
+        items.push("QUIT".to_string());
+
+        self.menu
+            .replace(initialize_menu(world, "HANDICAPS", &items));
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if let Some(menu) = self.menu.take() {
+            menu.delete(data.world);
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        let world = data.world;
+        let menu = match self.menu.as_mut() {
+            Some(menu) => menu,
+            None => return Trans::None,
+        };
+
+        let selected = match menu_command(&event) {
+            Some(MenuCommand::Previous) => {
+                menu.select_previous(world);
+                return Trans::None;
+            }
+            Some(MenuCommand::Next) => {
+                menu.select_next(world);
+                return Trans::None;
+            }
+            Some(MenuCommand::Confirm) => menu.selected(),
+            Some(MenuCommand::Back) => return Trans::Quit,
+            None => return Trans::None,
+        };
+
+        // the handicap options come first, then start and quit
+        if let Some((player, option)) = self.item(selected) {
+            self.handicaps.get_mut(player).cycle(option);
+
+            if let Some(menu) = self.menu.as_ref() {
+                menu.set_item_text(world, selected, self.item_text(player, option));
+            }
+
+            return Trans::None;
+        }
+
+        if selected > self.players.len() * HandicapOption::ALL.len() {
+            return Trans::Quit;
+        }
+
+        let next = match self.next.take() {
+            Some(next) => next.with_handicaps(self.handicaps.clone()),
+            None => return Trans::None,
+        };
+
+        if self.select_level {
+            Trans::Switch(Box::new(LevelSelect::new(next)))
+        } else {
+            Trans::Switch(Box::new(next))
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use amethyst::{prelude::*, utils::application_root_dir, SimpleState, StateEvent};
+use log::error;
+
+use crate::{
+    entities::{initialize_menu, menu_command, Menu, MenuCommand},
+    history::{match_history_path, MatchHistory, INITIAL_RATING},
+    settings::{profiles_path, Profiles},
+};
+
+/// Leaderboard screen: the profiles ranked by their rating, worked out from the match history.
+#[derive(Default)]
+pub struct Leaderboard {
+    ranked: usize, // lines listed before quit
+    menu: Option<Menu>,
+}
+
+impl SimpleState for Leaderboard {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+
+        let (profiles, history) = match application_root_dir() {
+            Ok(app_root) => (
+                Profiles::load_or_default(&profiles_path(&app_root)),
+                MatchHistory::load_or_default(&match_history_path(&app_root)),
+            ),
+            Err(err) => {
+                error!("Failed to find the profiles: {}", err);
+                (Profiles::default(), MatchHistory::default())
+            }
+        };
+
+        let ratings = history.ratings();
+        let mut ranking = profiles
+            .profiles
+            .iter()
+            .map(|profile| {
+                let rating = ratings
+                    .get(&profile.name)
+                    .cloned()
+                    .unwrap_or(INITIAL_RATING);
+
+                (profile.name.clone(), rating)
+            })
+            .collect::<Vec<_>>();
+
+        ranking.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+
+        let mut items = ranking
+            .iter()
+            .enumerate()
+            .map(|(rank, (name, rating))| format!("{}. {} {}", rank + 1, name, rating.round()))
+            .collect::<Vec<_>>();
+
+        if items.is_empty() {
+            items.push("NO PROFILES YET".to_string());
+        }
+
+        // the last item quits, after the ranking (or the missing profiles' line)
+        self.ranked = items.len();
+
+        items.push("QUIT".to_string());
+
+        let menu = initialize_menu(world, "LEADERBOARD", &items);
+
+        menu.set_footer(world, format!("RATINGS START AT {}", INITIAL_RATING));
+        self.menu.replace(menu);
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if let Some(menu) = self.menu.take() {
+            menu.delete(data.world);
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        let world = data.world;
+        let menu = match self.menu.as_mut() {
+            Some(menu) => menu,
+            None => return Trans::None,
+        };
+
+        match menu_command(&event) {
+            Some(MenuCommand::Previous) => menu.select_previous(world),
+            Some(MenuCommand::Next) => menu.select_next(world),
+            // the ranking is only there to be browsed
+            Some(MenuCommand::Confirm) if menu.selected() == self.ranked => return Trans::Quit,
+            Some(MenuCommand::Back) => return Trans::Quit,
+            _ => {}
+        }
+
+        Trans::None
+    }
+}
@@ -0,0 +1,96 @@
+use amethyst::{prelude::*, utils::application_root_dir, SimpleState, StateEvent};
+use log::error;
+
+use super::Pong;
+use crate::{
+    entities::{initialize_menu, menu_command, Menu, MenuCommand},
+    settings::{levels_dir, load_levels, Level},
+};
+
+/// Level select menu shown before a local match: the empty arena, then the levels found in the
+/// levels directory.
+pub struct LevelSelect {
+    next: Option<Pong>,
+    levels: Vec<Level>,
+    menu: Option<Menu>,
+}
+
+impl LevelSelect {
+    /// Starts the given match once a level is picked.
+    pub fn new(next: Pong) -> Self {
+        LevelSelect {
+            next: Some(next),
+            levels: Vec::new(),
+            menu: None,
+        }
+    }
+}
+
+impl SimpleState for LevelSelect {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+
+        match application_root_dir() {
+            Ok(app_root) => self.levels = load_levels(&levels_dir(&app_root)),
+            Err(err) => error!("Failed to find the levels: {}", err),
+        }
+
+        let mut items = vec![Level::default().name];
+        items.extend(self.levels.iter().map(|level| level.name.to_uppercase()));
+        items.push("QUIT".to_string());
+
+        self.menu
+            .replace(initialize_menu(world, "SELECT LEVEL", &items));
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if let Some(menu) = self.menu.take() {
+            menu.delete(data.world);
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        let world = data.world;
+        let menu = match self.menu.as_mut() {
+            Some(menu) => menu,
+            None => return Trans::None,
+        };
+
+        let selected = match menu_command(&event) {
+            Some(MenuCommand::Previous) => {
+                menu.select_previous(world);
+                return Trans::None;
+            }
+            Some(MenuCommand::Next) => {
+                menu.select_next(world);
+                return Trans::None;
+            }
+            Some(MenuCommand::Confirm) => menu.selected(),
+            Some(MenuCommand::Back) => return Trans::Quit,
+            None => return Trans::None,
+        };
+
+        // the first item is the empty arena and the last one quits
+        if selected > self.levels.len() {
+            return Trans::Quit;
+        }
+
+        let next = match self.next.take() {
+            Some(next) => next,
+            None => return Trans::None,
+        };
+
+        match selected {
+            0 => Trans::Switch(Box::new(next)),
+            _ => {
+                let level = self.levels.swap_remove(selected - 1);
+
+                Trans::Switch(Box::new(next.with_level(level)))
+            }
+        }
+    }
+}
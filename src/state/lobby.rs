@@ -0,0 +1,373 @@
+use amethyst::{prelude::*, SimpleState, StateEvent};
+use log::error;
+
+use super::Pong;
+use crate::{
+    entities::{initialize_menu, menu_command, Menu, MenuCommand},
+    net::{BuildInfo, LobbyBrowser, LobbyEvent, LobbyHost, LobbyMembership, MatchListing},
+    settings::GameplayConfig,
+};
+
+const HOST_ITEM: usize = 0;
+const START_ITEM: usize = 0;
+const READY_ITEM: usize = 0;
+/// Escape: same as the last item of every lobby menu (quit, cancel or leave).
+const BACK_ITEM: usize = usize::MAX;
+
+/// What the player is doing in the lobby.
+enum LobbyRole {
+    /// Looking at the matches advertised on the LAN.
+    Browsing,
+    Hosting(LobbyHost),
+    Joined(LobbyMembership),
+}
+
+/// LAN lobby: lists the matches advertised on the local network and lets the player host one
+/// or join one. Once both players are ready the host starts a netplay match.
+pub struct Lobby {
+    player_name: String,
+    build: Option<BuildInfo>,
+    browser: Option<LobbyBrowser>,
+    role: LobbyRole,
+    menu: Option<Menu>,
+    footer: String,
+}
+
+impl Lobby {
+    pub fn new(player_name: String) -> Self {
+        Lobby {
+            player_name,
+            build: None,
+            browser: None,
+            role: LobbyRole::Browsing,
+            menu: None,
+            footer: String::new(),
+        }
+    }
+
+    fn listings(&self) -> &[MatchListing] {
+        match self.browser.as_ref() {
+            Some(browser) => browser.listings(),
+            None => &[],
+        }
+    }
+
+    fn listing_text(&self, listing: &MatchListing) -> String {
+        let mut text = format!(
+            "{}  {}",
+            listing.advert.name.to_uppercase(),
+            listing.advert.rules
+        );
+
+        if self.incompatibility(listing).is_some() {
+            text.push_str("  (INCOMPATIBLE)");
+        }
+
+        text
+    }
+
+    fn incompatibility(&self, listing: &MatchListing) -> Option<String> {
+        self.build
+            .as_ref()
+            .and_then(|build| build.incompatibility(&listing.advert.build))
+    }
+
+    /// (Re)creates the menu for the current role, keeping the selection.
+    fn show_menu(&mut self, world: &mut World) {
+        let selected = match self.menu.take() {
+            Some(menu) => {
+                let selected = menu.selected();
+                menu.delete(world);
+                selected
+            }
+            None => 0,
+        };
+
+        let (title, items) = match &self.role {
+            LobbyRole::Browsing => {
+                let mut items = vec!["HOST MATCH".to_string()];
+                items.extend(
+                    self.listings()
+                        .iter()
+                        .map(|listing| self.listing_text(listing)),
+                );
+                items.push("QUIT".to_string());
+
+                ("LAN LOBBY".to_string(), items)
+            }
+            LobbyRole::Hosting(_) => (
+                format!("HOSTING {}", self.player_name.to_uppercase()),
+                vec!["START MATCH".to_string(), "CANCEL".to_string()],
+            ),
+            LobbyRole::Joined(membership) => (
+                "JOINED MATCH".to_string(),
+                vec![ready_text(membership.is_ready()), "LEAVE".to_string()],
+            ),
+        };
+
+        let mut menu = initialize_menu(world, &title, &items);
+
+        menu.select(world, selected);
+        menu.set_footer(world, self.footer.clone());
+
+        self.menu.replace(menu);
+    }
+
+    fn set_footer(&mut self, world: &mut World, text: String) {
+        if self.footer != text {
+            if let Some(menu) = self.menu.as_ref() {
+                menu.set_footer(world, text.clone());
+            }
+
+            self.footer = text;
+        }
+    }
+
+    /// Goes back to the list of matches (e.g. after cancelling or being refused).
+    fn browse(&mut self, world: &mut World, footer: String) {
+        self.role = LobbyRole::Browsing;
+        self.footer = footer;
+
+        if let Some(menu) = self.menu.as_mut() {
+            menu.select(world, 0);
+        }
+
+        self.show_menu(world);
+    }
+
+    fn host(&mut self, world: &mut World) {
+        let rules = world.read_resource::<GameplayConfig>().summary();
+        let build = match self.build.clone() {
+            Some(build) => build,
+            None => return,
+        };
+
+        match LobbyHost::bind(&self.player_name, rules, build) {
+            Ok(host) => {
+                self.role = LobbyRole::Hosting(host);
+                self.footer = "WAITING FOR A PLAYER".to_string();
+                self.show_menu(world);
+            }
+            Err(err) => {
+                error!("Failed to host a match: {}", err);
+                self.set_footer(world, "FAILED TO HOST A MATCH".to_string());
+            }
+        }
+    }
+
+    fn join(&mut self, world: &mut World, listing: MatchListing) {
+        // refused right away instead of by the host: the player sees why without waiting
+        if let Some(reason) = self.incompatibility(&listing) {
+            self.set_footer(world, reason);
+            return;
+        }
+
+        let build = match self.build.clone() {
+            Some(build) => build,
+            None => return,
+        };
+
+        match LobbyMembership::join(&listing, &self.player_name, build) {
+            Ok(membership) => {
+                self.role = LobbyRole::Joined(membership);
+                self.footer = "JOINING".to_string();
+                self.show_menu(world);
+            }
+            Err(err) => {
+                error!("Failed to join {}: {}", listing.host, err);
+                self.set_footer(world, "FAILED TO JOIN THE MATCH".to_string());
+            }
+        }
+    }
+
+    fn handle_browsing(&mut self, world: &mut World, selected: usize) -> SimpleTrans {
+        let quit_item = self.listings().len() + 1;
+
+        if selected == HOST_ITEM {
+            self.host(world);
+        } else if selected == quit_item {
+            return Trans::Quit;
+        } else if let Some(listing) = self.listings().get(selected - 1).cloned() {
+            self.join(world, listing);
+        }
+
+        Trans::None
+    }
+
+    fn handle_hosting(&mut self, world: &mut World, selected: usize) -> SimpleTrans {
+        if selected != START_ITEM {
+            self.browse(world, String::new());
+            return Trans::None;
+        }
+
+        let started = match &mut self.role {
+            LobbyRole::Hosting(host) => host.start(),
+            _ => return Trans::None,
+        };
+
+        match started {
+            Ok(Some(options)) => Trans::Switch(Box::new(Pong::netplay(options))),
+            Ok(None) => {
+                self.set_footer(world, "THE OTHER PLAYER ISN'T READY".to_string());
+                Trans::None
+            }
+            Err(err) => {
+                error!("Failed to start the match: {}", err);
+                self.browse(world, "FAILED TO START THE MATCH".to_string());
+                Trans::None
+            }
+        }
+    }
+
+    fn handle_joined(&mut self, world: &mut World, selected: usize) -> SimpleTrans {
+        if selected != READY_ITEM {
+            self.browse(world, String::new());
+            return Trans::None;
+        }
+
+        let membership = match &mut self.role {
+            LobbyRole::Joined(membership) => membership,
+            _ => return Trans::None,
+        };
+        let ready = !membership.is_ready();
+
+        match membership.set_ready(ready) {
+            Ok(()) => {
+                if let Some(menu) = self.menu.as_ref() {
+                    menu.set_item_text(world, READY_ITEM, ready_text(ready));
+                }
+            }
+            Err(err) => {
+                error!("Lost connection to the host: {}", err);
+                self.browse(world, "THE HOST LEFT".to_string());
+            }
+        }
+
+        Trans::None
+    }
+}
+
+impl SimpleState for Lobby {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+
+        self.build.replace(BuildInfo::current(world));
+
+        // hosting still works without the list (e.g. another game on this machine listens)
+        match LobbyBrowser::bind() {
+            Ok(browser) => {
+                self.browser.replace(browser);
+            }
+            Err(err) => {
+                error!("Failed to listen for LAN matches: {}", err);
+                self.footer = "CAN'T LIST THE LAN MATCHES".to_string();
+            }
+        }
+
+        self.show_menu(world);
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if let Some(menu) = self.menu.take() {
+            menu.delete(data.world);
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        let world = data.world;
+
+        let selected = match self.menu.as_mut() {
+            Some(menu) => match menu_command(&event) {
+                Some(MenuCommand::Previous) => {
+                    menu.select_previous(world);
+                    None
+                }
+                Some(MenuCommand::Next) => {
+                    menu.select_next(world);
+                    None
+                }
+                Some(MenuCommand::Confirm) => Some(menu.selected()),
+                Some(MenuCommand::Back) => Some(BACK_ITEM),
+                None => None,
+            },
+            None => None,
+        };
+
+        let selected = match selected {
+            Some(selected) => selected,
+            None => return Trans::None,
+        };
+
+        match self.role {
+            LobbyRole::Browsing if selected == BACK_ITEM => Trans::Quit,
+            LobbyRole::Browsing => self.handle_browsing(world, selected),
+            LobbyRole::Hosting(_) => self.handle_hosting(world, selected),
+            LobbyRole::Joined(_) => self.handle_joined(world, selected),
+        }
+    }
+
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        let listings_changed = self
+            .browser
+            .as_mut()
+            .map_or(false, |browser| browser.update());
+
+        if let LobbyRole::Browsing = self.role {
+            if listings_changed {
+                self.show_menu(data.world);
+            }
+
+            return Trans::None;
+        }
+
+        let (footer, event) = match &mut self.role {
+            LobbyRole::Browsing => return Trans::None,
+            LobbyRole::Hosting(host) => {
+                host.update();
+
+                let footer = match host.guest() {
+                    None => "WAITING FOR A PLAYER".to_string(),
+                    Some((name, false)) => format!("{} JOINED, NOT READY", name.to_uppercase()),
+                    Some((name, true)) => format!("{} IS READY", name.to_uppercase()),
+                };
+
+                (footer, None)
+            }
+            LobbyRole::Joined(membership) => {
+                let event = membership.update();
+                let footer = match membership.host_name() {
+                    Some(host_name) => {
+                        format!("WAITING FOR {} TO START", host_name.to_uppercase())
+                    }
+                    None => "JOINING".to_string(),
+                };
+
+                (footer, event)
+            }
+        };
+
+        match event {
+            Some(LobbyEvent::Start(options)) => Trans::Switch(Box::new(Pong::netplay(options))),
+            Some(LobbyEvent::Rejected(reason)) | Some(LobbyEvent::Left(reason)) => {
+                self.browse(data.world, reason);
+                Trans::None
+            }
+            None => {
+                self.set_footer(data.world, footer);
+                Trans::None
+            }
+        }
+    }
+}
+
+fn ready_text(ready: bool) -> String {
+    if ready {
+        "READY: YES".to_string()
+    } else {
+        "READY: NO".to_string()
+    }
+}
@@ -1,3 +1,25 @@
+mod controls;
+mod drill_select;
+mod handicap_select;
+mod leaderboard;
+mod level_select;
+mod lobby;
+mod paused;
 mod pong;
+mod practice;
+mod profile_select;
+mod run_state;
+mod stats;
 
+pub use controls::Controls;
+pub use drill_select::DrillSelect;
+pub use handicap_select::HandicapSelect;
+pub use leaderboard::Leaderboard;
+pub use level_select::LevelSelect;
+pub use lobby::Lobby;
+pub use paused::Paused;
 pub use pong::Pong;
+pub use practice::Practice;
+pub use profile_select::ProfileSelect;
+pub use run_state::RunState;
+pub use stats::Stats;
@@ -0,0 +1,110 @@
+use amethyst::{input::InputEvent, prelude::*, SimpleState, StateEvent};
+
+use super::{Controls, RunState};
+use crate::{
+    controls::is_pause_action,
+    entities::{initialize_menu, menu_command, Menu, MenuCommand},
+};
+
+const RESUME_ITEM: usize = 0;
+const CONTROLS_ITEM: usize = 1;
+const QUIT_ITEM: usize = 2;
+
+/// Pause menu pushed on top of the Pong state. Gameplay systems are halted while it is active.
+#[derive(Default)]
+pub struct Paused {
+    menu: Option<Menu>,
+    reason: Option<String>,
+    previous_run_state: RunState,
+}
+
+impl Paused {
+    /// Pause menu that explains why the game was paused (e.g. a controller was unplugged).
+    pub fn with_reason(reason: &str) -> Self {
+        Paused {
+            reason: Some(reason.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn show_menu(&mut self, world: &mut World) {
+        let items = ["RESUME", "CONTROLS", "QUIT"]
+            .iter()
+            .map(|item| item.to_string())
+            .collect::<Vec<_>>();
+        let menu = initialize_menu(world, "PAUSED", &items);
+
+        if let Some(reason) = self.reason.as_ref() {
+            menu.set_footer(world, reason.clone());
+        }
+
+        self.menu.replace(menu);
+    }
+
+    fn hide_menu(&mut self, world: &mut World) {
+        if let Some(menu) = self.menu.take() {
+            menu.delete(world);
+        }
+    }
+}
+
+impl SimpleState for Paused {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        // resource fetch must go out of scope before the world is borrowed again
+        {
+            let mut run_state = data.world.write_resource::<RunState>();
+
+            self.previous_run_state = *run_state;
+            *run_state = RunState::Paused;
+        }
+
+        self.show_menu(data.world);
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.hide_menu(data.world);
+        *data.world.write_resource::<RunState>() = self.previous_run_state;
+    }
+
+    // the controls screen is pushed on top of this state: it needs the screen for itself
+    fn on_pause(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.hide_menu(data.world);
+    }
+
+    fn on_resume(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.show_menu(data.world);
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        // pressing any player's pause button again resumes the game
+        if let StateEvent::Input(InputEvent::ActionPressed(action)) = &event {
+            if is_pause_action(action) {
+                return Trans::Pop;
+            }
+        }
+
+        let menu = match self.menu.as_mut() {
+            Some(menu) => menu,
+            None => return Trans::None,
+        };
+
+        match menu_command(&event) {
+            Some(MenuCommand::Previous) => menu.select_previous(data.world),
+            Some(MenuCommand::Next) => menu.select_next(data.world),
+            Some(MenuCommand::Confirm) => match menu.selected() {
+                RESUME_ITEM => return Trans::Pop,
+                CONTROLS_ITEM => return Trans::Push(Box::new(Controls::default())),
+                QUIT_ITEM => return Trans::Quit,
+                _ => {}
+            },
+            Some(MenuCommand::Back) => return Trans::Pop,
+            None => {}
+        }
+
+        Trans::None
+    }
+}
@@ -1,19 +1,256 @@
-use amethyst::{assets::Handle, core::Time, prelude::*, renderer::SpriteSheet, SimpleState};
+use std::fs;
 
+use amethyst::{
+    assets::Handle,
+    core::Time,
+    ecs::Entity,
+    input::{is_key_down, Button, InputEvent, InputHandler, StringBindings, VirtualKeyCode},
+    prelude::*,
+    renderer::SpriteSheet,
+    ui::UiText,
+    utils::application_root_dir,
+    SimpleState, StateEvent,
+};
+use log::{error, info};
+
+use super::{Paused, RunState};
 use crate::{
     audio::initialize_audio,
+    controls::{is_pause_action, ControllerAssignments, KeyBindings},
     entities::{
-        initialize_ball, initialize_camera, initialize_paddles, intialize_scoreboard, Ball, Paddle,
+        apply_handicaps, initialize_ball, initialize_bricks, initialize_camera, initialize_level,
+        initialize_mallets, initialize_overlay, initialize_paddles, initialize_physics,
+        initialize_power_ups, initialize_ring, intialize_scoreboard, Ball, Brick, Force,
+        MatchStats, MultiBall, Obstacle, Paddle, PaddleDriver, Portal, PowerUp, Scoreboard, Side,
+        Squash,
+    },
+    history::{match_history_path, MatchHistory, MatchRecord},
+    net::{NetplayOptions, NetplaySession, OnlineOptions, ServerConnection, SpectatorHost},
+    rng::initialize_rng,
+    saved_match::{saved_match_path, MatchRules, SavedMatch},
+    settings::{
+        high_scores_path, ControlsConfig, GameMode, GameplayConfig, Handicaps, HighScores, Level,
+        PlayerProfiles, BALL_SPAWN_DELAY, BALL_VELOCITY_X, BALL_VELOCITY_Y,
     },
-    settings::BALL_SPAWN_DELAY,
     sprite_sheet::load_sprite_sheet,
 };
 
 /// Represents the Pong game state.
 #[derive(Default)]
 pub struct Pong {
+    mode: GameMode,
+    multi_ball: bool,
+    power_ups: bool,
+    level: Option<Level>,
+    forces: Vec<Force>,
+    ring_players: usize,
+    handicaps: Handicaps,
+    profiles: PlayerProfiles,
+    saved_match: Option<SavedMatch>,
+    recorded: bool, // the match was played to its end and added to the match history
     ball_spawn_timer: Option<f32>,
     sprite_sheet_handle: Option<Handle<SpriteSheet>>,
+    netplay_options: Option<NetplayOptions>,
+    netplay_session: Option<NetplaySession>,
+    online_options: Option<OnlineOptions>,
+    server_connection: Option<ServerConnection>,
+    spectator_port: Option<u16>,
+    spectator_host: Option<SpectatorHost>,
+    net_stats_text: Option<Entity>,
+}
+
+impl Pong {
+    /// Local match in the given mode (e.g. four players or doubles).
+    pub fn local(mode: GameMode) -> Self {
+        Pong {
+            mode,
+            ..Default::default()
+        }
+    }
+
+    /// Local ring match: the given number of players (2 to 6) around a round arena.
+    pub fn ring(players: usize) -> Self {
+        Pong {
+            mode: GameMode::Ring,
+            ring_players: players,
+            ..Default::default()
+        }
+    }
+
+    /// Local match resumed where it was left: set up with the saved rules, then the saved
+    /// balls, paddles, scores and random numbers are restored.
+    pub fn resume(saved_match: SavedMatch) -> Self {
+        let rules = saved_match.rules.clone();
+
+        Pong {
+            mode: rules.mode,
+            multi_ball: rules.multi_ball,
+            power_ups: rules.power_ups,
+            level: rules.level,
+            forces: rules.forces,
+            ring_players: rules.ring_players,
+            handicaps: rules.handicaps,
+            profiles: rules.profiles,
+            saved_match: Some(saved_match),
+            ..Default::default()
+        }
+    }
+
+    /// Rules the match is played with, saved along with its state.
+    fn rules(&self) -> MatchRules {
+        MatchRules {
+            mode: self.mode,
+            multi_ball: self.multi_ball,
+            power_ups: self.power_ups,
+            level: self.level.clone(),
+            forces: self.forces.clone(),
+            ring_players: self.ring_players,
+            handicaps: self.handicaps.clone(),
+            profiles: self.profiles.clone(),
+        }
+    }
+
+    /// Serves extra balls during rallies and a new ball once every ball went into a goal.
+    pub fn with_multi_ball(self) -> Self {
+        Pong {
+            multi_ball: true,
+            ..self
+        }
+    }
+
+    /// Spawns power-up pickups in the arena, claimed by the last player to hit the ball.
+    pub fn with_power_ups(self) -> Self {
+        Pong {
+            power_ups: true,
+            ..self
+        }
+    }
+
+    /// Plays in the level's arena: obstacles, portals and narrowed goals.
+    pub fn with_level(self, level: Level) -> Self {
+        Pong {
+            level: Some(level),
+            ..self
+        }
+    }
+
+    /// Turns on the given arena physics (gravity, wind gusts and air drag).
+    pub fn with_forces(self, forces: Vec<Force>) -> Self {
+        Pong { forces, ..self }
+    }
+
+    /// Handicaps the players (e.g. shorter paddles or starting points), as set in the handicaps
+    /// menu.
+    pub fn with_handicaps(self, handicaps: Handicaps) -> Self {
+        Pong { handicaps, ..self }
+    }
+
+    /// Names the players after the profiles they picked, whose preferred controls they use.
+    pub fn with_profiles(self, profiles: PlayerProfiles) -> Self {
+        Pong { profiles, ..self }
+    }
+
+    /// Players driving a paddle in this match (by the side of their controls), in player order.
+    pub fn players(&self, gameplay_config: &GameplayConfig) -> Vec<Side> {
+        let drivers = match self.mode {
+            GameMode::Ring => gameplay_config
+                .ring
+                .seats
+                .iter()
+                .take(self.ring_players)
+                .cloned()
+                .collect::<Vec<_>>(),
+            _ => self
+                .mode
+                .lineup(gameplay_config)
+                .paddles
+                .iter()
+                .map(|placement| placement.driver)
+                .collect(),
+        };
+
+        Side::ALL
+            .iter()
+            .cloned()
+            .filter(|side| drivers.contains(&PaddleDriver::Player(*side)))
+            .collect()
+    }
+
+    /// Pong match against a remote player over UDP.
+    pub fn netplay(options: NetplayOptions) -> Self {
+        Pong {
+            netplay_options: Some(options),
+            ..Default::default()
+        }
+    }
+
+    /// Opens the netplay session: from now on the simulation is stepped by the session on each
+    /// fixed update instead of by the game loop.
+    fn start_netplay(&mut self, world: &mut World, options: &NetplayOptions) {
+        let session = match NetplaySession::new(options, world) {
+            Ok(session) => session,
+            Err(err) => {
+                error!("Failed to start netplay, playing locally instead: {}", err);
+                return;
+            }
+        };
+
+        *world.write_resource::<RunState>() = RunState::Networked;
+
+        // a rollback can't bring back the balls of a match ended by a mispredicted tick: netplay
+        // matches go on until they're quit
+        world.write_resource::<GameplayConfig>().points_to_win = None;
+
+        // both peers must spawn the ball at the same tick: right away
+        self.ball_spawn_timer.take();
+        initialize_ball(
+            world,
+            [BALL_VELOCITY_X, BALL_VELOCITY_Y],
+            self.sprite_sheet_handle.clone(),
+        );
+
+        self.net_stats_text.replace(initialize_overlay(world));
+        self.netplay_session.replace(session);
+    }
+
+    /// Pong match played (or watched) on a game server, which runs the simulation.
+    pub fn online(options: OnlineOptions) -> Self {
+        Pong {
+            online_options: Some(options),
+            ..Default::default()
+        }
+    }
+
+    /// Lets spectators watch this (local or netplay) match by connecting to the given port.
+    pub fn hosting_spectators(self, port: u16) -> Self {
+        Pong {
+            spectator_port: Some(port),
+            ..self
+        }
+    }
+
+    /// Connects to the game server: from now on the match state is received from the server
+    /// and the game only sends the local player's inputs.
+    fn start_online(&mut self, world: &mut World, options: &OnlineOptions) {
+        let connection = match ServerConnection::connect(options) {
+            Ok(connection) => connection,
+            Err(err) => {
+                error!(
+                    "Failed to connect to {}, playing locally instead: {}",
+                    options.server, err
+                );
+                return;
+            }
+        };
+
+        *world.write_resource::<RunState>() = RunState::Networked;
+
+        // the server serves the ball
+        self.ball_spawn_timer.take();
+
+        self.net_stats_text.replace(initialize_overlay(world));
+        self.server_connection.replace(connection);
+    }
 }
 
 impl SimpleState for Pong {
@@ -21,19 +258,249 @@ impl SimpleState for Pong {
         let world = _data.world;
 
         self.sprite_sheet_handle.replace(load_sprite_sheet(world)); // adds sprite sheet handle state
-        self.ball_spawn_timer.replace(BALL_SPAWN_DELAY); // adds ball time spawner
+
+        // multi-ball matches are served by the MultiBallSystem instead
+        if self.multi_ball {
+            world.insert(MultiBall::new(self.sprite_sheet_handle.clone()));
+        } else {
+            self.ball_spawn_timer.replace(BALL_SPAWN_DELAY); // adds ball time spawner
+        }
 
         world.register::<Paddle>(); // in order to use Paddle Component on an entity
         world.register::<Ball>(); // in order to use the Ball Component on an entity
+        world.insert(self.mode); // read by the collision and scoring systems
+
+        // players drive their paddles the way their profile prefers
+        let players = self.players(&world.read_resource::<GameplayConfig>());
+
+        for player in players {
+            if let Some(scheme) = self
+                .profiles
+                .get(player)
+                .and_then(|profile| profile.controls)
+            {
+                world
+                    .write_resource::<ControlsConfig>()
+                    .players
+                    .entry(player)
+                    .or_default()
+                    .scheme = scheme;
+            }
+        }
+
+        world.insert(self.profiles.clone()); // names the players on the scoreboard and ring
+
+        if self.mode == GameMode::FourPlayer || self.mode == GameMode::AirHockey {
+            add_missing_bindings(world);
+        }
+
+        if self.mode == GameMode::Squash {
+            world.insert(Squash::new(load_high_scores().squash));
+        }
 
         // entities and their componenets initialization
         initialize_camera(world);
-        initialize_paddles(world, self.sprite_sheet_handle.clone().unwrap());
-        intialize_scoreboard(world);
+        let lineup = self.mode.lineup(&world.read_resource::<GameplayConfig>());
+
+        if self.mode == GameMode::AirHockey {
+            let config = world.read_resource::<GameplayConfig>().air_hockey.clone();
+
+            initialize_mallets(world, self.sprite_sheet_handle.clone(), &lineup, &config);
+
+            // the goal mouths narrow the goals of the level (of an empty arena without one)
+            let level = self.level.take().unwrap_or_default();
+            self.level.replace(Level {
+                goal_size: Some(config.goal_width),
+                ..level
+            });
+        } else {
+            initialize_paddles(world, self.sprite_sheet_handle.clone(), &lineup);
+        }
+        intialize_scoreboard(world, self.mode);
+
+        // the level is kept in the rules saved with the match
+        if let Some(level) = self.level.clone() {
+            world.register::<Obstacle>();
+            world.register::<Portal>();
+            initialize_level(world, level, self.mode, self.sprite_sheet_handle.clone());
+        }
+
+        if self.mode == GameMode::Ring {
+            let config = world.read_resource::<GameplayConfig>().ring.clone();
+
+            initialize_ring(
+                world,
+                self.ring_players,
+                &config,
+                self.sprite_sheet_handle.clone(),
+            );
+        }
+
+        // every paddle is in the arena by now
+        apply_handicaps(world, &self.handicaps);
+
+        for side in self.mode.sides() {
+            let starting_points = self.handicaps.get(*side).starting_points;
+
+            world
+                .write_resource::<Scoreboard>()
+                .add_points(*side, starting_points);
+        }
+
+        if self.mode == GameMode::Breakout {
+            let config = world.read_resource::<GameplayConfig>().breakout.clone();
+
+            world.register::<Brick>();
+            initialize_bricks(world, &config, self.sprite_sheet_handle.clone());
+        }
+
+        // breakout bricks drop power-ups, even without --power-ups
+        if self.power_ups || self.mode == GameMode::Breakout {
+            world.register::<PowerUp>();
+            initialize_power_ups(world, self.mode, self.sprite_sheet_handle.clone());
+        }
+
+        if !self.forces.is_empty() {
+            initialize_physics(world, &self.forces, self.sprite_sheet_handle.clone());
+        }
+
+        initialize_rng(world);
         initialize_audio(world);
+
+        // local matches are recorded in the match history
+        if self.netplay_options.is_none() && self.online_options.is_none() {
+            world.insert(MatchStats::new());
+        }
+
+        if let Some(saved_match) = self.saved_match.take() {
+            self.ball_spawn_timer = saved_match.restore(world, self.sprite_sheet_handle.clone());
+        }
+
+        if let Some(options) = self.netplay_options.take() {
+            self.start_netplay(world, &options);
+        }
+
+        if let Some(options) = self.online_options.take() {
+            self.start_online(world, &options);
+        }
+
+        if let Some(port) = self.spectator_port.take() {
+            match SpectatorHost::bind(port) {
+                Ok(host) => {
+                    info!("Spectators can watch the match on port {}", port);
+                    self.spectator_host.replace(host);
+                }
+                Err(err) => error!("Failed to host spectators on port {}: {}", port, err),
+            }
+        }
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        // squash: keeps the best streak for the next sessions
+        if let Some(squash) = data.world.try_fetch::<Squash>() {
+            if squash.has_new_record() {
+                save_high_scores(|high_scores| high_scores.squash = squash.high_score());
+            }
+        }
+
+        // local matches left before their end are recorded and can be resumed with --resume
+        if self.netplay_session.is_none() && self.server_connection.is_none() {
+            if !self.recorded {
+                record_match(data.world, self.rules(), false);
+            }
+
+            save_match(data.world, self.rules(), self.ball_spawn_timer);
+        }
+    }
+
+    fn fixed_update(&mut self, data: StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        if let Some(session) = self.netplay_session.as_mut() {
+            session.advance(data.world);
+        }
+
+        if let Some(connection) = self.server_connection.as_mut() {
+            connection.send_input(data.world);
+        }
+
+        if let Some(host) = self.spectator_host.as_mut() {
+            host.update(data.world);
+        }
+
+        Trans::None
+    }
+
+    fn handle_event(
+        &mut self,
+        _data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        // stats overlay (game server only)
+        if let StateEvent::Window(window_event) = &event {
+            if is_key_down(window_event, VirtualKeyCode::Tab) {
+                if let Some(connection) = self.server_connection.as_mut() {
+                    connection.toggle_stats();
+                }
+            }
+        }
+
+        // the input event (rather than the window one) is also the one the pause menu sees, so
+        // the key press that pauses the match doesn't resume it right away
+        let pause_requested = match &event {
+            StateEvent::Input(InputEvent::ButtonPressed(Button::Key(VirtualKeyCode::Escape))) => {
+                true
+            }
+            StateEvent::Input(InputEvent::ActionPressed(action)) => is_pause_action(action),
+            _ => false,
+        };
+
+        if pause_requested {
+            Trans::Push(Box::new(Paused::default()))
+        } else {
+            Trans::None
+        }
     }
 
     fn update(&mut self, _data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        // a player lost their controller: wait for it to be plugged back in
+        if _data
+            .world
+            .write_resource::<ControllerAssignments>()
+            .take_disconnected()
+        {
+            return Trans::Push(Box::new(Paused::with_reason("CONTROLLER DISCONNECTED")));
+        }
+
+        if let Some(connection) = self.server_connection.as_mut() {
+            connection.update(_data.world, self.sprite_sheet_handle.clone());
+        }
+
+        // network stats overlay (netplay and game server only)
+        let net_stats = match (&self.netplay_session, &self.server_connection) {
+            (Some(session), _) => Some(session.stats().summary()),
+            (None, Some(connection)) => Some(connection.overlay_text()),
+            (None, None) => None,
+        };
+
+        if let (Some(net_stats), Some(text_entity)) = (net_stats, self.net_stats_text) {
+            if let Some(text) = _data.world.write_storage::<UiText>().get_mut(text_entity) {
+                text.text = net_stats;
+            }
+        }
+
+        if _data
+            .world
+            .read_resource::<Scoreboard>()
+            .is_match_over(self.mode)
+        {
+            // the match ended (e.g. a player reached the points to win): no more serves
+            self.ball_spawn_timer = None;
+
+            if !self.recorded {
+                record_match(_data.world, self.rules(), true);
+                self.recorded = true;
+            }
+        }
+
         // take the value from the Option, leaving None in its place
         if let Some(mut ball_timer) = self.ball_spawn_timer.take() {
             // time/fetch struct must be deallocated by going out of scope to avoid problems
@@ -43,9 +510,17 @@ impl SimpleState for Pong {
             }
 
             if ball_timer <= 0.0 {
-                let sprite_sheet_handle = self.sprite_sheet_handle.clone().unwrap(); // clone and unwraps Option handle
+                let sprite_sheet_handle = self.sprite_sheet_handle.clone(); // clone the Option handle
+
+                // air hockey: the puck is dropped at rest
+                let velocity = if self.mode == GameMode::AirHockey {
+                    [0.0, 0.0]
+                } else {
+                    [BALL_VELOCITY_X, BALL_VELOCITY_Y]
+                };
 
-                initialize_ball(_data.world, sprite_sheet_handle); // handler is consumed per entitty
+                // handler is consumed per entitty
+                initialize_ball(_data.world, velocity, sprite_sheet_handle);
             } else {
                 self.ball_spawn_timer.replace(ball_timer);
             }
@@ -54,3 +529,85 @@ impl SimpleState for Pong {
         Trans::None
     }
 }
+
+/// Key bindings saved before four-player (or air-hockey) mode existed have no P3/P4 (or mallet
+/// forward/back) controls: the defaults are used for them.
+fn add_missing_bindings(world: &mut World) {
+    let mut input = world.write_resource::<InputHandler<StringBindings>>();
+
+    match KeyBindings::from_bindings(&input.bindings).to_bindings() {
+        Ok(bindings) => input.bindings = bindings,
+        Err(err) => error!("Failed to add the missing key bindings: {}", err),
+    }
+}
+
+/// Adds the match (if it's a local one) to the match history.
+fn record_match(world: &World, rules: MatchRules, finished: bool) {
+    let record = match MatchRecord::capture(world, rules, finished) {
+        Some(record) => record,
+        None => return,
+    };
+
+    let recorded = application_root_dir()
+        .map_err(amethyst::Error::from)
+        .and_then(|app_root| MatchHistory::append(&match_history_path(&app_root), &record));
+
+    if let Err(err) = recorded {
+        error!("Failed to record the match: {}", err);
+    }
+}
+
+/// Saves the match in progress to be resumed later, or forgets the saved match once it's over.
+fn save_match(world: &World, rules: MatchRules, ball_spawn_timer: Option<f32>) {
+    let path = match application_root_dir() {
+        Ok(app_root) => saved_match_path(&app_root),
+        Err(err) => {
+            error!("Failed to save the match: {}", err);
+            return;
+        }
+    };
+
+    let saved = if world
+        .read_resource::<Scoreboard>()
+        .is_match_over(rules.mode)
+    {
+        if path.exists() {
+            fs::remove_file(&path).map_err(amethyst::Error::from)
+        } else {
+            Ok(())
+        }
+    } else {
+        SavedMatch::capture(world, rules, ball_spawn_timer).save(&path)
+    };
+
+    if let Err(err) = saved {
+        error!("Failed to save the match: {}", err);
+    }
+}
+
+fn load_high_scores() -> HighScores {
+    match application_root_dir() {
+        Ok(app_root) => HighScores::load_or_default(&high_scores_path(&app_root)),
+        Err(err) => {
+            error!("Failed to load the high scores: {}", err);
+            HighScores::default()
+        }
+    }
+}
+
+/// Updates the saved high scores.
+fn save_high_scores(update: impl FnOnce(&mut HighScores)) {
+    let saved = application_root_dir()
+        .map_err(amethyst::Error::from)
+        .and_then(|app_root| {
+            let path = high_scores_path(&app_root);
+            let mut high_scores = HighScores::load_or_default(&path);
+
+            update(&mut high_scores);
+            high_scores.save(&path)
+        });
+
+    if let Err(err) = saved {
+        error!("Failed to save the high scores: {}", err);
+    }
+}
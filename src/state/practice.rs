@@ -0,0 +1,198 @@
+use amethyst::{
+    assets::Handle,
+    core::Time,
+    ecs::{Entity, Join},
+    input::{Button, InputEvent, VirtualKeyCode},
+    prelude::*,
+    renderer::SpriteSheet,
+    SimpleState, StateEvent,
+};
+use log::error;
+use rand::Rng;
+
+use super::Paused;
+use crate::{
+    audio::initialize_audio,
+    controls::{is_pause_action, ControllerAssignments},
+    entities::{
+        initialize_ball, initialize_camera, initialize_paddles, initialize_practice, shot_velocity,
+        Ball, Paddle, PracticeSession,
+    },
+    rng::{initialize_rng, GameRng, RngStream},
+    settings::{Drill, GameMode, GameplayConfig},
+    sprite_sheet::load_sprite_sheet,
+};
+
+/// Fires the last shot again.
+const REWIND_KEY: VirtualKeyCode = VirtualKeyCode::R;
+
+/// Practice session: a launcher in the middle of the arena fires the drill's shots at the
+/// player's paddle, one at a time, and the returns are scored against the target zones of the
+/// wall (see the PracticeSystem).
+pub struct Practice {
+    drill: Option<Drill>,
+    launch_timer: Option<f32>,
+    sprite_sheet_handle: Option<Handle<SpriteSheet>>,
+}
+
+impl Practice {
+    pub fn new(drill: Drill) -> Self {
+        Practice {
+            drill: Some(drill),
+            launch_timer: None,
+            sprite_sheet_handle: None,
+        }
+    }
+
+    /// Fires a ball from the launcher, in the middle of the arena.
+    fn launch(&mut self, world: &mut World, velocity: [f32; 2]) {
+        initialize_ball(world, velocity, self.sprite_sheet_handle.clone());
+    }
+
+    /// Takes the ball in play out of the arena and fires the last shot again.
+    fn rewind(&mut self, world: &mut World) {
+        let velocity = match world.write_resource::<PracticeSession>().rewind() {
+            Some(velocity) => velocity,
+            None => return,
+        };
+
+        let balls = {
+            let entities = world.entities();
+            let ball_storage = world.read_storage::<Ball>();
+
+            (&entities, &ball_storage)
+                .join()
+                .map(|(entity, _)| entity)
+                .collect::<Vec<Entity>>()
+        };
+
+        if let Err(err) = world.delete_entities(&balls) {
+            error!("Failed to delete the balls: {}", err);
+        }
+
+        self.launch_timer.take();
+        self.launch(world, velocity);
+    }
+}
+
+impl SimpleState for Practice {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+
+        self.sprite_sheet_handle.replace(load_sprite_sheet(world));
+
+        world.register::<Paddle>();
+        world.register::<Ball>();
+        world.insert(GameMode::Practice);
+
+        initialize_camera(world);
+
+        let config = world.read_resource::<GameplayConfig>().clone();
+        let lineup = GameMode::Practice.lineup(&config);
+
+        initialize_paddles(world, self.sprite_sheet_handle.clone(), &lineup);
+
+        if let Some(drill) = self.drill.take() {
+            initialize_practice(
+                world,
+                drill,
+                &config.practice.target_zones,
+                self.sprite_sheet_handle.clone(),
+            );
+        }
+
+        initialize_rng(world);
+        initialize_audio(world);
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        if let StateEvent::Input(InputEvent::ButtonPressed(Button::Key(REWIND_KEY))) = &event {
+            self.rewind(data.world);
+            return Trans::None;
+        }
+
+        // the input event (rather than the window one) is also the one the pause menu sees
+        let pause_requested = match &event {
+            StateEvent::Input(InputEvent::ButtonPressed(Button::Key(VirtualKeyCode::Escape))) => {
+                true
+            }
+            StateEvent::Input(InputEvent::ActionPressed(action)) => is_pause_action(action),
+            _ => false,
+        };
+
+        if pause_requested {
+            Trans::Push(Box::new(Paused::default()))
+        } else {
+            Trans::None
+        }
+    }
+
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        let world = &mut *data.world;
+
+        // the player lost their controller: wait for it to be plugged back in
+        if world
+            .write_resource::<ControllerAssignments>()
+            .take_disconnected()
+        {
+            return Trans::Push(Box::new(Paused::with_reason("CONTROLLER DISCONNECTED")));
+        }
+
+        let (ready, drill) = {
+            let session = world.read_resource::<PracticeSession>();
+
+            (
+                !session.is_shot_in_play() && !session.is_over(),
+                session.drill().clone(),
+            )
+        };
+
+        if !ready {
+            return Trans::None;
+        }
+
+        // the launcher waits a moment after each shot
+        let launch_delay = world
+            .read_resource::<GameplayConfig>()
+            .practice
+            .launch_delay;
+        let launch_timer =
+            self.launch_timer.unwrap_or(launch_delay) - world.fetch::<Time>().delta_seconds();
+
+        if launch_timer > 0.0 {
+            self.launch_timer.replace(launch_timer);
+            return Trans::None;
+        }
+
+        self.launch_timer.take();
+
+        let velocity = {
+            let mut game_rng = world.write_resource::<GameRng>();
+            let rng = game_rng.stream(RngStream::Launcher);
+
+            shot_velocity(
+                random_in(rng, drill.min_angle, drill.max_angle),
+                random_in(rng, drill.min_speed, drill.max_speed),
+                rng.gen(),
+            )
+        };
+
+        world.write_resource::<PracticeSession>().launch(velocity);
+        self.launch(world, velocity);
+
+        Trans::None
+    }
+}
+
+/// Random value within the range (the range's start when it's empty).
+fn random_in(rng: &mut impl Rng, min: f32, max: f32) -> f32 {
+    if max > min {
+        rng.gen_range(min, max)
+    } else {
+        min
+    }
+}
@@ -0,0 +1,176 @@
+use amethyst::{prelude::*, utils::application_root_dir, SimpleState, StateEvent};
+use log::error;
+
+use super::{HandicapSelect, LevelSelect, Pong};
+use crate::{
+    entities::{initialize_menu, menu_command, Menu, MenuCommand, Side},
+    settings::{profiles_path, GameplayConfig, PlayerProfiles, Profile, Profiles},
+};
+
+/// Profiles menu shown before a local match: each player's profile, changed to the next one
+/// not picked by another player when selected (players without one play as guests), then start
+/// and quit.
+pub struct ProfileSelect {
+    next: Option<Pong>,
+    set_handicaps: bool,
+    select_level: bool,
+    players: Vec<Side>,
+    profiles: Vec<Profile>,
+    picks: Vec<Option<usize>>, // index of each player's profile
+    menu: Option<Menu>,
+}
+
+impl ProfileSelect {
+    /// Starts the given match once the profiles are picked.
+    pub fn new(next: Pong) -> Self {
+        ProfileSelect {
+            next: Some(next),
+            set_handicaps: false,
+            select_level: false,
+            players: Vec::new(),
+            profiles: Vec::new(),
+            picks: Vec::new(),
+            menu: None,
+        }
+    }
+
+    /// Shows the handicaps menu once the profiles are picked.
+    pub fn setting_handicaps(self) -> Self {
+        ProfileSelect {
+            set_handicaps: true,
+            ..self
+        }
+    }
+
+    /// Shows the level select menu once the profiles (and handicaps) are set.
+    pub fn selecting_level(self) -> Self {
+        ProfileSelect {
+            select_level: true,
+            ..self
+        }
+    }
+
+    /// Next profile of a player: the following one that no other player picked, or none after
+    /// the last one.
+    fn next_pick(&self, player: usize) -> Option<usize> {
+        let first = self.picks[player].map_or(0, |pick| pick + 1);
+
+        (first..self.profiles.len()).find(|profile| {
+            self.picks
+                .iter()
+                .enumerate()
+                .all(|(other, pick)| other == player || *pick != Some(*profile))
+        })
+    }
+
+    fn item_text(&self, player: usize) -> String {
+        let name = match self.picks[player] {
+            Some(pick) => self.profiles[pick].name.as_str(),
+            None => "GUEST",
+        };
+
+        format!("{} {}", self.players[player].player_label(), name)
+    }
+
+    fn player_profiles(&self) -> PlayerProfiles {
+        let mut profiles = PlayerProfiles::default();
+
+        for (player, pick) in self.players.iter().zip(self.picks.iter()) {
+            profiles.set(*player, pick.map(|pick| self.profiles[pick].clone()));
+        }
+
+        profiles
+    }
+}
+
+impl SimpleState for ProfileSelect {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+
+        match application_root_dir() {
+            Ok(app_root) => {
+                self.profiles = Profiles::load_or_default(&profiles_path(&app_root)).profiles
+            }
+            Err(err) => error!("Failed to find the profiles: {}", err),
+        }
+
+        if let Some(next) = self.next.as_ref() {
+            self.players = next.players(&world.read_resource::<GameplayConfig>());
+        }
+
+        self.picks = vec![None; self.players.len()];
+
+        let mut items = (0..self.players.len())
+            .map(|player| self.item_text(player))
+            .collect::<Vec<_>>();
+        items.push("START".to_string());
+        items.push("QUIT".to_string());
+
+        let menu = initialize_menu(world, "PROFILES", &items);
+
+        if self.profiles.is_empty() {
+            menu.set_footer(world, "CREATE ONE WITH --new-profile <name>".to_string());
+        }
+
+        self.menu.replace(menu);
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if let Some(menu) = self.menu.take() {
+            menu.delete(data.world);
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        let world = data.world;
+        let menu = match self.menu.as_mut() {
+            Some(menu) => menu,
+            None => return Trans::None,
+        };
+
+        let selected = match menu_command(&event) {
+            Some(MenuCommand::Previous) => {
+                menu.select_previous(world);
+                return Trans::None;
+            }
+            Some(MenuCommand::Next) => {
+                menu.select_next(world);
+                return Trans::None;
+            }
+            Some(MenuCommand::Confirm) => menu.selected(),
+            Some(MenuCommand::Back) => return Trans::Quit,
+            None => return Trans::None,
+        };
+
+        // the players come first, then start and quit
+        if selected < self.players.len() {
+            self.picks[selected] = self.next_pick(selected);
+
+            if let Some(menu) = self.menu.as_ref() {
+                menu.set_item_text(world, selected, self.item_text(selected));
+            }
+
+            return Trans::None;
+        }
+
+        if selected > self.players.len() {
+            return Trans::Quit;
+        }
+
+        let next = match self.next.take() {
+            Some(next) => next.with_profiles(self.player_profiles()),
+            None => return Trans::None,
+        };
+
+        match (self.set_handicaps, self.select_level) {
+            (true, true) => Trans::Switch(Box::new(HandicapSelect::new(next).selecting_level())),
+            (true, false) => Trans::Switch(Box::new(HandicapSelect::new(next))),
+            (false, true) => Trans::Switch(Box::new(LevelSelect::new(next))),
+            (false, false) => Trans::Switch(Box::new(next)),
+        }
+    }
+}
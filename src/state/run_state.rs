@@ -0,0 +1,15 @@
+/// Resource that gates the gameplay systems: they only run while the game is `Running`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RunState {
+    Running,
+    Paused,
+    /// The simulation is stepped by the netplay session (at a fixed rate) or runs on the game
+    /// server instead.
+    Networked,
+}
+
+impl Default for RunState {
+    fn default() -> Self {
+        RunState::Running
+    }
+}
@@ -0,0 +1,122 @@
+use amethyst::{prelude::*, utils::application_root_dir, SimpleState, StateEvent};
+use log::error;
+
+use crate::{
+    entities::{initialize_menu, menu_command, Menu, MenuCommand},
+    history::{match_history_path, MatchHistory, PlayerStats},
+};
+
+/// Stats screen: the win/loss records of the players of the match history, with the personal
+/// bests and head-to-head results of the selected one below.
+#[derive(Default)]
+pub struct Stats {
+    history: MatchHistory,
+    players: Vec<PlayerStats>,
+    menu: Option<Menu>,
+}
+
+impl Stats {
+    /// Shows the selected player's bests and results against the others in the footer.
+    fn show_details(&self, world: &mut World) {
+        let menu = match self.menu.as_ref() {
+            Some(menu) => menu,
+            None => return,
+        };
+
+        let footer = match self.players.get(menu.selected()) {
+            Some(player) => {
+                let mut details = vec![
+                    format!("ABANDONED {}", player.abandoned),
+                    format!("BEST STREAK {}", player.best_streak),
+                    format!("BEST SCORE {}", player.best_score),
+                    format!("LONGEST RALLY {}", player.longest_rally),
+                ];
+
+                details.extend(
+                    self.players
+                        .iter()
+                        .filter(|opponent| opponent.name != player.name)
+                        .map(|opponent| {
+                            let (won, lost) =
+                                self.history.head_to_head(&player.name, &opponent.name);
+
+                            format!("VS {} {}-{}", opponent.name, won, lost)
+                        }),
+                );
+
+                details.join("  ")
+            }
+            None => String::new(),
+        };
+
+        menu.set_footer(world, footer);
+    }
+}
+
+impl SimpleState for Stats {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+
+        match application_root_dir() {
+            Ok(app_root) => {
+                self.history = MatchHistory::load_or_default(&match_history_path(&app_root))
+            }
+            Err(err) => error!("Failed to find the match history: {}", err),
+        }
+
+        self.players = self.history.player_stats();
+
+        let mut items = self
+            .players
+            .iter()
+            .map(|player| {
+                format!(
+                    "{}  {}W {}L  STREAK {}",
+                    player.name, player.wins, player.losses, player.streak
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if items.is_empty() {
+            items.push("NO MATCHES PLAYED YET".to_string());
+        }
+
+        items.push("QUIT".to_string());
+
+        self.menu.replace(initialize_menu(world, "STATS", &items));
+        self.show_details(world);
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if let Some(menu) = self.menu.take() {
+            menu.delete(data.world);
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        let world = data.world;
+        // the last item quits, after the players' lines (or the empty history's)
+        let quit_item = self.players.len().max(1);
+        let menu = match self.menu.as_mut() {
+            Some(menu) => menu,
+            None => return Trans::None,
+        };
+
+        match menu_command(&event) {
+            Some(MenuCommand::Previous) => menu.select_previous(world),
+            Some(MenuCommand::Next) => menu.select_next(world),
+            // the players' lines are only there to be browsed
+            Some(MenuCommand::Confirm) if menu.selected() == quit_item => return Trans::Quit,
+            Some(MenuCommand::Back) => return Trans::Quit,
+            _ => return Trans::None,
+        }
+
+        self.show_details(world);
+
+        Trans::None
+    }
+}
@@ -1,10 +1,13 @@
 use amethyst::{
     core::{Time, Transform},
     derive::SystemDesc,
-    ecs::{Join, Read, ReadStorage, System, SystemData, WriteStorage},
+    ecs::{Entities, Join, Read, ReadExpect, System, SystemData, Write, WriteStorage},
 };
 
-use crate::entities::Ball;
+use crate::{
+    entities::{ArenaPhysics, Ball, PowerUps},
+    settings::{GameMode, GameplayConfig},
+};
 
 // SystemDesc requires System Trait implementation
 #[derive(SystemDesc)]
@@ -13,21 +16,108 @@ pub struct BallSystem;
 impl<'s> System<'s> for BallSystem {
     type SystemData = (
         WriteStorage<'s, Transform>,
-        ReadStorage<'s, Ball>,
+        WriteStorage<'s, Ball>, // mutable: spin curves the velocity and fades away
         Read<'s, Time>,
+        Entities<'s>,
+        Option<Read<'s, PowerUps>>, // speeds up/slows down and holds balls
+        ReadExpect<'s, GameplayConfig>,
+        Read<'s, GameMode>,
+        Option<Write<'s, ArenaPhysics>>, // gravity, wind and drag
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut transform_storage, ball_storage, time) = data;
+        let (
+            mut transform_storage,
+            mut ball_storage,
+            time,
+            entities,
+            power_ups,
+            gameplay_config,
+            mode,
+            mut physics,
+        ) = data;
+
+        let speed_factor = power_ups
+            .as_ref()
+            .map_or(1.0, |power_ups| power_ups.ball_speed_factor());
+        let spin_config = &gameplay_config.spin;
+        let spin_enabled = spin_config.is_enabled(*mode);
+        let elapsed = physics.as_ref().map_or(0.0, |physics| physics.elapsed);
 
         // traverses the storages to get components (state) to add behavior to it!
-        for (transform, ball) in (&mut transform_storage, &ball_storage).join() {
+        for (entity, transform, ball) in
+            (&entities, &mut transform_storage, &mut ball_storage).join()
+        {
+            // held by a sticky paddle, which moves it
+            if let Some(power_ups) = power_ups.as_ref() {
+                if power_ups.is_stuck(entity) {
+                    continue;
+                }
+            }
+
             // increment the ball's velocity to each translation (position) vector
 
             // game_velocity (m/s) * delta_time (s/frame) = velocity_per_frame m/frame
             // velocity_per_frame (m/frame) * fps (frame/s) = m/s -> game velocity is independent from the framerate
-            transform.prepend_translation_x(ball.velocity[0] * time.delta_seconds());
-            transform.prepend_translation_y(ball.velocity[1] * time.delta_seconds());
+            let delta_seconds = time.delta_seconds() * speed_factor;
+
+            // spinning balls curve (Magnus effect) and the spin fades away
+            if spin_enabled && ball.spin != 0.0 {
+                ball.velocity =
+                    curve_velocity(ball.velocity, ball.spin * spin_config.curve * delta_seconds);
+                ball.spin *= (-spin_config.damping * delta_seconds).exp();
+                transform.rotate_2d(ball.spin * delta_seconds);
+            }
+
+            // arena physics: the forces change the velocity before it moves the ball
+            if let Some(physics) = physics.as_ref() {
+                ball.velocity = physics.step_velocity(ball.velocity, elapsed, delta_seconds);
+            }
+
+            // air hockey: the puck slides, slowing down
+            if *mode == GameMode::AirHockey {
+                let friction = gameplay_config.air_hockey.friction.clamp(0.0, 1.0);
+                let factor = (1.0 - friction).powf(delta_seconds);
+
+                ball.velocity = [ball.velocity[0] * factor, ball.velocity[1] * factor];
+            }
+
+            transform.prepend_translation_x(ball.velocity[0] * delta_seconds);
+            transform.prepend_translation_y(ball.velocity[1] * delta_seconds);
+        }
+
+        if let Some(physics) = physics.as_mut() {
+            physics.elapsed += time.delta_seconds();
         }
     }
 }
+
+/// Velocity turned by the given angle (counterclockwise, in radians): the ball curves without
+/// speeding up or slowing down.
+fn curve_velocity(velocity: [f32; 2], angle: f32) -> [f32; 2] {
+    let (sin, cos) = angle.sin_cos();
+
+    [
+        velocity[0] * cos - velocity[1] * sin,
+        velocity[0] * sin + velocity[1] * cos,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_curve_ball_without_changing_its_speed() {
+        // arrange - ball heading right with counterclockwise spin
+        let velocity = [75.0, 0.0];
+
+        // act
+        let curved = curve_velocity(velocity, 0.1);
+
+        // assert
+        let speed = (curved[0] * curved[0] + curved[1] * curved[1]).sqrt();
+        assert!(curved[1] > 0.0); // curves upwards
+        assert!((speed - 75.0).abs() < 0.001);
+    }
+}
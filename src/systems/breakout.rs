@@ -0,0 +1,357 @@
+use amethyst::{
+    core::Transform,
+    derive::SystemDesc,
+    ecs::{Entities, Join, Read, ReadExpect, ReadStorage, System, SystemData, Write, WriteStorage},
+    renderer::{resources::Tint, SpriteRender},
+};
+use log::info;
+use rand::Rng;
+
+use crate::{
+    entities::{
+        pick_rule, spawn_pickup, Ball, Brick, PowerUp, PowerUps, Scoreboard, CRACKED_BRICK_SPRITE,
+    },
+    rng::{GameRng, RngStream},
+    settings::{GameMode, GameplayConfig},
+};
+
+/// Removes the broken bricks of breakout matches, awarding their points to the player who
+/// broke them and, once in a while, dropping a power-up in their place. Ends the match once
+/// the bricks are cleared or a player reaches the target points. Does nothing in other
+/// matches.
+#[derive(SystemDesc)]
+pub struct BreakoutSystem;
+
+impl<'s> System<'s> for BreakoutSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Brick>,
+        ReadStorage<'s, Ball>,
+        WriteStorage<'s, PowerUp>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, Tint>,
+        WriteStorage<'s, SpriteRender>,
+        Write<'s, Scoreboard>,
+        Option<Read<'s, PowerUps>>,
+        Write<'s, GameRng>,
+        ReadExpect<'s, GameplayConfig>,
+        Read<'s, GameMode>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            brick_storage,
+            ball_storage,
+            mut power_up_storage,
+            mut transform_storage,
+            mut tint_storage,
+            mut sprite_render_storage,
+            mut scoreboard,
+            power_ups,
+            mut game_rng,
+            gameplay_config,
+            mode,
+        ) = data;
+
+        if *mode != GameMode::Breakout || scoreboard.is_match_over(*mode) {
+            return;
+        }
+
+        let config = &gameplay_config.breakout;
+        let rng = game_rng.stream(RngStream::BrickDrops);
+        let mut broken = Vec::new();
+
+        for (entity, brick, transform) in (&entities, &brick_storage, &transform_storage).join() {
+            if brick.hit_points == 0 {
+                let position = [transform.translation().x, transform.translation().y];
+
+                broken.push((entity, position, brick.last_hit_by));
+            } else if brick.hit_points < brick.max_hit_points {
+                if let Some(sprite_render) = sprite_render_storage.get_mut(entity) {
+                    sprite_render.sprite_number = CRACKED_BRICK_SPRITE;
+                }
+            }
+        }
+
+        for (entity, position, last_hit_by) in broken {
+            entities
+                .delete(entity)
+                .expect("brick entity should be alive");
+
+            // bricks broken by a ball nobody hit since the serve are worth nothing
+            if let Some(side) = last_hit_by {
+                scoreboard.add_points(side, config.points_per_brick);
+            }
+
+            let power_ups = match power_ups.as_ref() {
+                Some(power_ups) => power_ups,
+                None => continue,
+            };
+
+            let power_up_config = &gameplay_config.power_ups;
+            let total_weight = power_up_config
+                .rules
+                .iter()
+                .map(|rule| rule.weight)
+                .sum::<u32>();
+
+            if total_weight == 0 || rng.gen::<f32>() >= config.drop_chance {
+                continue;
+            }
+
+            if let Some(rule) = pick_rule(power_up_config, rng.gen_range(0, total_weight)) {
+                spawn_pickup(
+                    &entities,
+                    (
+                        &mut power_up_storage,
+                        &mut transform_storage,
+                        &mut tint_storage,
+                        &mut sprite_render_storage,
+                    ),
+                    rule.kind,
+                    position,
+                    power_ups.sprite_sheet_handle(),
+                );
+            }
+        }
+
+        let bricks_left = (&entities, &brick_storage)
+            .join()
+            .filter(|(entity, _)| entities.is_alive(*entity))
+            .count();
+        let target_reached = config.target_points.map_or(false, |target| {
+            mode.sides()
+                .iter()
+                .any(|side| scoreboard.score(*side) >= target)
+        });
+
+        if bricks_left > 0 && !target_reached {
+            return;
+        }
+
+        match scoreboard.leader() {
+            Some(winner) => info!("{} wins the breakout match", winner.player_label()),
+            None => info!("The breakout match ended in a draw"),
+        }
+        scoreboard.finish();
+
+        // no more serves
+        for (entity, _) in (&entities, &ball_storage).join() {
+            entities
+                .delete(entity)
+                .expect("ball entity should be alive");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amethyst::{
+        assets::{AssetStorage, Loader},
+        ecs::{Builder, Entity, RunNow, World, WorldExt},
+        renderer::{
+            loaders::load_from_srgba, palette::Srgba, types::TextureData, SpriteSheet, Texture,
+        },
+    };
+    use rayon::ThreadPoolBuilder;
+    use std::sync::Arc;
+
+    use crate::entities::Side;
+
+    const POINTS_PER_BRICK: u32 = 2;
+
+    fn breakout_world(target_points: Option<u32>) -> World {
+        let mut world = World::new();
+
+        world.register::<Brick>();
+        world.register::<Ball>();
+        world.register::<PowerUp>();
+        world.register::<Transform>();
+        world.register::<Tint>();
+        world.register::<SpriteRender>();
+
+        let mut gameplay_config = GameplayConfig::default();
+        gameplay_config.breakout.points_per_brick = POINTS_PER_BRICK;
+        gameplay_config.breakout.target_points = target_points;
+
+        world.insert(gameplay_config);
+        world.insert(GameMode::Breakout);
+        world.insert(Scoreboard::default());
+        world.insert(GameRng::new(42));
+
+        world
+    }
+
+    fn add_brick(world: &mut World, hit_points: u32, last_hit_by: Option<Side>) -> Entity {
+        world
+            .create_entity()
+            .with(Brick {
+                hit_points,
+                max_hit_points: 2,
+                width: 4.0,
+                height: 10.0,
+                last_hit_by,
+            })
+            .with(Transform::default())
+            .build()
+    }
+
+    fn add_ball(world: &mut World) -> Entity {
+        world
+            .create_entity()
+            .with(Ball::new([75.0, 50.0], 2.0))
+            .with(Transform::default())
+            .build()
+    }
+
+    /// Sprite of an uncracked brick, from a sprite sheet nobody draws.
+    fn brick_sprite() -> SpriteRender {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .expect("thread pool should build");
+        let loader = Loader::new(".", Arc::new(pool));
+        let texture = loader.load_from_data(
+            TextureData(load_from_srgba(Srgba::new(1.0, 1.0, 1.0, 1.0))),
+            (),
+            &AssetStorage::<Texture>::new(),
+        );
+        let sprite_sheet = loader.load_from_data(
+            SpriteSheet {
+                texture,
+                sprites: Vec::new(),
+            },
+            (),
+            &AssetStorage::<SpriteSheet>::new(),
+        );
+
+        SpriteRender {
+            sprite_sheet,
+            sprite_number: 0,
+        }
+    }
+
+    fn run_breakout_system(world: &mut World) {
+        BreakoutSystem.run_now(world);
+        world.maintain();
+    }
+
+    #[test]
+    fn should_award_broken_brick_to_player_who_broke_it() {
+        // arrange
+        let mut world = breakout_world(None);
+        let broken = add_brick(&mut world, 0, Some(Side::Left));
+        let intact = add_brick(&mut world, 2, None);
+
+        // act
+        run_breakout_system(&mut world);
+
+        // assert
+        let scoreboard = world.read_resource::<Scoreboard>();
+
+        assert_eq!(POINTS_PER_BRICK, scoreboard.score(Side::Left));
+        assert_eq!(0, scoreboard.score(Side::Right));
+        assert_eq!(false, world.is_alive(broken));
+        assert_eq!(true, world.is_alive(intact));
+        assert_eq!(false, scoreboard.is_finished());
+    }
+
+    #[test]
+    fn should_crack_damaged_brick() {
+        // arrange
+        let mut world = breakout_world(None);
+        let damaged = add_brick(&mut world, 1, Some(Side::Right));
+        world
+            .write_storage::<SpriteRender>()
+            .insert(damaged, brick_sprite())
+            .expect("brick entity should be alive");
+
+        // act
+        run_breakout_system(&mut world);
+
+        // assert
+        let sprite_renders = world.read_storage::<SpriteRender>();
+
+        assert_eq!(true, world.is_alive(damaged));
+        assert_eq!(
+            Some(CRACKED_BRICK_SPRITE),
+            sprite_renders
+                .get(damaged)
+                .map(|sprite| sprite.sprite_number)
+        );
+        assert_eq!(0, world.read_resource::<Scoreboard>().score(Side::Right));
+    }
+
+    #[test]
+    fn should_award_nothing_for_brick_broken_before_any_hit() {
+        // arrange
+        let mut world = breakout_world(None);
+        let broken = add_brick(&mut world, 0, None);
+        add_brick(&mut world, 2, None);
+
+        // act
+        run_breakout_system(&mut world);
+
+        // assert
+        let scoreboard = world.read_resource::<Scoreboard>();
+
+        assert_eq!(false, world.is_alive(broken));
+        assert_eq!(0, scoreboard.score(Side::Left));
+        assert_eq!(0, scoreboard.score(Side::Right));
+    }
+
+    #[test]
+    fn should_end_match_once_bricks_run_out() {
+        // arrange
+        let mut world = breakout_world(None);
+        add_brick(&mut world, 0, Some(Side::Right));
+        let ball = add_ball(&mut world);
+
+        // act
+        run_breakout_system(&mut world);
+
+        // assert
+        let scoreboard = world.read_resource::<Scoreboard>();
+
+        assert_eq!(true, scoreboard.is_finished());
+        assert_eq!(Some(Side::Right), scoreboard.leader());
+        assert_eq!(false, world.is_alive(ball)); // no more serves
+    }
+
+    #[test]
+    fn should_end_match_once_target_points_are_reached() {
+        // arrange - the target is reached with bricks still standing
+        let mut world = breakout_world(Some(POINTS_PER_BRICK));
+        add_brick(&mut world, 0, Some(Side::Left));
+        let intact = add_brick(&mut world, 2, None);
+        let ball = add_ball(&mut world);
+
+        // act
+        run_breakout_system(&mut world);
+
+        // assert
+        let scoreboard = world.read_resource::<Scoreboard>();
+
+        assert_eq!(true, scoreboard.is_finished());
+        assert_eq!(Some(Side::Left), scoreboard.leader());
+        assert_eq!(true, world.is_alive(intact));
+        assert_eq!(false, world.is_alive(ball));
+    }
+
+    #[test]
+    fn should_leave_other_matches_alone() {
+        // arrange
+        let mut world = breakout_world(None);
+        world.insert(GameMode::Classic);
+        let broken = add_brick(&mut world, 0, Some(Side::Left));
+
+        // act
+        run_breakout_system(&mut world);
+
+        // assert
+        assert_eq!(true, world.is_alive(broken));
+        assert_eq!(false, world.read_resource::<Scoreboard>().is_finished());
+    }
+}
@@ -2,66 +2,286 @@ use amethyst::{
     assets::AssetStorage,
     audio::{output::Output, Source},
     core::Transform,
-    ecs::{Join, ReadStorage, WriteStorage},
-    shred::{Read, ReadExpect, System},
+    ecs::{Entities, Join, ReadStorage, WriteStorage},
+    shred::{Read, ReadExpect, System, Write},
 };
 
 use crate::{
     audio::{play_bounce_sfx, Sounds},
-    entities::{Ball, Paddle, Side},
-    settings::ARENA_HEIGHT,
+    entities::{
+        ring_angle, speed_up, Ball, Brick, MatchStats, MultiBall, Obstacle, ObstacleShape, Paddle,
+        PowerUpKind, PowerUps, Scoreboard, Side, Squash, StuckBall, STICKY_HOLD_SECONDS,
+    },
+    settings::{GameMode, GameplayConfig, Level, ARENA_HEIGHT, ARENA_WIDTH},
 };
 
 pub struct CollisionSystem;
 
 impl<'s> System<'s> for CollisionSystem {
     type SystemData = (
+        Entities<'s>,
         ReadStorage<'s, Transform>,
         ReadStorage<'s, Paddle>,
         WriteStorage<'s, Ball>, // mutable: gonna update it's velocity vector
         Read<'s, AssetStorage<Source>>,
-        ReadExpect<'s, Sounds>,
+        Option<ReadExpect<'s, Sounds>>, // no sounds when running headless
         Option<Read<'s, Output>>,
+        Read<'s, Scoreboard>,
+        Read<'s, GameMode>,
+        Option<Write<'s, MultiBall>>, // counts the hits in multi-ball matches
+        ReadExpect<'s, GameplayConfig>,
+        Option<Write<'s, PowerUps>>, // shields and sticky paddles
+        ReadStorage<'s, Obstacle>,
+        Option<Read<'s, Level>>,       // narrowed goals
+        Option<Write<'s, Squash>>,     // counts the returns in squash matches
+        WriteStorage<'s, Brick>,       // loses a hit point per bounce
+        Option<Write<'s, MatchStats>>, // counts the hits of the rallies
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
+            entities,
             transform_storage,
             paddle_storage,
             mut ball_storage,
             audio_storage,
             sounds,
             audio_output,
+            scoreboard,
+            mode,
+            mut multi_ball,
+            gameplay_config,
+            mut power_ups,
+            obstacle_storage,
+            level,
+            mut squash,
+            mut brick_storage,
+            mut match_stats,
         ) = data;
 
+        let spin_config = &gameplay_config.spin;
+        let spin_enabled = spin_config.is_enabled(*mode);
+
+        // edges without a goal (e.g. the top and bottom in classic matches) are walls, and so
+        // are the shielded goals
+        let goals = match power_ups.as_ref() {
+            Some(power_ups) => power_ups.open_goals(scoreboard.goals(*mode)),
+            None => scoreboard.goals(*mode),
+        };
+
         // ball and its transforms
-        for (ball, transform) in (&mut ball_storage, &transform_storage).join() {
+        for (ball_entity, ball, transform) in
+            (&entities, &mut ball_storage, &transform_storage).join()
+        {
             let ball_x = transform.translation().x;
             let ball_y = transform.translation().y;
-            let ball_velocity_y = ball.velocity[1];
 
-            if has_top_or_bottom_collision(ball_y, ball_velocity_y, ball.radius) {
-                ball.velocity[1] *= -1.0;
+            for edge in Side::ALL.iter() {
+                // goals are walls beside their opening, when narrowed by the level
+                let is_wall = !goals.contains(edge)
+                    || level.as_ref().map_or(false, |level| {
+                        !level.is_within_goal(*edge, [ball_x, ball_y])
+                    });
+
+                if is_wall
+                    && has_wall_collision(*edge, [ball_x, ball_y], ball.velocity, ball.radius)
+                {
+                    ball.velocity[edge.axis()] *= -1.0;
+
+                    // spinning balls grip the wall: they kick sideways and lose some spin
+                    if spin_enabled {
+                        let (velocity, spin) = wall_spin_bounce(
+                            *edge,
+                            ball.velocity,
+                            ball.spin,
+                            ball.radius,
+                            spin_config.wall_grip,
+                        );
+
+                        ball.velocity = velocity;
+                        ball.spin = spin;
+                    }
+                }
             }
 
             // traverses all paddles/transforms to check for collisions
-            for (paddle, transform) in (&paddle_storage, &transform_storage).join() {
+            for (paddle_entity, paddle, transform) in
+                (&entities, &paddle_storage, &transform_storage).join()
+            {
                 let paddle_x = transform.translation().x;
                 let paddle_y = transform.translation().y;
+                let axis = paddle.side.axis();
 
-                if has_paddle_collision(
-                    ball_x,
-                    ball_y,
-                    paddle_x,
-                    paddle_y,
-                    paddle.width,
-                    paddle.height,
-                ) {
-                    if (paddle.side == Side::Left && ball.velocity[0] < 0.0)
-                        || (paddle.side == Side::Right && ball.velocity[0] > 0.0)
+                // bounced velocity of the ball and spin given to it (per unit of transfer)
+                let hit = match (paddle.arc, paddle.mallet) {
+                    // air-hockey mallets are round and move in any direction
+                    (_, Some(mallet)) => mallet_bounce(
+                        mallet.radius,
+                        [paddle_x, paddle_y],
+                        paddle.velocity_2d(),
+                        [ball_x, ball_y],
+                        ball.velocity,
+                        ball.radius,
+                        gameplay_config.air_hockey.max_puck_speed,
+                    )
+                    .map(|velocity| (velocity, 0.0)),
+                    // ring paddles lie along their arc, facing the arena's center
+                    (Some(_), None) => arc_paddle_bounce(
+                        paddle,
+                        [paddle_x, paddle_y],
+                        [ball_x, ball_y],
+                        ball.velocity,
+                        ball.radius,
+                    )
+                    .map(|velocity| (velocity, paddle.velocity)),
+                    // only bounces the ball heading towards the paddle's goal
+                    (None, None)
+                        if has_paddle_collision(
+                            ball_x,
+                            ball_y,
+                            paddle_x,
+                            paddle_y,
+                            paddle.width,
+                            paddle.height,
+                        ) && ball.velocity[axis] * paddle.side.outwards() > 0.0 =>
                     {
-                        play_bounce_sfx(&sounds, &audio_storage, audio_output.as_deref());
-                        ball.velocity[0] *= -1.0;
+                        let mut velocity = ball.velocity;
+                        velocity[axis] *= -1.0;
+
+                        Some((velocity, paddle_spin(paddle.side, paddle.velocity)))
+                    }
+                    (None, None) => None,
+                };
+
+                if let Some((velocity, spin)) = hit {
+                    if let Some(sounds) = sounds.as_ref() {
+                        play_bounce_sfx(sounds, &audio_storage, audio_output.as_deref());
+                    }
+                    ball.velocity = velocity;
+                    ball.last_hit_by = Some(paddle.side);
+
+                    // moving paddles brush the ball, making it spin
+                    if spin_enabled {
+                        let spin = ball.spin + spin_config.transfer * spin;
+
+                        ball.spin = spin.max(-spin_config.max_spin).min(spin_config.max_spin);
+                    }
+
+                    if let Some(multi_ball) = multi_ball.as_mut() {
+                        multi_ball.count_hit();
+                    }
+
+                    if let Some(match_stats) = match_stats.as_mut() {
+                        match_stats.count_hit();
+                    }
+
+                    // squash: every return makes the ball a bit faster
+                    if let Some(squash) = squash.as_mut() {
+                        let config = &gameplay_config.squash;
+
+                        squash.count_return();
+                        ball.velocity = speed_up(ball.velocity, config.speed_up, config.max_speed);
+                    }
+
+                    // sticky paddles hold the ball for a moment before sending it back
+                    if let Some(power_ups) = power_ups.as_mut() {
+                        if power_ups.has_effect(PowerUpKind::StickyPaddle, paddle.side)
+                            && !power_ups.is_stuck(ball_entity)
+                        {
+                            let along_edge = 1 - axis;
+                            let paddle_position = [paddle_x, paddle_y];
+
+                            power_ups.stuck_balls.push(StuckBall {
+                                ball: ball_entity,
+                                paddle: paddle_entity,
+                                offset: [ball_x, ball_y][along_edge] - paddle_position[along_edge],
+                                remaining: STICKY_HOLD_SECONDS,
+                            });
+                        }
+                    }
+                }
+            }
+
+            // the level's obstacles
+            for (obstacle, transform) in (&obstacle_storage, &transform_storage).join() {
+                let obstacle_position = [transform.translation().x, transform.translation().y];
+
+                let bounce = obstacle_normal(
+                    obstacle.shape,
+                    obstacle_position,
+                    [ball_x, ball_y],
+                    ball.radius,
+                )
+                .and_then(|normal| reflect(ball.velocity, normal, obstacle.velocity));
+
+                if let Some(velocity) = bounce {
+                    if let Some(sounds) = sounds.as_ref() {
+                        play_bounce_sfx(sounds, &audio_storage, audio_output.as_deref());
+                    }
+                    ball.velocity = velocity;
+                }
+            }
+
+            // breakout bricks: bounces off at most one of them per frame
+            for (brick, transform) in (&mut brick_storage, &transform_storage).join() {
+                if brick.hit_points == 0 {
+                    continue; // broken, about to be removed by the BreakoutSystem
+                }
+
+                let shape = ObstacleShape::Rectangle {
+                    width: brick.width,
+                    height: brick.height,
+                };
+                let brick_position = [transform.translation().x, transform.translation().y];
+
+                let bounce = obstacle_normal(shape, brick_position, [ball_x, ball_y], ball.radius)
+                    .and_then(|normal| reflect(ball.velocity, normal, [0.0, 0.0]));
+
+                if let Some(velocity) = bounce {
+                    if let Some(sounds) = sounds.as_ref() {
+                        play_bounce_sfx(sounds, &audio_storage, audio_output.as_deref());
+                    }
+                    ball.velocity = velocity;
+                    brick.hit_points -= 1;
+                    brick.last_hit_by = ball.last_hit_by;
+                    break;
+                }
+            }
+        }
+
+        if multi_ball.is_none() || !gameplay_config.multi_ball.ball_collisions {
+            return;
+        }
+
+        // balls bouncing off each other (multi-ball only)
+        let balls = (&entities, &ball_storage, &transform_storage)
+            .join()
+            .map(|(entity, ball, transform)| {
+                let position = [transform.translation().x, transform.translation().y];
+
+                (entity, position, ball.radius)
+            })
+            .collect::<Vec<_>>();
+
+        for (index, (entity_a, position_a, radius_a)) in balls.iter().enumerate() {
+            for (entity_b, position_b, radius_b) in balls.iter().skip(index + 1) {
+                let velocity_a = ball_storage.get(*entity_a).map(|ball| ball.velocity);
+                let velocity_b = ball_storage.get(*entity_b).map(|ball| ball.velocity);
+
+                if let (Some(velocity_a), Some(velocity_b)) = (velocity_a, velocity_b) {
+                    let bounce = ball_bounce(
+                        [*position_a, *position_b],
+                        [velocity_a, velocity_b],
+                        radius_a + radius_b,
+                    );
+
+                    if let Some([bounced_a, bounced_b]) = bounce {
+                        if let Some(ball) = ball_storage.get_mut(*entity_a) {
+                            ball.velocity = bounced_a;
+                        }
+                        if let Some(ball) = ball_storage.get_mut(*entity_b) {
+                            ball.velocity = bounced_b;
+                        }
                     }
                 }
             }
@@ -69,9 +289,240 @@ impl<'s> System<'s> for CollisionSystem {
     }
 }
 
-fn has_top_or_bottom_collision(ball_y: f32, ball_velocity_y: f32, ball_radius: f32) -> bool {
-    (ball_y < ball_radius && ball_velocity_y < 0.0)
-        || (ball_y > ARENA_HEIGHT - ball_radius && ball_velocity_y > 0.0)
+/// Unit normal of the obstacle's surface at the point touched by the ball, pointing towards the
+/// ball, or None when the ball doesn't touch the obstacle.
+fn obstacle_normal(
+    shape: ObstacleShape,
+    obstacle_position: [f32; 2],
+    ball_position: [f32; 2],
+    ball_radius: f32,
+) -> Option<[f32; 2]> {
+    let offset = [
+        ball_position[0] - obstacle_position[0],
+        ball_position[1] - obstacle_position[1],
+    ];
+
+    match shape {
+        ObstacleShape::Circle { radius } => {
+            let distance = (offset[0] * offset[0] + offset[1] * offset[1]).sqrt();
+
+            if distance > radius + ball_radius || distance == 0.0 {
+                return None;
+            }
+
+            Some([offset[0] / distance, offset[1] / distance])
+        }
+        ObstacleShape::Rectangle { width, height } => {
+            let half_size = [width * 0.5, height * 0.5];
+
+            // point of the rectangle closest to the ball's center
+            let closest = [
+                offset[0].clamp(-half_size[0], half_size[0]),
+                offset[1].clamp(-half_size[1], half_size[1]),
+            ];
+            let outside = [offset[0] - closest[0], offset[1] - closest[1]];
+            let distance = (outside[0] * outside[0] + outside[1] * outside[1]).sqrt();
+
+            if distance > ball_radius {
+                return None;
+            }
+
+            if distance > 0.0 {
+                return Some([outside[0] / distance, outside[1] / distance]);
+            }
+
+            // the ball's center got inside: pushed out through the nearest side
+            let depth = [
+                half_size[0] - offset[0].abs(),
+                half_size[1] - offset[1].abs(),
+            ];
+            let axis = if depth[0] < depth[1] { 0 } else { 1 };
+
+            let mut normal = [0.0, 0.0];
+            normal[axis] = if offset[axis] < 0.0 { -1.0 } else { 1.0 };
+
+            Some(normal)
+        }
+    }
+}
+
+/// Velocity of a ball bouncing off a surface (moving at the given velocity) with the given
+/// normal, or None when the ball is already moving away from it.
+fn reflect(velocity: [f32; 2], normal: [f32; 2], surface_velocity: [f32; 2]) -> Option<[f32; 2]> {
+    let relative = [
+        velocity[0] - surface_velocity[0],
+        velocity[1] - surface_velocity[1],
+    ];
+    let approach = relative[0] * normal[0] + relative[1] * normal[1];
+
+    if approach >= 0.0 {
+        return None;
+    }
+
+    Some([
+        velocity[0] - 2.0 * approach * normal[0],
+        velocity[1] - 2.0 * approach * normal[1],
+    ])
+}
+
+/// Velocity of a ball bouncing off a ring paddle, or None when it doesn't touch it or is already
+/// moving away from it. The paddle is a rectangle turned to lie along its arc, moving along it.
+fn arc_paddle_bounce(
+    paddle: &Paddle,
+    paddle_position: [f32; 2],
+    ball_position: [f32; 2],
+    ball_velocity: [f32; 2],
+    ball_radius: f32,
+) -> Option<[f32; 2]> {
+    let (sin, cos) = ring_angle(paddle_position).sin_cos();
+    let offset = [
+        ball_position[0] - paddle_position[0],
+        ball_position[1] - paddle_position[1],
+    ];
+
+    // in the paddle's frame: x towards the boundary, y along the arc (counterclockwise)
+    let local = [
+        offset[0] * cos + offset[1] * sin,
+        offset[1] * cos - offset[0] * sin,
+    ];
+    let shape = ObstacleShape::Rectangle {
+        width: paddle.width,
+        height: paddle.height,
+    };
+
+    let [normal_x, normal_y] = obstacle_normal(shape, [0.0, 0.0], local, ball_radius)?;
+    let normal = [
+        normal_x * cos - normal_y * sin,
+        normal_x * sin + normal_y * cos,
+    ];
+    let surface_velocity = [-sin * paddle.velocity, cos * paddle.velocity];
+
+    reflect(ball_velocity, normal, surface_velocity)
+}
+
+/// Velocity of a puck struck by an air-hockey mallet, or None when they don't touch or the puck
+/// is already moving away from it. The mallet is far heavier: the puck bounces off it as off a
+/// moving wall, taking the mallet's momentum along, up to the given max speed.
+fn mallet_bounce(
+    mallet_radius: f32,
+    mallet_position: [f32; 2],
+    mallet_velocity: [f32; 2],
+    ball_position: [f32; 2],
+    ball_velocity: [f32; 2],
+    ball_radius: f32,
+    max_speed: f32,
+) -> Option<[f32; 2]> {
+    let shape = ObstacleShape::Circle {
+        radius: mallet_radius,
+    };
+
+    let normal = obstacle_normal(shape, mallet_position, ball_position, ball_radius)?;
+    let velocity = reflect(ball_velocity, normal, mallet_velocity)?;
+    let speed = velocity[0].hypot(velocity[1]);
+
+    if speed > max_speed {
+        Some([
+            velocity[0] * max_speed / speed,
+            velocity[1] * max_speed / speed,
+        ])
+    } else {
+        Some(velocity)
+    }
+}
+
+/// Spin (counterclockwise) given to a ball by a paddle moving along its edge at the given
+/// velocity, per unit of transfer: the ball's side touching the paddle is dragged along.
+fn paddle_spin(side: Side, paddle_velocity: f32) -> f32 {
+    let mut outwards = [0.0, 0.0];
+    let mut velocity = [0.0, 0.0];
+
+    outwards[side.axis()] = side.outwards();
+    velocity[1 - side.axis()] = paddle_velocity;
+
+    // cross product of the contact point's direction and the paddle's velocity
+    outwards[0] * velocity[1] - outwards[1] * velocity[0]
+}
+
+/// Velocity and spin of a spinning ball that just bounced off the given wall: friction turns
+/// part of the spin into speed along the wall, against the way the ball's surface slides.
+fn wall_spin_bounce(
+    wall: Side,
+    velocity: [f32; 2],
+    spin: f32,
+    radius: f32,
+    grip: f32,
+) -> ([f32; 2], f32) {
+    let axis = wall.axis();
+    let along_wall = 1 - axis;
+
+    // velocity of the ball's surface where it touches the wall
+    let mut contact = [0.0, 0.0];
+    contact[axis] = wall.outwards() * radius;
+    let surface_velocity = [-spin * contact[1], spin * contact[0]];
+
+    let mut bounced = velocity;
+    bounced[along_wall] -= grip * surface_velocity[along_wall];
+
+    (bounced, spin * (1.0 - grip))
+}
+
+/// Velocities of two balls (of the same mass) after bouncing off each other, or None when they
+/// don't touch or are already moving apart. The velocity along the line between their centers
+/// is exchanged, as in an elastic collision.
+fn ball_bounce(
+    positions: [[f32; 2]; 2],
+    velocities: [[f32; 2]; 2],
+    touch_distance: f32,
+) -> Option<[[f32; 2]; 2]> {
+    let [position_a, position_b] = positions;
+    let [velocity_a, velocity_b] = velocities;
+
+    let normal = [position_b[0] - position_a[0], position_b[1] - position_a[1]];
+    let distance_squared = normal[0] * normal[0] + normal[1] * normal[1];
+
+    if distance_squared == 0.0 || distance_squared > touch_distance * touch_distance {
+        return None;
+    }
+
+    // how fast a is closing in on b, along the normal
+    let approach =
+        (velocity_a[0] - velocity_b[0]) * normal[0] + (velocity_a[1] - velocity_b[1]) * normal[1];
+
+    if approach <= 0.0 {
+        return None;
+    }
+
+    let exchanged = approach / distance_squared;
+
+    Some([
+        [
+            velocity_a[0] - exchanged * normal[0],
+            velocity_a[1] - exchanged * normal[1],
+        ],
+        [
+            velocity_b[0] + exchanged * normal[0],
+            velocity_b[1] + exchanged * normal[1],
+        ],
+    ])
+}
+
+/// Whether the ball reached the edge of the given side while heading out of the arena.
+fn has_wall_collision(
+    wall: Side,
+    ball_position: [f32; 2],
+    ball_velocity: [f32; 2],
+    ball_radius: f32,
+) -> bool {
+    let axis = wall.axis();
+    let arena_size = [ARENA_WIDTH, ARENA_HEIGHT][axis];
+
+    let beyond_edge = if wall.outwards() > 0.0 {
+        ball_position[axis] > arena_size - ball_radius
+    } else {
+        ball_position[axis] < ball_radius
+    };
+
+    beyond_edge && ball_velocity[axis] * wall.outwards() > 0.0
 }
 
 fn has_paddle_collision(
@@ -96,6 +547,7 @@ fn has_paddle_collision(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::entities::RingArc;
 
     #[test]
     fn should_assert_paddle_collision() {
@@ -174,4 +626,175 @@ mod tests {
         // assert
         assert_eq!(false, has_collision);
     }
+
+    #[test]
+    fn should_exchange_velocities_of_balls_colliding_head_on() {
+        // arrange - two balls touching, moving towards each other along x
+        let positions = [[48.0, 50.0], [51.0, 50.0]];
+        let velocities = [[75.0, 0.0], [-50.0, 0.0]];
+
+        // act
+        let bounce = ball_bounce(positions, velocities, 4.0);
+
+        // assert
+        assert_eq!(Some([[-50.0, 0.0], [75.0, 0.0]]), bounce);
+    }
+
+    #[test]
+    fn should_not_bounce_balls_moving_apart() {
+        // arrange - two overlapping balls that already bounced
+        let positions = [[48.0, 50.0], [51.0, 50.0]];
+        let velocities = [[-50.0, 0.0], [75.0, 0.0]];
+
+        // act
+        let bounce = ball_bounce(positions, velocities, 4.0);
+
+        // assert
+        assert_eq!(None, bounce);
+    }
+
+    #[test]
+    fn should_assert_wall_collision_on_left_edge() {
+        // arrange - ball heading out through the left edge (e.g. the left player is out)
+        let ball_position = [1.0, 50.0];
+        let ball_velocity = [-75.0, 50.0];
+
+        // act
+        let has_collision = has_wall_collision(Side::Left, ball_position, ball_velocity, 2.0);
+
+        // assert
+        assert_eq!(true, has_collision);
+    }
+
+    #[test]
+    fn should_assert_no_wall_collision_when_ball_moves_away() {
+        // arrange - ball already bounced off the top edge
+        let ball_position = [50.0, 99.0];
+        let ball_velocity = [75.0, -50.0];
+
+        // act
+        let has_collision = has_wall_collision(Side::Top, ball_position, ball_velocity, 2.0);
+
+        // assert
+        assert_eq!(false, has_collision);
+    }
+
+    #[test]
+    fn should_spin_ball_and_kick_it_off_the_wall() {
+        // arrange - left paddle moving up, ball then bouncing off the bottom wall
+        let spin = paddle_spin(Side::Left, 75.0) * 0.04;
+
+        // act
+        let (velocity, spin_after) = wall_spin_bounce(Side::Bottom, [75.0, 50.0], spin, 2.0, 0.5);
+
+        // assert
+        assert_eq!(-3.0, spin); // clockwise
+        assert_eq!([78.0, 50.0], velocity);
+        assert_eq!(-1.5, spin_after);
+    }
+
+    #[test]
+    fn should_bounce_ball_off_ring_paddle_facing_center() {
+        // arrange - ring paddle at the top of the arena, ball coming up underneath it
+        let paddle = Paddle {
+            arc: Some(RingArc {
+                seat: 1,
+                center: std::f32::consts::FRAC_PI_2,
+                radius: 44.0,
+                half_angle: 0.5,
+            }),
+            ..Paddle::new(Side::Left)
+        };
+
+        // act
+        let bounced = arc_paddle_bounce(&paddle, [50.0, 94.0], [50.0, 91.0], [0.0, 50.0], 2.0);
+        let missed = arc_paddle_bounce(&paddle, [50.0, 94.0], [70.0, 91.0], [0.0, 50.0], 2.0);
+
+        // assert
+        let [x, y] = bounced.unwrap();
+        assert!(x.abs() < 0.001);
+        assert!((y + 50.0).abs() < 0.001);
+        assert_eq!(None, missed);
+    }
+
+    #[test]
+    fn should_send_resting_puck_off_with_striking_mallet_momentum() {
+        // arrange - mallet moving right, touching a puck at rest
+        let mallet_position = [20.0, 50.0];
+        let puck_position = [26.0, 50.0];
+
+        // act
+        let struck = mallet_bounce(
+            5.0,
+            mallet_position,
+            [50.0, 0.0],
+            puck_position,
+            [0.0, 0.0],
+            2.0,
+            200.0,
+        );
+        let capped = mallet_bounce(
+            5.0,
+            mallet_position,
+            [50.0, 0.0],
+            puck_position,
+            [0.0, 0.0],
+            2.0,
+            80.0,
+        );
+
+        // assert
+        assert_eq!(Some([100.0, 0.0]), struck); // twice the mallet's velocity
+        assert_eq!(Some([80.0, 0.0]), capped);
+    }
+
+    #[test]
+    fn should_bounce_ball_off_rectangle_corner_diagonally() {
+        // arrange - ball touching the top right corner of a 10x10 rectangle
+        let shape = ObstacleShape::Rectangle {
+            width: 10.0,
+            height: 10.0,
+        };
+        let ball_position = [56.0, 56.0];
+        let ball_velocity = [-50.0, -50.0];
+
+        // act
+        let bounce = obstacle_normal(shape, [50.0, 50.0], ball_position, 2.0)
+            .and_then(|normal| reflect(ball_velocity, normal, [0.0, 0.0]));
+
+        // assert
+        let [velocity_x, velocity_y] = bounce.unwrap();
+        assert!((velocity_x - 50.0).abs() < 0.001);
+        assert!((velocity_y - 50.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn should_bounce_ball_off_circle_along_its_normal() {
+        // arrange - ball hitting the top of a circle while moving down and right
+        let shape = ObstacleShape::Circle { radius: 5.0 };
+        let ball_position = [50.0, 56.0];
+        let ball_velocity = [75.0, -50.0];
+
+        // act
+        let normal = obstacle_normal(shape, [50.0, 50.0], ball_position, 2.0);
+        let bounce = normal.and_then(|normal| reflect(ball_velocity, normal, [0.0, 0.0]));
+
+        // assert
+        assert_eq!(Some([0.0, 1.0]), normal);
+        assert_eq!(Some([75.0, 50.0]), bounce);
+    }
+
+    #[test]
+    fn should_not_bounce_ball_moving_away_from_obstacle() {
+        // arrange - ball above an obstacle moving up
+        let normal = [0.0, 1.0];
+
+        // act
+        let bounce = reflect([0.0, 30.0], normal, [0.0, 10.0]);
+        let caught_up = reflect([0.0, 5.0], normal, [0.0, 10.0]);
+
+        // assert
+        assert_eq!(None, bounce);
+        assert_eq!(Some([0.0, 15.0]), caught_up);
+    }
 }
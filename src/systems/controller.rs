@@ -0,0 +1,50 @@
+use amethyst::{
+    derive::SystemDesc,
+    ecs::{Read, System, SystemData, Write},
+    input::{InputEvent, StringBindings},
+    shrev::{EventChannel, ReaderId},
+};
+use log::info;
+
+use crate::controls::ControllerAssignments;
+
+/// Assigns controllers to players as they are plugged in and frees them when unplugged.
+#[derive(SystemDesc)]
+#[system_desc(name(ControllerSystemDesc))]
+pub struct ControllerSystem {
+    #[system_desc(event_channel_reader)]
+    reader_id: ReaderId<InputEvent<StringBindings>>,
+}
+
+impl ControllerSystem {
+    pub fn new(reader_id: ReaderId<InputEvent<StringBindings>>) -> Self {
+        ControllerSystem { reader_id }
+    }
+}
+
+impl<'s> System<'s> for ControllerSystem {
+    type SystemData = (
+        Read<'s, EventChannel<InputEvent<StringBindings>>>,
+        Write<'s, ControllerAssignments>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (input_events, mut assignments) = data;
+
+        for event in input_events.read(&mut self.reader_id) {
+            match event {
+                InputEvent::ControllerConnected { which } => {
+                    if let Some(side) = assignments.connect(*which) {
+                        info!("Controller {} assigned to the {:?} paddle", which, side);
+                    }
+                }
+                InputEvent::ControllerDisconnected { which } => {
+                    if let Some(side) = assignments.disconnect(*which) {
+                        info!("Controller {} of the {:?} paddle disconnected", which, side);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -1,9 +1,31 @@
 mod ball;
+mod breakout;
 mod collision;
+mod controller;
+mod multi_ball;
+mod obstacle;
 mod paddle;
+mod paddle_input;
+mod portal;
+mod power_up;
+mod practice;
+mod ring;
 mod score;
+mod score_text;
+mod trajectory;
 
 pub use self::ball::BallSystem;
+pub use self::breakout::BreakoutSystem;
 pub use self::collision::CollisionSystem;
-pub use self::paddle::PaddleSystem;
+pub use self::controller::ControllerSystemDesc;
+pub use self::multi_ball::MultiBallSystem;
+pub use self::obstacle::ObstacleSystem;
+pub use self::paddle::{move_paddle, PaddleSystem};
+pub use self::paddle_input::PaddleInputSystem;
+pub use self::portal::PortalSystem;
+pub use self::power_up::PowerUpSystem;
+pub use self::practice::PracticeSystem;
+pub use self::ring::RingSystem;
 pub use self::score::ScoreSystem;
+pub use self::score_text::ScoreTextSystem;
+pub use self::trajectory::TrajectorySystem;
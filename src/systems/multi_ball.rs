@@ -0,0 +1,75 @@
+use amethyst::{
+    core::{Time, Transform},
+    derive::SystemDesc,
+    ecs::{Entities, Join, Read, ReadExpect, System, SystemData, Write, WriteStorage},
+    renderer::SpriteRender,
+};
+
+use crate::{
+    entities::{serve_ball, Ball, MultiBall, Scoreboard},
+    settings::{GameMode, GameplayConfig},
+};
+
+/// Serves the balls of multi-ball matches from the center of the arena: a new one once every
+/// ball went into a goal and extra ones during rallies. Does nothing in other matches.
+#[derive(SystemDesc)]
+pub struct MultiBallSystem;
+
+impl<'s> System<'s> for MultiBallSystem {
+    type SystemData = (
+        Entities<'s>,
+        WriteStorage<'s, Ball>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, SpriteRender>,
+        Option<Write<'s, MultiBall>>,
+        ReadExpect<'s, GameplayConfig>,
+        Read<'s, Scoreboard>,
+        Read<'s, GameMode>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut ball_storage,
+            mut transform_storage,
+            mut sprite_render_storage,
+            multi_ball,
+            gameplay_config,
+            scoreboard,
+            mode,
+            time,
+        ) = data;
+
+        let mut multi_ball = match multi_ball {
+            Some(multi_ball) => multi_ball,
+            None => return,
+        };
+
+        // no more serves once the match is over
+        if scoreboard.is_match_over(*mode) {
+            return;
+        }
+
+        let ball_count = (&ball_storage).join().count();
+
+        if !multi_ball.should_serve(
+            ball_count,
+            &gameplay_config.multi_ball,
+            time.delta_seconds(),
+        ) {
+            return;
+        }
+
+        serve_ball(
+            &entities,
+            (
+                &mut ball_storage,
+                &mut transform_storage,
+                &mut sprite_render_storage,
+            ),
+            multi_ball.next_serve_velocity(),
+            multi_ball.sprite_sheet_handle(),
+        );
+    }
+}
@@ -0,0 +1,39 @@
+use amethyst::{
+    core::{Time, Transform},
+    derive::SystemDesc,
+    ecs::{Join, Read, System, SystemData, WriteStorage},
+};
+
+use crate::entities::Obstacle;
+
+/// Moves the level's moving obstacles along their path.
+#[derive(SystemDesc)]
+pub struct ObstacleSystem;
+
+impl<'s> System<'s> for ObstacleSystem {
+    type SystemData = (
+        WriteStorage<'s, Obstacle>,
+        WriteStorage<'s, Transform>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut obstacle_storage, mut transform_storage, time) = data;
+
+        for (obstacle, transform) in (&mut obstacle_storage, &mut transform_storage).join() {
+            let motion = match obstacle.motion.as_mut() {
+                Some(motion) => motion,
+                None => continue,
+            };
+
+            motion.elapsed += time.delta_seconds();
+
+            let ([x, y], velocity) = motion.state();
+
+            // the velocity is used to bounce the balls off the moving obstacle
+            obstacle.velocity = velocity;
+            transform.set_translation_x(x);
+            transform.set_translation_y(y);
+        }
+    }
+}
@@ -1,55 +1,396 @@
 use amethyst::{
-    core::Transform,
+    core::{Time, Transform},
     derive::SystemDesc,
-    ecs::{Join, Read, ReadStorage, System, SystemData, WriteStorage},
-    input::{InputHandler, StringBindings},
+    ecs::{Join, Read, ReadExpect, ReadStorage, System, SystemData, WriteStorage},
 };
 
 use crate::{
-    entities::{Paddle, Side},
-    settings::{ARENA_HEIGHT, PADDLE_HEIGHT},
+    controls::{
+        cpu_input, mallet_cpu_input, move_towards, ring_cpu_input, PaddleInput, PaddleInputs,
+    },
+    entities::{ArenaPhysics, Ball, Paddle, PaddleDriver, PowerUpKind, PowerUps, RingArc, Side},
+    settings::{ControlsConfig, GameplayConfig, PaddleConfig, ARENA_HEIGHT, ARENA_WIDTH},
 };
 
 #[derive(SystemDesc)]
 pub struct PaddleSystem;
 
 impl PaddleSystem {
-    /// Updates the paddle's position (translation's y position) by adding a movement amount to it.
-    fn update_position(&self, paddle_transform: &mut Transform, mv_amount: f32) {
-        let current_y = paddle_transform.translation().y;
-        let scaled_mv_amount = 1.2 * mv_amount;
-
-        // updates y between a given range
-        let updated_y = (current_y + scaled_mv_amount).clamp(
-            0.0 + PADDLE_HEIGHT * 0.5,
-            ARENA_HEIGHT - PADDLE_HEIGHT * 0.5,
+    /// Updates the paddle's velocity given the movement input (-1.0 to 1.0 of the max speed) and
+    /// moves the paddle along its edge (translation's y position, x for top/bottom paddles, or
+    /// its angular position for ring paddles) accordingly.
+    fn update_position(
+        &self,
+        paddle: &mut Paddle,
+        paddle_transform: &mut Transform,
+        mv_amount: f32,
+        config: &PaddleConfig,
+        delta_seconds: f32,
+    ) {
+        let current = position_along_edge(paddle, paddle_transform);
+        let velocity = step_velocity(paddle.velocity, mv_amount, config, delta_seconds);
+
+        // average of the velocities before/after the step: exact for a constant acceleration,
+        // so the distance travelled doesn't depend on the framerate
+        let updated = current + (paddle.velocity + velocity) * 0.5 * delta_seconds;
+
+        // keeps the position between a given range
+        let clamped = clamp_to_edge(paddle, updated);
+
+        // hitting the arena's edge stops the paddle
+        paddle.velocity = if clamped == updated { velocity } else { 0.0 };
+        set_position_along_edge(paddle, paddle_transform, clamped);
+    }
+
+    /// Moves the paddle towards a target position along its edge (e.g. the mouse cursor's y)
+    /// without exceeding the max step allowed for this frame.
+    fn follow_target(
+        &self,
+        paddle: &mut Paddle,
+        paddle_transform: &mut Transform,
+        target: f32,
+        max_step: f32,
+        delta_seconds: f32,
+    ) {
+        let current = position_along_edge(paddle, paddle_transform);
+
+        let updated = clamp_to_edge(paddle, move_towards(current, target, max_step));
+
+        if delta_seconds > 0.0 {
+            paddle.velocity = (updated - current) / delta_seconds;
+        }
+
+        set_position_along_edge(paddle, paddle_transform, updated);
+    }
+
+    /// Moves an air-hockey mallet across its edge (x for left/right mallets) given the input's
+    /// part across it, keeping the mallet within its half of the arena.
+    fn move_across(
+        &self,
+        paddle: &mut Paddle,
+        paddle_transform: &mut Transform,
+        input: PaddleInput,
+        config: &PaddleConfig,
+        max_step: f32,
+        delta_seconds: f32,
+    ) {
+        let mallet = match paddle.mallet.as_mut() {
+            Some(mallet) => mallet,
+            None => return,
+        };
+
+        let axis = paddle.side.axis();
+        let current = paddle_transform.translation()[axis];
+        let (min, max) = mallet_range(paddle.side, mallet.radius);
+
+        let (updated, velocity) = match input {
+            PaddleInput::Axis(mv_amount) => {
+                let velocity = step_velocity(mallet.velocity, mv_amount, config, delta_seconds);
+                let updated = current + (mallet.velocity + velocity) * 0.5 * delta_seconds;
+                let clamped = updated.clamp(min, max);
+
+                // hitting the arena's edge (or the middle line) stops the mallet
+                (clamped, if clamped == updated { velocity } else { 0.0 })
+            }
+            PaddleInput::Target(target) => {
+                let updated = move_towards(current, target, max_step).clamp(min, max);
+
+                if delta_seconds > 0.0 {
+                    (updated, (updated - current) / delta_seconds)
+                } else {
+                    (updated, mallet.velocity)
+                }
+            }
+            // split into their parts along and across the edge by move_paddle
+            PaddleInput::Stick(_) | PaddleInput::Point(_) => (current, mallet.velocity),
+        };
+
+        mallet.velocity = velocity;
+
+        if axis == 0 {
+            paddle_transform.set_translation_x(updated);
+        } else {
+            paddle_transform.set_translation_y(updated);
+        }
+    }
+}
+
+/// Moves a paddle for one step of the simulation given its player's input. Also used by the
+/// game server's clients to predict their own paddle.
+pub fn move_paddle(
+    paddle: &mut Paddle,
+    paddle_transform: &mut Transform,
+    input: PaddleInput,
+    controls_config: &ControlsConfig,
+    gameplay_config: &GameplayConfig,
+    delta_seconds: f32,
+) {
+    let (along, across) = split_input(input, paddle.side);
+
+    // handicapped paddles move slower (or faster), whatever drives them
+    let paddle_config = paddle.handicap.paddle_config(&gameplay_config.paddle);
+    let max_step = controls_config.mouse_max_speed * paddle.handicap.paddle_speed * delta_seconds;
+
+    match along {
+        PaddleInput::Axis(mv_amount) => PaddleSystem.update_position(
+            paddle,
+            paddle_transform,
+            mv_amount,
+            &paddle_config,
+            delta_seconds,
+        ),
+        PaddleInput::Target(target) => {
+            PaddleSystem.follow_target(paddle, paddle_transform, target, max_step, delta_seconds);
+        }
+        // split into their parts along and across the edge above
+        PaddleInput::Stick(_) | PaddleInput::Point(_) => {}
+    }
+
+    // mallets slow down across their edge without an input for it (e.g. from a netplay peer)
+    if paddle.mallet.is_some() {
+        PaddleSystem.move_across(
+            paddle,
+            paddle_transform,
+            across.unwrap_or_default(),
+            &paddle_config,
+            max_step,
+            delta_seconds,
         );
+    }
+}
+
+/// Input the CPU would give the paddle: following the balls along its edge, its arc (ring
+/// paddles) or anywhere in its half (air-hockey mallets).
+fn cpu_paddle_input(
+    paddle: &Paddle,
+    position: [f32; 2],
+    balls: &[([f32; 2], [f32; 2])],
+    physics: Option<&ArenaPhysics>,
+) -> PaddleInput {
+    match (paddle.arc, paddle.mallet) {
+        (Some(arc), _) => ring_cpu_input(&arc, position, balls, physics),
+        (None, Some(_)) => mallet_cpu_input(paddle.side, position, balls, physics),
+        (None, None) => cpu_input(paddle.side, position, balls, physics),
+    }
+}
+
+/// Player's input nudged towards the CPU's (assistance handicap) by the given strength. Mouse
+/// targets are followed as they are.
+fn assist_input(input: PaddleInput, nudge: PaddleInput, assist: f32) -> PaddleInput {
+    let nudged = |mv_amount: f32, nudge: f32| (mv_amount + assist * nudge).clamp(-1.0, 1.0);
+
+    match (input, nudge) {
+        (PaddleInput::Axis(mv_amount), PaddleInput::Axis(nudge)) => {
+            PaddleInput::Axis(nudged(mv_amount, nudge))
+        }
+        (PaddleInput::Stick([x, y]), PaddleInput::Stick([nudge_x, nudge_y])) => {
+            PaddleInput::Stick([nudged(x, nudge_x), nudged(y, nudge_y)])
+        }
+        _ => input,
+    }
+}
+
+/// Splits an input into its parts along and across the paddle's edge. Only the 2D inputs of
+/// air-hockey mallets have a part across it.
+fn split_input(input: PaddleInput, side: Side) -> (PaddleInput, Option<PaddleInput>) {
+    let goal_axis = side.axis();
+    let edge_axis = 1 - goal_axis;
+
+    match input {
+        PaddleInput::Stick(stick) => (
+            PaddleInput::Axis(stick[edge_axis]),
+            Some(PaddleInput::Axis(stick[goal_axis])),
+        ),
+        PaddleInput::Point(point) => (
+            PaddleInput::Target(point[edge_axis]),
+            Some(PaddleInput::Target(point[goal_axis])),
+        ),
+        PaddleInput::Axis(_) | PaddleInput::Target(_) => (input, None),
+    }
+}
+
+/// Input moving the paddle the other way around (reverse controls power-up): targets are
+/// mirrored around the paddle's current position.
+fn reverse_input(input: PaddleInput, paddle: &Paddle, paddle_transform: &Transform) -> PaddleInput {
+    let translation = paddle_transform.translation();
 
-        paddle_transform.set_translation_y(updated_y);
+    match input {
+        PaddleInput::Axis(mv_amount) => PaddleInput::Axis(-mv_amount),
+        PaddleInput::Target(target) => {
+            PaddleInput::Target(2.0 * position_along_edge(paddle, paddle_transform) - target)
+        }
+        PaddleInput::Stick([x, y]) => PaddleInput::Stick([-x, -y]),
+        PaddleInput::Point([x, y]) => {
+            PaddleInput::Point([2.0 * translation.x - x, 2.0 * translation.y - y])
+        }
     }
 }
 
+/// Input moving a ring paddle along its arc (counterclockwise) given its player's input, which
+/// moves it up (or right) on the screen. Targets are taken as offsets from the arena's middle.
+fn arc_input(input: PaddleInput, arc: &RingArc) -> PaddleInput {
+    let direction = arc.screen_direction();
+
+    match input {
+        PaddleInput::Axis(mv_amount) => PaddleInput::Axis(direction * mv_amount),
+        PaddleInput::Target(target) => {
+            PaddleInput::Target(direction * (target - ARENA_HEIGHT * 0.5))
+        }
+        // only the vertical part of 2D inputs counts
+        PaddleInput::Stick([_, y]) => arc_input(PaddleInput::Axis(y), arc),
+        PaddleInput::Point([_, y]) => arc_input(PaddleInput::Target(y), arc),
+    }
+}
+
+/// Position of the paddle along its edge: y for the left/right paddles, x for the top/bottom ones
+/// and the distance from the middle of its arc for ring paddles.
+fn position_along_edge(paddle: &Paddle, paddle_transform: &Transform) -> f32 {
+    if let Some(arc) = paddle.arc {
+        let translation = paddle_transform.translation();
+
+        arc.offset([translation.x, translation.y])
+    } else if paddle.side.is_horizontal() {
+        paddle_transform.translation().x
+    } else {
+        paddle_transform.translation().y
+    }
+}
+
+fn set_position_along_edge(paddle: &Paddle, paddle_transform: &mut Transform, position: f32) {
+    if let Some(arc) = paddle.arc {
+        let ([x, y], angle) = arc.position(position);
+
+        paddle_transform.set_translation_x(x);
+        paddle_transform.set_translation_y(y);
+        paddle_transform.set_rotation_2d(angle); // keeps facing the arena's center
+    } else if paddle.side.is_horizontal() {
+        paddle_transform.set_translation_x(position);
+    } else {
+        paddle_transform.set_translation_y(position);
+    }
+}
+
+/// Keeps the whole paddle inside the arena (in front of its goal, for ring paddles).
+fn clamp_to_edge(paddle: &Paddle, position: f32) -> f32 {
+    if let Some(arc) = paddle.arc {
+        let max_offset = arc.max_offset(paddle.height);
+
+        position.clamp(-max_offset, max_offset)
+    } else if paddle.side.is_horizontal() {
+        position.clamp(0.0 + paddle.width * 0.5, ARENA_WIDTH - paddle.width * 0.5)
+    } else {
+        position.clamp(
+            0.0 + paddle.height * 0.5,
+            ARENA_HEIGHT - paddle.height * 0.5,
+        )
+    }
+}
+
+/// Range of a mallet's position across its edge: its own half of the arena, the whole mallet
+/// inside it.
+fn mallet_range(side: Side, radius: f32) -> (f32, f32) {
+    let length = [ARENA_WIDTH, ARENA_HEIGHT][side.axis()];
+
+    if side.outwards() < 0.0 {
+        (radius, length * 0.5 - radius)
+    } else {
+        (length * 0.5 + radius, length - radius)
+    }
+}
+
+/// Accelerates the paddle's velocity towards the velocity requested by the input. Releasing or
+/// reversing the input slows the paddle down using the deceleration instead.
+fn step_velocity(velocity: f32, mv_amount: f32, config: &PaddleConfig, delta_seconds: f32) -> f32 {
+    let target_velocity = mv_amount.clamp(-1.0, 1.0) * config.max_speed;
+    let speeding_up = target_velocity.abs() > velocity.abs() && target_velocity * velocity >= 0.0;
+
+    let rate = if speeding_up {
+        config.acceleration
+    } else {
+        config.deceleration
+    };
+
+    move_towards(velocity, target_velocity, rate * delta_seconds)
+}
+
 // implementation of System interface for PaddleSystem
 impl<'s> System<'s> for PaddleSystem {
     type SystemData = (
         WriteStorage<'s, Transform>, // mutates the transforms
-        ReadStorage<'s, Paddle>,
-        Read<'s, InputHandler<StringBindings>>,
+        WriteStorage<'s, Paddle>,    // mutates the velocities
+        ReadStorage<'s, Ball>,       // followed by the CPU paddles
+        Read<'s, PaddleInputs>,
+        ReadExpect<'s, ControlsConfig>,
+        ReadExpect<'s, GameplayConfig>,
+        Read<'s, Time>,
+        Option<Read<'s, PowerUps>>,     // reversed controls
+        Option<Read<'s, ArenaPhysics>>, // predicted by the CPU paddles
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut transform_storage, paddle_storage, input) = data;
+        let (
+            mut transform_storage,
+            mut paddle_storage,
+            ball_storage,
+            paddle_inputs,
+            controls_config,
+            gameplay_config,
+            time,
+            power_ups,
+            physics,
+        ) = data;
+
+        let delta_seconds = time.delta_seconds();
 
-        for (paddle, transform) in (&paddle_storage, &mut transform_storage).join() {
-            // axis_value returns the axis input value or None
-            let captured_input = match paddle.side {
-                Side::Left => input.axis_value("left_paddle"),
-                Side::Right => input.axis_value("right_paddle"),
+        let balls = (&ball_storage, &transform_storage)
+            .join()
+            .map(|(ball, transform)| {
+                let position = [transform.translation().x, transform.translation().y];
+
+                (position, ball.velocity)
+            })
+            .collect::<Vec<_>>();
+
+        for (paddle, transform) in (&mut paddle_storage, &mut transform_storage).join() {
+            let position = [transform.translation().x, transform.translation().y];
+
+            let input = match (paddle.driver, paddle.arc) {
+                (PaddleDriver::Player(player), None) => paddle_inputs.get(player),
+                (PaddleDriver::Player(player), Some(arc)) => {
+                    arc_input(paddle_inputs.get(player), &arc)
+                }
+                (PaddleDriver::Cpu, _) => {
+                    cpu_paddle_input(paddle, position, &balls, physics.as_deref())
+                }
             };
 
-            if let Some(mv_amount) = captured_input {
-                self.update_position(transform, mv_amount);
-            }
+            let reversed = power_ups.as_ref().map_or(false, |power_ups| {
+                power_ups.has_effect(PowerUpKind::ReverseControls, paddle.side)
+            });
+
+            let input = if reversed {
+                reverse_input(input, paddle, transform)
+            } else {
+                input
+            };
+
+            // assistance handicap: nudged towards where the ball will reach the paddle
+            let input = if paddle.handicap.assist > 0.0 {
+                let nudge = cpu_paddle_input(paddle, position, &balls, physics.as_deref());
+
+                assist_input(input, nudge, paddle.handicap.assist)
+            } else {
+                input
+            };
+
+            move_paddle(
+                paddle,
+                transform,
+                input,
+                &controls_config,
+                &gameplay_config,
+                delta_seconds,
+            );
         }
     }
 }
@@ -57,58 +398,275 @@ impl<'s> System<'s> for PaddleSystem {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::entities::Mallet;
 
     #[test]
     fn should_update_paddle_movement_with_bottom_clamping() {
         // arrange
         let paddle_system = PaddleSystem {};
+        let mut paddle = Paddle::new(Side::Left);
         let mut paddle_transform = Transform::default();
         let paddle_y = 5.0;
-        let mv_amount = 2.0;
-        let expected_y = 8.0; // 5.0 + 1.2 * 2.0 = 7.4, which is less than MIN == 8.0 => so, bottom clamped!
+        let mv_amount = -1.0;
+        let expected_y = 8.0; // 5.0 - 75.0 * 0.5 = -32.5, which is less than MIN == 8.0 => so, bottom clamped!
 
+        paddle.velocity = -75.0; // already at max speed
         paddle_transform.set_translation_xyz(1.0, paddle_y, 0.0);
 
         // act
-        paddle_system.update_position(&mut paddle_transform, mv_amount);
+        paddle_system.update_position(
+            &mut paddle,
+            &mut paddle_transform,
+            mv_amount,
+            &PaddleConfig::default(),
+            0.5,
+        );
 
         // assert
         assert_eq!(expected_y, paddle_transform.translation().y);
+        assert_eq!(0.0, paddle.velocity);
     }
 
     #[test]
     fn should_update_paddle_movement_with_top_clamping() {
         // arrange
         let paddle_system = PaddleSystem {};
+        let mut paddle = Paddle::new(Side::Left);
         let mut paddle_transform = Transform::default();
         let paddle_y = 94.0;
-        let mv_amount = 2.0;
-        let expected_y = 92.0; // 94.0 + 1.2 * 2.0 = 96.4, which is more than MAX == 92.0 => so, top clamped!
+        let mv_amount = 1.0;
+        let expected_y = 92.0; // 94.0 + 75.0 * 0.5 = 131.5, which is more than MAX == 92.0 => so, top clamped!
 
+        paddle.velocity = 75.0;
         paddle_transform.set_translation_xyz(1.0, paddle_y, 0.0);
 
         // act
-        paddle_system.update_position(&mut paddle_transform, mv_amount);
+        paddle_system.update_position(
+            &mut paddle,
+            &mut paddle_transform,
+            mv_amount,
+            &PaddleConfig::default(),
+            0.5,
+        );
 
         // assert
         assert_eq!(expected_y, paddle_transform.translation().y);
+        assert_eq!(0.0, paddle.velocity);
     }
 
     #[test]
     fn should_update_paddle_movement_without_clamping() {
         // arrange
         let paddle_system = PaddleSystem {};
+        let mut paddle = Paddle::new(Side::Left);
         let mut paddle_transform = Transform::default();
         let paddle_y = 40.0;
-        let mv_amount = 2.0;
-        let expected_y = 42.4; // 40.0 + 1.2 * 2.0 = 42.4 => 8.0 < 42.4 < 92.0 : no clamping!
+        let mv_amount = 1.0;
+        let expected_y = 77.5; // 40.0 + 75.0 * 0.5 = 77.5 => 8.0 < 77.5 < 92.0 : no clamping!
+
+        paddle.velocity = 75.0;
+        paddle_transform.set_translation_xyz(1.0, paddle_y, 0.0);
+
+        // act
+        paddle_system.update_position(
+            &mut paddle,
+            &mut paddle_transform,
+            mv_amount,
+            &PaddleConfig::default(),
+            0.5,
+        );
+
+        // assert
+        assert_eq!(expected_y, paddle_transform.translation().y);
+        assert_eq!(75.0, paddle.velocity);
+    }
+
+    #[test]
+    fn should_accelerate_paddle_from_rest() {
+        // arrange
+        let config = PaddleConfig::default();
+
+        // act
+        let velocity = step_velocity(0.0, 1.0, &config, 0.05);
+
+        // assert
+        assert_eq!(30.0, velocity); // 600.0 * 0.05
+    }
+
+    #[test]
+    fn should_decelerate_paddle_when_input_is_released() {
+        // arrange
+        let config = PaddleConfig::default();
+
+        // act
+        let velocity = step_velocity(75.0, 0.0, &config, 0.05);
+
+        // assert
+        assert_eq!(35.0, velocity); // 75.0 - 800.0 * 0.05
+    }
+
+    #[test]
+    fn should_move_paddle_independently_from_framerate() {
+        // arrange
+        let paddle_system = PaddleSystem {};
+        let config = PaddleConfig::default();
+        let simulate_one_second = |fps: u32| {
+            let mut paddle = Paddle::new(Side::Left);
+            let mut paddle_transform = Transform::default();
+            paddle_transform.set_translation_xyz(1.0, 10.0, 0.0);
+
+            for _ in 0..fps {
+                paddle_system.update_position(
+                    &mut paddle,
+                    &mut paddle_transform,
+                    1.0,
+                    &config,
+                    1.0 / fps as f32,
+                );
+            }
+
+            (paddle_transform.translation().y, paddle.velocity)
+        };
+
+        // act
+        let (y_at_30_fps, velocity_at_30_fps) = simulate_one_second(30);
+        let (y_at_144_fps, velocity_at_144_fps) = simulate_one_second(144);
+
+        // assert
+        assert!((y_at_30_fps - y_at_144_fps).abs() < 0.1);
+        assert_eq!(velocity_at_30_fps, velocity_at_144_fps);
+    }
+
+    #[test]
+    fn should_follow_target_with_top_clamping() {
+        // arrange
+        let paddle_system = PaddleSystem {};
+        let mut paddle = Paddle::new(Side::Left);
+        let mut paddle_transform = Transform::default();
+        let paddle_y = 90.0;
+        let target_y = 99.0; // cursor near the top edge
+        let max_step = 5.0;
+        let expected_y = 92.0; // 90.0 + 5.0 = 95.0, which is more than MAX == 92.0 => top clamped!
 
         paddle_transform.set_translation_xyz(1.0, paddle_y, 0.0);
 
         // act
-        paddle_system.update_position(&mut paddle_transform, mv_amount);
+        paddle_system.follow_target(&mut paddle, &mut paddle_transform, target_y, max_step, 0.1);
 
         // assert
         assert_eq!(expected_y, paddle_transform.translation().y);
     }
+
+    #[test]
+    fn should_move_ring_paddle_along_its_arc() {
+        // arrange - ring paddle in the middle of the left goal
+        let paddle_system = PaddleSystem {};
+        let arc = RingArc {
+            seat: 0,
+            center: std::f32::consts::PI,
+            radius: 44.0,
+            half_angle: 0.5,
+        };
+        let mut paddle = Paddle {
+            arc: Some(arc),
+            ..Paddle::new(Side::Left)
+        };
+        let mut paddle_transform = Transform::default();
+        let max_offset = 14.0; // 0.5 * 44.0 - 16.0 * 0.5
+
+        paddle.velocity = 75.0;
+        paddle_transform.set_translation_xyz(6.0, 50.0, 0.0);
+
+        // act
+        paddle_system.update_position(
+            &mut paddle,
+            &mut paddle_transform,
+            1.0,
+            &PaddleConfig::default(),
+            0.5,
+        );
+
+        // assert - clamped counterclockwise (downwards), still on the arc
+        let translation = paddle_transform.translation();
+        let distance = (translation.x - 50.0).hypot(translation.y - 50.0);
+        assert!((arc.offset([translation.x, translation.y]) - max_offset).abs() < 0.001);
+        assert!(translation.y < 50.0);
+        assert!((distance - 44.0).abs() < 0.001);
+        assert_eq!(0.0, paddle.velocity);
+    }
+
+    #[test]
+    fn should_keep_mallet_within_its_half() {
+        // arrange - left mallet close to the middle line, pushed forward and up
+        let mut paddle = Paddle::mallet(Side::Left, 5.0);
+        let mut paddle_transform = Transform::default();
+        let controls_config = ControlsConfig::default();
+        let gameplay_config = GameplayConfig::default();
+
+        paddle_transform.set_translation_xyz(40.0, 50.0, 0.0);
+        paddle.mallet = paddle.mallet.map(|mallet| Mallet {
+            velocity: 75.0,
+            ..mallet
+        });
+
+        // act
+        move_paddle(
+            &mut paddle,
+            &mut paddle_transform,
+            PaddleInput::Stick([1.0, 1.0]),
+            &controls_config,
+            &gameplay_config,
+            0.5,
+        );
+
+        // assert - stopped at the middle line (minus its radius), moved up freely
+        assert_eq!(45.0, paddle_transform.translation().x);
+        assert_eq!(Some(0.0), paddle.mallet.map(|mallet| mallet.velocity));
+        assert!(paddle_transform.translation().y > 50.0);
+    }
+
+    #[test]
+    fn should_nudge_assisted_player_towards_cpu_input() {
+        // arrange - player barely pushing up while the ball comes further up
+        let input = PaddleInput::Axis(0.25);
+        let nudge = PaddleInput::Axis(1.0);
+        let mouse_input = PaddleInput::Target(40.0);
+
+        // act
+        let assisted = assist_input(input, nudge, 0.5);
+        let capped = assist_input(PaddleInput::Axis(1.0), nudge, 0.5);
+        let mouse_assisted = assist_input(mouse_input, nudge, 0.5);
+
+        // assert
+        assert_eq!(PaddleInput::Axis(0.75), assisted);
+        assert_eq!(PaddleInput::Axis(1.0), capped);
+        assert_eq!(mouse_input, mouse_assisted);
+    }
+
+    #[test]
+    fn should_move_horizontal_paddle_along_x() {
+        // arrange
+        let paddle_system = PaddleSystem {};
+        let mut paddle = Paddle::new(Side::Top);
+        let mut paddle_transform = Transform::default();
+        let paddle_x = 94.0;
+        let mv_amount = 1.0;
+        let expected_x = 92.0; // 94.0 + 75.0 * 0.5 = 131.5, which is more than MAX == 92.0 => right clamped!
+
+        paddle.velocity = 75.0;
+        paddle_transform.set_translation_xyz(paddle_x, 98.0, 0.0);
+
+        // act
+        paddle_system.update_position(
+            &mut paddle,
+            &mut paddle_transform,
+            mv_amount,
+            &PaddleConfig::default(),
+            0.5,
+        );
+
+        // assert
+        assert_eq!(expected_x, paddle_transform.translation().x);
+        assert_eq!(98.0, paddle_transform.translation().y);
+    }
 }
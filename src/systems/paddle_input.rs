@@ -0,0 +1,155 @@
+use amethyst::{
+    core::Transform,
+    derive::SystemDesc,
+    ecs::{Join, Read, ReadExpect, ReadStorage, System, SystemData, Write},
+    input::{InputHandler, StringBindings},
+    renderer::Camera,
+    window::ScreenDimensions,
+};
+
+use crate::{
+    controls::{
+        axis_name, controller_axis_value, cross_axis_name, cursor_arena_position,
+        ControllerAssignments, PaddleInput, PaddleInputs,
+    },
+    entities::{Paddle, PaddleDriver},
+    settings::{ControlScheme, ControlsConfig},
+};
+
+/// Reads the local devices (keyboard, controllers and mouse) into the `PaddleInputs` resource.
+#[derive(SystemDesc)]
+pub struct PaddleInputSystem;
+
+impl<'s> System<'s> for PaddleInputSystem {
+    type SystemData = (
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, Paddle>,
+        ReadStorage<'s, Camera>,
+        Read<'s, InputHandler<StringBindings>>,
+        Read<'s, ControllerAssignments>,
+        ReadExpect<'s, ControlsConfig>,
+        Option<ReadExpect<'s, ScreenDimensions>>,
+        Write<'s, PaddleInputs>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            transform_storage,
+            paddle_storage,
+            camera_storage,
+            input,
+            assignments,
+            controls_config,
+            screen_dimensions,
+            mut paddle_inputs,
+        ) = data;
+
+        // mouse cursor position in arena coordinates (uses the game camera to unproject it)
+        let cursor_position = match screen_dimensions {
+            Some(screen_dimensions) => (&camera_storage, &transform_storage)
+                .join()
+                .next()
+                .and_then(|(camera, camera_transform)| {
+                    cursor_arena_position(&input, camera, camera_transform, &screen_dimensions)
+                }),
+            None => None,
+        };
+
+        for (paddle, transform) in (&paddle_storage, &transform_storage).join() {
+            // the controls of the player driving the paddle (CPU paddles have none)
+            let player = match paddle.driver {
+                PaddleDriver::Player(player) => player,
+                PaddleDriver::Cpu => continue,
+            };
+            let player_controls = controls_config.player(player);
+
+            let horizontal = paddle.side.is_horizontal();
+
+            // air-hockey mallets follow the cursor (or are pushed) in both directions
+            if paddle.mallet.is_some() {
+                let position = [transform.translation().x, transform.translation().y];
+
+                let mallet_input = if player_controls.scheme == ControlScheme::Mouse {
+                    PaddleInput::Point(cursor_position.unwrap_or(position))
+                } else {
+                    // forward/back keys are relative to the player's goal, the controller's
+                    // stick and D-pad to the screen
+                    let keyboard_forward = cross_axis_name(player)
+                        .and_then(|name| input.axis_value(name))
+                        .unwrap_or(0.0);
+                    let keyboard_input = [
+                        -paddle.side.outwards() * keyboard_forward,
+                        input.axis_value(axis_name(player)).unwrap_or(0.0),
+                    ];
+
+                    let controller_input = assignments
+                        .controller(player)
+                        .map(|controller_id| {
+                            [
+                                controller_axis_value(
+                                    &input,
+                                    controller_id,
+                                    &player_controls,
+                                    true,
+                                ),
+                                controller_axis_value(
+                                    &input,
+                                    controller_id,
+                                    &player_controls,
+                                    false,
+                                ),
+                            ]
+                        })
+                        .unwrap_or([0.0, 0.0]);
+
+                    // on each axis, whichever device is pushed the most drives the mallet
+                    let strongest = |axis: usize| {
+                        if controller_input[axis].abs() > keyboard_input[axis].abs() {
+                            controller_input[axis]
+                        } else {
+                            keyboard_input[axis]
+                        }
+                    };
+
+                    PaddleInput::Stick([strongest(0), strongest(1)])
+                };
+
+                paddle_inputs.set(player, mallet_input);
+                continue;
+            }
+
+            if player_controls.scheme == ControlScheme::Mouse {
+                // horizontal paddles follow the cursor's x, the others its y
+                let axis = if horizontal { 0 } else { 1 };
+
+                // the paddle stays put while the cursor is outside the window
+                let target = cursor_position
+                    .map(|position| position[axis])
+                    .unwrap_or_else(|| transform.translation()[axis]);
+
+                paddle_inputs.set(player, PaddleInput::Target(target));
+                continue;
+            }
+
+            // axis_value returns the axis input value or None
+            let keyboard_input = input.axis_value(axis_name(player)).unwrap_or(0.0);
+
+            // analog input from the player's controller (if one is plugged in)
+            let controller_input = assignments
+                .controller(player)
+                .map(|controller_id| {
+                    controller_axis_value(&input, controller_id, &player_controls, horizontal)
+                })
+                .unwrap_or(0.0);
+
+            // whichever device is pushed the most drives the paddle
+            let mv_amount = if controller_input.abs() > keyboard_input.abs() {
+                controller_input
+            } else {
+                keyboard_input
+            };
+
+            paddle_inputs.set(player, PaddleInput::Axis(mv_amount));
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use amethyst::{
+    core::Transform,
+    derive::SystemDesc,
+    ecs::{Join, ReadStorage, System, SystemData, WriteStorage},
+};
+
+use crate::entities::{Ball, Portal};
+
+/// Sends the balls going into a portal out of its twin, keeping their velocity.
+#[derive(SystemDesc)]
+pub struct PortalSystem;
+
+impl<'s> System<'s> for PortalSystem {
+    type SystemData = (
+        ReadStorage<'s, Portal>,
+        ReadStorage<'s, Ball>,
+        WriteStorage<'s, Transform>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (portal_storage, ball_storage, mut transform_storage) = data;
+
+        let portals = (&portal_storage, &transform_storage)
+            .join()
+            .map(|(portal, transform)| {
+                let position = [transform.translation().x, transform.translation().y];
+
+                (position, portal.radius, portal.exit)
+            })
+            .collect::<Vec<_>>();
+
+        for (ball, transform) in (&ball_storage, &mut transform_storage).join() {
+            let ball_position = [transform.translation().x, transform.translation().y];
+
+            let exit = portals
+                .iter()
+                .find(|(position, radius, _)| {
+                    is_entering(ball_position, ball.velocity, *position, *radius)
+                })
+                .map(|(_, _, exit)| *exit);
+
+            if let Some([x, y]) = exit {
+                transform.set_translation_x(x);
+                transform.set_translation_y(y);
+            }
+        }
+    }
+}
+
+/// Whether the ball is inside the portal and heading towards its center. Balls coming out of a
+/// portal (at its center, then moving away from it) don't go back in.
+fn is_entering(
+    ball_position: [f32; 2],
+    ball_velocity: [f32; 2],
+    portal_position: [f32; 2],
+    portal_radius: f32,
+) -> bool {
+    let to_center = [
+        portal_position[0] - ball_position[0],
+        portal_position[1] - ball_position[1],
+    ];
+    let distance_squared = to_center[0] * to_center[0] + to_center[1] * to_center[1];
+    let approach = to_center[0] * ball_velocity[0] + to_center[1] * ball_velocity[1];
+
+    distance_squared <= portal_radius * portal_radius && approach > 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_not_send_ball_back_through_exit_portal() {
+        // arrange
+        let portal_position = [80.0, 20.0];
+        let velocity = [75.0, 50.0];
+
+        // act
+        let entering = is_entering([78.0, 19.0], velocity, portal_position, 4.0);
+        let at_exit = is_entering(portal_position, velocity, portal_position, 4.0);
+        let leaving = is_entering([81.0, 21.0], velocity, portal_position, 4.0);
+
+        // assert
+        assert_eq!(true, entering);
+        assert_eq!(false, at_exit);
+        assert_eq!(false, leaving);
+    }
+}
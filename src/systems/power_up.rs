@@ -0,0 +1,351 @@
+use amethyst::{
+    core::{math::Vector3, Hidden, Time, Transform},
+    derive::SystemDesc,
+    ecs::{Entities, Join, Read, ReadExpect, System, SystemData, Write, WriteStorage},
+    renderer::{palette::Srgba, resources::Tint, SpriteRender},
+    ui::{UiImage, UiText},
+};
+use rand::Rng;
+
+use crate::{
+    entities::{
+        pick_rule, serve_ball, spawn_pickup, Ball, Paddle, PowerUp, PowerUpKind, PowerUps,
+        Scoreboard, Side,
+    },
+    rng::{GameRng, RngStream},
+    settings::{
+        GameMode, GameplayConfig, ARENA_HEIGHT, ARENA_WIDTH, BALL_VELOCITY_X, BALL_VELOCITY_Y,
+        PADDLE_HEIGHT,
+    },
+};
+
+/// Pickups are spawned away from the goals, in the middle of the arena.
+const SPAWN_MARGIN: f32 = 20.0;
+
+/// Thickness of the shield walls.
+const SHIELD_THICKNESS: f32 = 1.0;
+
+/// Spawns the power-up pickups, hands them to the players whose balls claim them and applies
+/// the active effects: paddle lengths, shields, sticky paddles and the HUD. The ball speed and
+/// reversed controls are applied by the BallSystem and the PaddleSystem. Does nothing in
+/// matches without power-ups.
+#[derive(SystemDesc)]
+pub struct PowerUpSystem;
+
+impl<'s> System<'s> for PowerUpSystem {
+    type SystemData = (
+        Entities<'s>,
+        WriteStorage<'s, PowerUp>,
+        WriteStorage<'s, Ball>,
+        WriteStorage<'s, Paddle>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, SpriteRender>,
+        WriteStorage<'s, Tint>,
+        WriteStorage<'s, UiText>,
+        WriteStorage<'s, UiImage>,
+        WriteStorage<'s, Hidden>,
+        Option<Write<'s, PowerUps>>,
+        Write<'s, GameRng>,
+        ReadExpect<'s, GameplayConfig>,
+        Read<'s, Scoreboard>,
+        Read<'s, GameMode>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut power_up_storage,
+            mut ball_storage,
+            mut paddle_storage,
+            mut transform_storage,
+            mut sprite_render_storage,
+            mut tint_storage,
+            mut ui_text_storage,
+            mut ui_image_storage,
+            mut hidden_storage,
+            power_ups,
+            mut game_rng,
+            gameplay_config,
+            scoreboard,
+            mode,
+            time,
+        ) = data;
+
+        let mut power_ups = match power_ups {
+            Some(power_ups) => power_ups,
+            None => return,
+        };
+
+        let config = &gameplay_config.power_ups;
+        let delta_seconds = time.delta_seconds();
+        let goals = scoreboard.goals(*mode);
+        let rng = game_rng.stream(RngStream::PowerUps);
+
+        // spawns a new pickup once in a while
+        power_ups.spawn_timer += delta_seconds;
+
+        let pickup_count = (&power_up_storage).join().count();
+        let total_weight = config.rules.iter().map(|rule| rule.weight).sum::<u32>();
+
+        // in breakout matches the pickups are dropped by the bricks instead
+        if *mode != GameMode::Breakout
+            && power_ups.spawn_timer >= config.spawn_interval
+            && pickup_count < config.max_pickups
+            && total_weight > 0
+            && !scoreboard.is_match_over(*mode)
+        {
+            power_ups.spawn_timer = 0.0;
+
+            if let Some(rule) = pick_rule(config, rng.gen_range(0, total_weight)) {
+                let position = [
+                    rng.gen_range(SPAWN_MARGIN, ARENA_WIDTH - SPAWN_MARGIN),
+                    rng.gen_range(SPAWN_MARGIN, ARENA_HEIGHT - SPAWN_MARGIN),
+                ];
+
+                spawn_pickup(
+                    &entities,
+                    (
+                        &mut power_up_storage,
+                        &mut transform_storage,
+                        &mut tint_storage,
+                        &mut sprite_render_storage,
+                    ),
+                    rule.kind,
+                    position,
+                    power_ups.sprite_sheet_handle(),
+                );
+            }
+        }
+
+        // pickups are claimed by the last player to hit a ball going through them
+        let balls = (&ball_storage, &transform_storage)
+            .join()
+            .filter_map(|(ball, transform)| {
+                let position = [transform.translation().x, transform.translation().y];
+
+                ball.last_hit_by.map(|side| (position, ball.radius, side))
+            })
+            .collect::<Vec<_>>();
+
+        let mut claimed = Vec::new();
+
+        for (entity, pickup, transform) in (&entities, &power_up_storage, &transform_storage).join()
+        {
+            let position = [transform.translation().x, transform.translation().y];
+
+            let claimer = balls.iter().find(|(ball_position, ball_radius, _)| {
+                let distance_x = ball_position[0] - position[0];
+                let distance_y = ball_position[1] - position[1];
+                let touch_distance = ball_radius + pickup.radius;
+
+                distance_x * distance_x + distance_y * distance_y <= touch_distance * touch_distance
+            });
+
+            if let Some((_, _, side)) = claimer {
+                claimed.push((entity, pickup.kind, *side));
+            }
+        }
+
+        for (entity, kind, side) in claimed {
+            entities
+                .delete(entity)
+                .expect("power-up entity should be alive");
+
+            if kind == PowerUpKind::MultiBall {
+                let velocity = [
+                    BALL_VELOCITY_X * random_sign(rng),
+                    BALL_VELOCITY_Y * random_sign(rng),
+                ];
+
+                serve_ball(
+                    &entities,
+                    (
+                        &mut ball_storage,
+                        &mut transform_storage,
+                        &mut sprite_render_storage,
+                    ),
+                    velocity,
+                    power_ups.sprite_sheet_handle(),
+                );
+                continue;
+            }
+
+            if let Some(rule) = config.rules.iter().find(|rule| rule.kind == kind) {
+                if kind.targets_opponents() {
+                    for opponent in goals.iter().filter(|goal| **goal != side) {
+                        power_ups.add_effect(*opponent, rule);
+                    }
+                } else {
+                    power_ups.add_effect(side, rule);
+                }
+            }
+        }
+
+        power_ups.update_effects(delta_seconds);
+
+        // enlarged/shrunk paddles
+        for (paddle, transform) in (&mut paddle_storage, &mut transform_storage).join() {
+            let factor = power_ups.paddle_length_factor(paddle.side);
+
+            paddle.set_length(paddle.base_length() * factor);
+
+            // the sprite's height is the paddle's length, even when rotated
+            transform.set_scale(Vector3::new(
+                1.0,
+                paddle.handicap.paddle_length * factor,
+                1.0,
+            ));
+        }
+
+        // shield walls come and go with the effect
+        for side in mode.sides() {
+            let shielded = power_ups.has_effect(PowerUpKind::ShieldWall, *side);
+
+            match (shielded, power_ups.shields.contains_key(side)) {
+                (true, false) => {
+                    let [red, green, blue, _] = PowerUpKind::ShieldWall.color();
+
+                    let mut shield_builder = entities
+                        .build_entity()
+                        .with(shield_transform(*side), &mut transform_storage)
+                        .with(Tint(Srgba::new(red, green, blue, 0.5)), &mut tint_storage);
+
+                    // a paddle stretched along the whole edge
+                    if let Some(handle) = power_ups.sprite_sheet_handle() {
+                        shield_builder = shield_builder
+                            .with(SpriteRender::new(handle, 0), &mut sprite_render_storage);
+                    }
+
+                    let shield = shield_builder.build();
+                    power_ups.shields.insert(*side, shield);
+                }
+                (false, true) => {
+                    if let Some(shield) = power_ups.shields.remove(side) {
+                        entities
+                            .delete(shield)
+                            .expect("shield entity should be alive");
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // balls held by sticky paddles follow them until released
+        power_ups
+            .stuck_balls
+            .retain(|stuck| entities.is_alive(stuck.ball) && entities.is_alive(stuck.paddle));
+
+        for stuck in power_ups.stuck_balls.iter_mut() {
+            stuck.remaining -= delta_seconds;
+
+            let held_position = match (
+                paddle_storage.get(stuck.paddle),
+                transform_storage.get(stuck.paddle),
+                ball_storage.get(stuck.ball),
+            ) {
+                (Some(paddle), Some(paddle_transform), Some(ball)) => {
+                    let side = paddle.side;
+                    let axis = side.axis();
+                    let thickness = if side.is_horizontal() {
+                        paddle.height
+                    } else {
+                        paddle.width
+                    };
+
+                    let mut position = [
+                        paddle_transform.translation().x,
+                        paddle_transform.translation().y,
+                    ];
+
+                    // in front of the paddle, towards the middle of the arena
+                    position[axis] -= side.outwards() * (thickness * 0.5 + ball.radius);
+                    position[1 - axis] += stuck.offset;
+
+                    Some(position)
+                }
+                _ => None,
+            };
+
+            if let (Some([x, y]), Some(transform)) =
+                (held_position, transform_storage.get_mut(stuck.ball))
+            {
+                transform.set_translation_x(x);
+                transform.set_translation_y(y);
+            }
+        }
+
+        power_ups.stuck_balls.retain(|stuck| stuck.remaining > 0.0);
+
+        // HUD: the icons of the active effects first, the other slots hidden
+        for (side, slots) in power_ups.hud.iter() {
+            let effects = power_ups.player_effects(*side);
+
+            for (index, slot) in slots.iter().enumerate() {
+                let effect = effects.get(index);
+
+                if let Some(text) = ui_text_storage.get_mut(slot.stacks) {
+                    text.text = match effect {
+                        Some(effect) if effect.stacks > 1 => format!("x{}", effect.stacks),
+                        _ => String::new(),
+                    };
+                }
+
+                let effect = match effect {
+                    Some(effect) => effect,
+                    None => {
+                        hidden_storage
+                            .insert(slot.icon, Hidden)
+                            .expect("HUD icon entity should be alive");
+                        continue;
+                    }
+                };
+
+                if let Some(UiImage::Sprite(sprite_render)) = ui_image_storage.get_mut(slot.icon) {
+                    sprite_render.sprite_number = effect.kind.icon();
+                }
+
+                let [red, green, blue, alpha] = effect.kind.color();
+                tint_storage
+                    .insert(slot.icon, Tint(Srgba::new(red, green, blue, alpha)))
+                    .expect("HUD icon entity should be alive");
+                hidden_storage.remove(slot.icon);
+            }
+        }
+    }
+}
+
+/// Transform of a shield wall covering the whole goal of the given side.
+fn shield_transform(side: Side) -> Transform {
+    let mut transform = Transform::default();
+
+    match side {
+        Side::Left => transform.set_translation_xyz(SHIELD_THICKNESS, ARENA_HEIGHT * 0.5, 0.0),
+        Side::Right => {
+            transform.set_translation_xyz(ARENA_WIDTH - SHIELD_THICKNESS, ARENA_HEIGHT * 0.5, 0.0)
+        }
+        Side::Top => {
+            transform.set_translation_xyz(ARENA_WIDTH * 0.5, ARENA_HEIGHT - SHIELD_THICKNESS, 0.0)
+        }
+        Side::Bottom => transform.set_translation_xyz(ARENA_WIDTH * 0.5, SHIELD_THICKNESS, 0.0),
+    };
+
+    let edge_length = if side.is_horizontal() {
+        transform.set_rotation_2d(std::f32::consts::FRAC_PI_2);
+        ARENA_WIDTH
+    } else {
+        ARENA_HEIGHT
+    };
+
+    transform.set_scale(Vector3::new(0.25, edge_length / PADDLE_HEIGHT, 1.0));
+
+    transform
+}
+
+fn random_sign(rng: &mut impl Rng) -> f32 {
+    if rng.gen() {
+        1.0
+    } else {
+        -1.0
+    }
+}
@@ -0,0 +1,207 @@
+use amethyst::{
+    core::Transform,
+    derive::SystemDesc,
+    ecs::{Entities, Join, ReadExpect, ReadStorage, System, SystemData, Write, WriteStorage},
+    ui::UiText,
+};
+
+use crate::{
+    entities::{zone_points, Ball, PracticeSession, ShotResult},
+    settings::{GameplayConfig, ARENA_WIDTH},
+};
+
+/// Ends the shots of practice sessions: a ball going into the player's goal is a miss and a
+/// ball returned to the wall is scored by the target zone it reaches. The ball then leaves the
+/// arena and the Practice state fires the next shot. Does nothing outside of practice sessions.
+#[derive(SystemDesc)]
+pub struct PracticeSystem;
+
+impl<'s> System<'s> for PracticeSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Ball>,
+        ReadStorage<'s, Transform>,
+        WriteStorage<'s, UiText>,
+        Option<Write<'s, PracticeSession>>,
+        ReadExpect<'s, GameplayConfig>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, ball_storage, transform_storage, mut ui_text_storage, session, config) =
+            data;
+
+        let mut session = match session {
+            Some(session) => session,
+            None => return,
+        };
+
+        let zones = &config.practice.target_zones;
+
+        for (entity, ball, transform) in (&entities, &ball_storage, &transform_storage).join() {
+            let [ball_x, ball_y] = [transform.translation().x, transform.translation().y];
+
+            let result = if ball_x <= ball.radius {
+                ShotResult::Missed
+            } else if ball_x >= ARENA_WIDTH - ball.radius && ball.last_hit_by.is_some() {
+                match zone_points(zones, ball_y) {
+                    Some(points) => ShotResult::OnTarget(points),
+                    None => ShotResult::Returned,
+                }
+            } else {
+                continue;
+            };
+
+            session.record(result);
+            entities
+                .delete(entity)
+                .expect("ball entity should be alive");
+        }
+
+        if let Some(text) = session.hud.and_then(|hud| ui_text_storage.get_mut(hud)) {
+            text.text = session.hud_text();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amethyst::ecs::{Builder, Entity, RunNow, World, WorldExt};
+
+    use crate::{
+        entities::Side,
+        settings::{Drill, TargetZone},
+    };
+
+    const BALL_RADIUS: f32 = 2.0;
+
+    fn practice_world() -> World {
+        let mut world = World::new();
+
+        world.register::<Ball>();
+        world.register::<Transform>();
+        world.register::<UiText>();
+
+        let mut gameplay_config = GameplayConfig::default();
+        gameplay_config.practice.target_zones = vec![TargetZone {
+            center: 50.0,
+            size: 20.0,
+            points: 3,
+        }];
+        world.insert(gameplay_config);
+
+        let mut session = PracticeSession::new(Drill {
+            name: "random".to_string(),
+            min_angle: 0.0,
+            max_angle: 60.0,
+            min_speed: 60.0,
+            max_speed: 150.0,
+            shots: 2,
+        });
+        session.launch([-75.0, 50.0]);
+        world.insert(session);
+
+        world
+    }
+
+    fn add_ball(world: &mut World, position: [f32; 2], last_hit_by: Option<Side>) -> Entity {
+        let mut ball = Ball::new([-75.0, 50.0], BALL_RADIUS);
+        ball.last_hit_by = last_hit_by;
+
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(position[0], position[1], 0.0);
+
+        world.create_entity().with(ball).with(transform).build()
+    }
+
+    fn run_practice_system(world: &mut World) {
+        PracticeSystem.run_now(world);
+        world.maintain();
+    }
+
+    #[test]
+    fn should_score_return_reaching_target_zone() {
+        // arrange
+        let mut world = practice_world();
+        let ball = add_ball(&mut world, [ARENA_WIDTH - 1.0, 55.0], Some(Side::Left));
+
+        // act
+        run_practice_system(&mut world);
+
+        // assert
+        let session = world.read_resource::<PracticeSession>();
+
+        assert_eq!(false, world.is_alive(ball));
+        assert_eq!(false, session.is_shot_in_play());
+        assert_eq!(3, session.points());
+        assert_eq!(100, session.accuracy());
+    }
+
+    #[test]
+    fn should_not_score_return_outside_target_zones() {
+        // arrange
+        let mut world = practice_world();
+        let ball = add_ball(&mut world, [ARENA_WIDTH - 1.0, 10.0], Some(Side::Left));
+
+        // act
+        run_practice_system(&mut world);
+
+        // assert
+        let session = world.read_resource::<PracticeSession>();
+
+        assert_eq!(false, world.is_alive(ball));
+        assert_eq!(false, session.is_shot_in_play());
+        assert_eq!(0, session.points());
+        assert_eq!(0, session.accuracy());
+    }
+
+    #[test]
+    fn should_end_shot_going_into_players_goal_as_missed() {
+        // arrange - even at the height of a target zone
+        let mut world = practice_world();
+        let ball = add_ball(&mut world, [1.0, 50.0], None);
+
+        // act
+        run_practice_system(&mut world);
+
+        // assert
+        let session = world.read_resource::<PracticeSession>();
+
+        assert_eq!(false, world.is_alive(ball));
+        assert_eq!(false, session.is_shot_in_play());
+        assert_eq!(0, session.points());
+    }
+
+    #[test]
+    fn should_keep_shot_in_play_until_player_returns_it() {
+        // arrange - a ball the player never hit isn't a return, even at the wall
+        let mut world = practice_world();
+        let launched = add_ball(&mut world, [50.0, 50.0], None);
+        let unreturned = add_ball(&mut world, [ARENA_WIDTH - 1.0, 50.0], None);
+
+        // act
+        run_practice_system(&mut world);
+
+        // assert
+        assert_eq!(true, world.is_alive(launched));
+        assert_eq!(true, world.is_alive(unreturned));
+        assert_eq!(
+            true,
+            world.read_resource::<PracticeSession>().is_shot_in_play()
+        );
+    }
+
+    #[test]
+    fn should_leave_balls_alone_outside_of_practice_sessions() {
+        // arrange
+        let mut world = practice_world();
+        world.remove::<PracticeSession>();
+        let ball = add_ball(&mut world, [1.0, 50.0], None);
+
+        // act
+        run_practice_system(&mut world);
+
+        // assert
+        assert_eq!(true, world.is_alive(ball));
+    }
+}
@@ -0,0 +1,142 @@
+use amethyst::{
+    core::Transform,
+    derive::SystemDesc,
+    ecs::{Entities, Join, Read, ReadStorage, System, SystemData, Write, WriteStorage},
+    renderer::{palette::Srgba, resources::Tint},
+    ui::UiText,
+};
+use log::info;
+
+use crate::{
+    entities::{
+        boundary_bounce, ring_angle, Ball, MatchStats, MultiBall, Paddle, Ring, Scoreboard,
+        RING_CENTER, WALL_TINT,
+    },
+    settings::{ARENA_HEIGHT, ARENA_WIDTH},
+};
+
+/// Plays the round arena of ring matches: balls bounce off its boundary between the goals, and
+/// a ball going through a player's goal costs them a life. Players out of lives leave and their
+/// goal becomes a wall, until a single one is left. Does nothing in other matches.
+#[derive(SystemDesc)]
+pub struct RingSystem;
+
+impl<'s> System<'s> for RingSystem {
+    type SystemData = (
+        Entities<'s>,
+        WriteStorage<'s, Ball>,
+        WriteStorage<'s, Transform>,
+        ReadStorage<'s, Paddle>,
+        WriteStorage<'s, Tint>,
+        WriteStorage<'s, UiText>,
+        Option<Write<'s, Ring>>,
+        Write<'s, Scoreboard>, // finished once a single player is left
+        Option<Read<'s, MultiBall>>,
+        Option<Write<'s, MatchStats>>, // rallies of local matches
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut ball_storage,
+            mut transform_storage,
+            paddle_storage,
+            mut tint_storage,
+            mut ui_text_storage,
+            ring,
+            mut scoreboard,
+            multi_ball,
+            mut match_stats,
+        ) = data;
+
+        let mut ring = match ring {
+            Some(ring) => ring,
+            None => return,
+        };
+
+        let mut ball_count = (&ball_storage).join().count();
+        let mut eliminated = Vec::new();
+
+        for (entity, ball, transform) in
+            (&entities, &mut ball_storage, &mut transform_storage).join()
+        {
+            let position = [transform.translation().x, transform.translation().y];
+            let distance = (position[0] - RING_CENTER[0]).hypot(position[1] - RING_CENTER[1]);
+
+            let conceded = match ring.goal_at(ring_angle(position)) {
+                // the boundary is a wall between the goals (and along the eliminated players')
+                None => {
+                    if let Some(velocity) =
+                        boundary_bounce(position, ball.velocity, ball.radius, ring.radius())
+                    {
+                        ball.velocity = velocity;
+                    }
+                    continue;
+                }
+                // scores once the whole ball went through the goal
+                Some(seat) if distance >= ring.radius() + ball.radius => seat,
+                Some(_) => continue,
+            };
+
+            if let Some(match_stats) = match_stats.as_mut() {
+                match_stats.end_rally();
+            }
+
+            if ring.concede(conceded) {
+                info!("{} is out", ring.seats()[conceded].name);
+                eliminated.push(conceded);
+            }
+
+            // multi-ball: the ball leaves, the MultiBallSystem serves new ones
+            if multi_ball.is_some() || ball_count > 1 {
+                entities
+                    .delete(entity)
+                    .expect("ball entity should be alive");
+                ball_count -= 1;
+                continue;
+            }
+
+            // served back towards the player who conceded
+            transform.set_translation_xyz(ARENA_WIDTH / 2.0, ARENA_HEIGHT / 2.0, 0.0);
+            ball.velocity = [-ball.velocity[0], -ball.velocity[1]];
+            ball.last_hit_by = None;
+            ball.spin = 0.0;
+        }
+
+        // eliminated players' paddles leave: their goal is a wall from now on
+        for seat in eliminated.iter() {
+            for dot in ring.seats()[*seat].goal_dots.iter() {
+                let [red, green, blue, alpha] = WALL_TINT;
+
+                tint_storage
+                    .insert(*dot, Tint(Srgba::new(red, green, blue, alpha)))
+                    .expect("boundary dot entity should be alive");
+            }
+        }
+
+        for (entity, paddle) in (&entities, &paddle_storage).join() {
+            let seat = paddle.arc.map(|arc| arc.seat);
+
+            if seat.map_or(false, |seat| eliminated.contains(&seat)) {
+                entities
+                    .delete(entity)
+                    .expect("paddle entity should be alive");
+            }
+        }
+
+        // the last player standing won: no more serves
+        if ring.is_over() && !scoreboard.is_finished() {
+            scoreboard.finish();
+
+            for (entity, _) in (&entities, &ball_storage).join() {
+                entities
+                    .delete(entity)
+                    .expect("ball entity should be alive");
+            }
+        }
+
+        if let Some(text) = ring.hud.and_then(|hud| ui_text_storage.get_mut(hud)) {
+            text.text = ring.hud_text();
+        }
+    }
+}
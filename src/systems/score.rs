@@ -1,30 +1,39 @@
 use amethyst::{
-    core::Transform,
+    core::{Time, Transform},
     derive::SystemDesc,
-    ecs::{Join, SystemData, WriteStorage},
-    shred::{ReadExpect, System},
-    ui::UiText,
+    ecs::{Entities, Join, ReadExpect, ReadStorage, SystemData, WriteStorage},
+    shred::{Read, System, Write},
 };
+use log::info;
 
 use crate::{
-    entities::{Ball, ScoreText, Side},
-    settings::{ARENA_HEIGHT, ARENA_WIDTH},
+    entities::{Ball, MatchStats, MultiBall, Paddle, PowerUps, Scoreboard, Side, Squash},
+    settings::{
+        FourPlayerScoring, GameMode, GameplayConfig, Level, ARENA_HEIGHT, ARENA_WIDTH,
+        BALL_VELOCITY_X, BALL_VELOCITY_Y,
+    },
 };
 
 #[derive(SystemDesc)]
 pub struct ScoreSystem;
 
 impl ScoreSystem {
-    // Returns an Option with the paddle side that got defeated. If neither player has
-    // scored, than the Option contains None.
-    fn has_player_scored(&mut self, ball_x: f32, ball_radius: f32) -> Option<Side> {
-        if ball_x <= ball_radius {
-            Some(Side::Left)
-        } else if ball_x >= ARENA_WIDTH - ball_radius {
-            Some(Side::Right)
-        } else {
-            None
-        }
+    // Returns an Option with the side whose goal the ball went into. If neither player has
+    // scored, than the Option contains None. Only the given edges are goals.
+    fn has_player_scored(
+        &mut self,
+        ball_position: [f32; 2],
+        ball_radius: f32,
+        goals: &[Side],
+    ) -> Option<Side> {
+        let [ball_x, ball_y] = ball_position;
+
+        goals.iter().cloned().find(|goal| match goal {
+            Side::Left => ball_x <= ball_radius,
+            Side::Right => ball_x >= ARENA_WIDTH - ball_radius,
+            Side::Bottom => ball_y <= ball_radius,
+            Side::Top => ball_y >= ARENA_HEIGHT - ball_radius,
+        })
     }
 }
 
@@ -32,44 +41,194 @@ impl ScoreSystem {
 impl<'s> System<'s> for ScoreSystem {
     // data changed by the system
     type SystemData = (
+        Entities<'s>,
         WriteStorage<'s, Ball>,
         WriteStorage<'s, Transform>,
-        WriteStorage<'s, UiText>,
-        ReadExpect<'s, ScoreText>,
+        ReadStorage<'s, Paddle>,
+        Write<'s, Scoreboard>,
+        Read<'s, GameMode>,
+        ReadExpect<'s, GameplayConfig>,
+        Option<Read<'s, MultiBall>>,
+        Option<Read<'s, PowerUps>>,    // shielded goals
+        Option<Read<'s, Level>>,       // narrowed goals
+        Option<Write<'s, Squash>>,     // squash streaks
+        Option<Write<'s, MatchStats>>, // rallies and time played of local matches
+        Read<'s, Time>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut ball_storage, mut transform_storage, mut ui_text_storage, score_text) = data;
+        let (
+            entities,
+            mut ball_storage,
+            mut transform_storage,
+            paddle_storage,
+            mut scoreboard,
+            mode,
+            gameplay_config,
+            multi_ball,
+            power_ups,
+            level,
+            mut squash,
+            mut match_stats,
+            time,
+        ) = data;
+
+        if let Some(match_stats) = match_stats.as_mut() {
+            if !scoreboard.is_match_over(*mode) {
+                match_stats.duration += time.delta_seconds();
+            }
+        }
+
+        // practice and ring balls are handled by the PracticeSystem and RingSystem
+        if *mode == GameMode::Practice || *mode == GameMode::Ring {
+            return;
+        }
+
+        let goals = match power_ups.as_ref() {
+            Some(power_ups) => power_ups.open_goals(scoreboard.goals(*mode)),
+            None => scoreboard.goals(*mode),
+        };
+        let mut ball_count = (&ball_storage).join().count();
+        let mut eliminated = Vec::new();
 
         // join storages: only ball with transforms
-        for (ball, transform) in (&mut ball_storage, &mut transform_storage).join() {
-            let ball_x = transform.translation().x;
-            let ball_radius = ball.radius;
+        for (entity, ball, transform) in
+            (&entities, &mut ball_storage, &mut transform_storage).join()
+        {
+            let ball_position = [transform.translation().x, transform.translation().y];
+
+            // balls beside a narrowed goal's opening bounce off the edge instead
+            let conceded = self
+                .has_player_scored(ball_position, ball.radius, &goals)
+                .filter(|goal| {
+                    level
+                        .as_ref()
+                        .map_or(true, |level| level.is_within_goal(*goal, ball_position))
+                });
 
-            if let Some(side_score) = self.has_player_scored(ball_x, ball_radius) {
-                // score UI update
-                if side_score == Side::Left {
-                    if let Some(text) = ui_text_storage.get_mut(score_text.p2_score) {
-                        let score: i32 = text.text.parse().unwrap();
+            if let Some(conceded) = conceded {
+                if let Some(match_stats) = match_stats.as_mut() {
+                    match_stats.end_rally();
+                }
 
-                        text.text = (score + 1).to_string();
+                match (*mode, gameplay_config.four_player.scoring) {
+                    // the opponent of the defeated side gets the point
+                    (GameMode::Classic, _)
+                    | (GameMode::Doubles, _)
+                    | (GameMode::Breakout, _)
+                    | (GameMode::AirHockey, _) => scoreboard.add_point(conceded.opponent()),
+                    (GameMode::FourPlayer, FourPlayerScoring::Points) => {
+                        // nobody scores own goals or balls that weren't hit since the serve
+                        if let Some(hitter) = ball.last_hit_by.filter(|side| *side != conceded) {
+                            scoreboard.add_point(hitter);
+                        }
                     }
-                } else {
-                    if let Some(text) = ui_text_storage.get_mut(score_text.p1_score) {
-                        let score: i32 = text.text.parse().unwrap();
+                    (GameMode::Squash, _) => {
+                        if let Some(squash) = squash.as_mut() {
+                            squash.miss();
+                        }
 
-                        text.text = (score + 1).to_string();
+                        if scoreboard.lose_life(conceded) {
+                            info!("Game over");
+                            eliminated.push(conceded);
+                        }
                     }
+                    (GameMode::Practice, _) | (GameMode::Ring, _) => {} // returned above
+                    (GameMode::FourPlayer, FourPlayerScoring::Lives) => {
+                        if scoreboard.lose_life(conceded) {
+                            info!("{} is out", conceded.player_label());
+                            eliminated.push(conceded);
+                        }
+                    }
+                }
+
+                // multi-ball: the ball leaves, the MultiBallSystem serves new ones. Extra balls
+                // (e.g. from power-ups) leave as well, until a single one is left
+                if multi_ball.is_some() || ball_count > 1 {
+                    entities
+                        .delete(entity)
+                        .expect("ball entity should be alive");
+                    ball_count -= 1;
+                    continue;
                 }
 
-                // after score UI update
+                // after score update
                 let spawn_x = ARENA_WIDTH / 2.0;
                 let spawn_y = ARENA_HEIGHT / 2.0;
 
                 transform.set_translation_xyz(spawn_x, spawn_y, 0.0);
-                ball.velocity[0] *= -1.0;
+                ball.last_hit_by = None;
+                ball.spin = 0.0;
+
+                // squash: served towards the wall again, back to its starting speed
+                if *mode == GameMode::Squash {
+                    ball.velocity = [BALL_VELOCITY_X, BALL_VELOCITY_Y];
+                } else if *mode == GameMode::AirHockey {
+                    // air hockey: the puck is dropped at rest in the middle of the conceder's half
+                    let offset = conceded.outwards() * ARENA_WIDTH / 4.0;
+
+                    transform.set_translation_x(spawn_x + offset);
+                    ball.velocity = [0.0, 0.0];
+                } else {
+                    ball.velocity[conceded.axis()] *= -1.0;
+                }
+            }
+        }
+
+        // matches played for points are won by the first player to reach the points to win
+        let target_reached = gameplay_config.points_to_win.map_or(false, |target| {
+            mode.sides()
+                .iter()
+                .any(|side| scoreboard.score(*side) >= target)
+        });
+
+        if plays_for_points(*mode, gameplay_config.four_player.scoring)
+            && target_reached
+            && !scoreboard.is_finished()
+        {
+            if let Some(winner) = scoreboard.leader() {
+                info!("{} wins the match", winner.player_label());
+            }
+            scoreboard.finish();
+
+            // no more serves
+            for (entity, _) in (&entities, &ball_storage).join() {
+                entities
+                    .delete(entity)
+                    .expect("ball entity should be alive");
             }
         }
+
+        if eliminated.is_empty() {
+            return;
+        }
+
+        // eliminated players' paddles leave: their edge is a wall from now on
+        for (entity, paddle) in (&entities, &paddle_storage).join() {
+            if eliminated.contains(&paddle.side) {
+                entities
+                    .delete(entity)
+                    .expect("paddle entity should be alive");
+            }
+        }
+
+        // the last player standing won (or the squash player lost): no more serves
+        if scoreboard.is_match_over(*mode) {
+            for (entity, _) in (&entities, &ball_storage).join() {
+                entities
+                    .delete(entity)
+                    .expect("ball entity should be alive");
+            }
+        }
+    }
+}
+
+/// Whether matches of the mode are won on points (rather than lives or bricks).
+fn plays_for_points(mode: GameMode, four_player_scoring: FourPlayerScoring) -> bool {
+    match mode {
+        GameMode::Classic | GameMode::Doubles | GameMode::AirHockey => true,
+        GameMode::FourPlayer => four_player_scoring == FourPlayerScoring::Points,
+        _ => false,
     }
 }
 
@@ -77,6 +236,8 @@ impl<'s> System<'s> for ScoreSystem {
 mod tests {
     use super::*;
 
+    const CLASSIC_GOALS: &[Side] = &[Side::Left, Side::Right];
+
     #[test]
     fn should_assert_ball_has_scored() {
         // arrange
@@ -85,7 +246,8 @@ mod tests {
         let mut score_system = ScoreSystem {};
 
         // act
-        let has_scored_opt = score_system.has_player_scored(ball_x, ball_radius);
+        let has_scored_opt =
+            score_system.has_player_scored([ball_x, 50.0], ball_radius, CLASSIC_GOALS);
 
         // assert
         assert_eq!(has_scored_opt.is_none(), false); // player has scored
@@ -99,7 +261,8 @@ mod tests {
         let mut score_system = ScoreSystem {};
 
         // act
-        let has_scored_opt = score_system.has_player_scored(ball_x, ball_radius);
+        let has_scored_opt =
+            score_system.has_player_scored([ball_x, 50.0], ball_radius, CLASSIC_GOALS);
 
         // assert
         assert_eq!(has_scored_opt.is_none(), false); // player has scored
@@ -114,7 +277,8 @@ mod tests {
         let mut score_system = ScoreSystem {};
 
         // act
-        let has_scored_opt = score_system.has_player_scored(ball_x, ball_radius);
+        let has_scored_opt =
+            score_system.has_player_scored([ball_x, 50.0], ball_radius, CLASSIC_GOALS);
 
         // assert
         assert_eq!(has_scored_opt.is_none(), false); // player has scored
@@ -129,9 +293,39 @@ mod tests {
         let mut score_system = ScoreSystem {};
 
         // act
-        let has_scored_opt = score_system.has_player_scored(ball_x, ball_radius);
+        let has_scored_opt =
+            score_system.has_player_scored([ball_x, 50.0], ball_radius, CLASSIC_GOALS);
 
         // assert
         assert_eq!(has_scored_opt.is_none(), true);
     }
+
+    #[test]
+    fn should_assert_ball_has_scored_on_top_edge() {
+        // arrange
+        let ball_radius = 2.0;
+        let ball_position = [50.0, ARENA_HEIGHT - 1.0];
+        let mut score_system = ScoreSystem {};
+
+        // act
+        let has_scored_opt = score_system.has_player_scored(ball_position, ball_radius, &Side::ALL);
+
+        // assert
+        assert_eq!(Some(Side::Top), has_scored_opt);
+    }
+
+    #[test]
+    fn should_assert_ball_has_not_scored_on_wall() {
+        // arrange - the top player was eliminated: their edge is a wall
+        let ball_radius = 2.0;
+        let ball_position = [50.0, ARENA_HEIGHT - 1.0];
+        let goals = [Side::Left, Side::Right, Side::Bottom];
+        let mut score_system = ScoreSystem {};
+
+        // act
+        let has_scored_opt = score_system.has_player_scored(ball_position, ball_radius, &goals);
+
+        // assert
+        assert_eq!(None, has_scored_opt);
+    }
 }
@@ -0,0 +1,77 @@
+use amethyst::{
+    derive::SystemDesc,
+    ecs::{SystemData, WriteStorage},
+    shred::{Read, ReadExpect, System},
+    ui::UiText,
+};
+
+use crate::{
+    entities::{ScoreText, Scoreboard, Side, Squash},
+    settings::GameMode,
+};
+
+/// Mirrors the scoreboard into the score texts. The scoreboard isn't only changed by the
+/// ScoreSystem: it can also be restored from a snapshot or received from the game server, so
+/// this system runs even when the gameplay systems don't.
+#[derive(SystemDesc)]
+pub struct ScoreTextSystem;
+
+impl<'s> System<'s> for ScoreTextSystem {
+    type SystemData = (
+        WriteStorage<'s, UiText>,
+        Read<'s, Scoreboard>,
+        Read<'s, GameMode>,
+        Option<ReadExpect<'s, ScoreText>>,
+        Option<Read<'s, Squash>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut ui_text_storage, scoreboard, mode, score_text, squash) = data;
+
+        // the score texts are only created once the match starts
+        let score_text = match score_text {
+            Some(score_text) => score_text,
+            None => return,
+        };
+
+        let goals = scoreboard.goals(*mode);
+
+        for (side, score_entity) in score_text.texts.iter() {
+            if let Some(text) = ui_text_storage.get_mut(*score_entity) {
+                let score = match squash.as_ref() {
+                    Some(squash) => squash_label(&scoreboard, *side, squash),
+                    None => score_label(&scoreboard, *side, &goals),
+                };
+
+                if text.text != score {
+                    text.text = score;
+                }
+            }
+        }
+    }
+}
+
+/// Text shown for a player: their points or, when playing with lives, their lives left.
+fn score_label(scoreboard: &Scoreboard, side: Side, goals: &[Side]) -> String {
+    match scoreboard.lives(side) {
+        // e.g. breakout matches, over once the bricks are cleared
+        None if scoreboard.is_finished() && scoreboard.leader() == Some(side) => "WINS".to_string(),
+        None => scoreboard.score(side).to_string(),
+        Some(0) => "OUT".to_string(),
+        Some(_) if goals == [side] => "WINS".to_string(),
+        Some(lives) => lives.to_string(),
+    }
+}
+
+/// Text shown in squash matches: the streak, the lives left and the high score.
+fn squash_label(scoreboard: &Scoreboard, side: Side, squash: &Squash) -> String {
+    match scoreboard.lives(side) {
+        Some(0) => format!("GAME OVER  BEST {}", squash.high_score()),
+        lives => format!(
+            "{}  LIVES {}  BEST {}",
+            squash.streak(),
+            lives.unwrap_or(0),
+            squash.high_score()
+        ),
+    }
+}
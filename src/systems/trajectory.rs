@@ -0,0 +1,82 @@
+use amethyst::{
+    core::Transform,
+    derive::SystemDesc,
+    ecs::{Join, Read, ReadStorage, System, SystemData, WriteStorage},
+};
+
+use crate::{
+    entities::{
+        predict_path, ArenaPhysics, Ball, Scoreboard, Side, TrajectoryPreview, PREDICTION_STEP,
+    },
+    settings::{GameMode, ARENA_HEIGHT, ARENA_WIDTH},
+};
+
+/// Moves the dots of the trajectory preview along the predicted path of the ball, under the
+/// arena physics. Does nothing when the preview is off.
+#[derive(SystemDesc)]
+pub struct TrajectorySystem;
+
+impl<'s> System<'s> for TrajectorySystem {
+    type SystemData = (
+        ReadStorage<'s, Ball>,
+        WriteStorage<'s, Transform>,
+        Option<Read<'s, TrajectoryPreview>>,
+        Option<Read<'s, ArenaPhysics>>,
+        Read<'s, Scoreboard>,
+        Read<'s, GameMode>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (ball_storage, mut transform_storage, preview, physics, scoreboard, mode) = data;
+
+        let preview = match preview {
+            Some(preview) => preview,
+            None => return,
+        };
+
+        // the ball bounces off the top and bottom edges, unless they're goals (or out of reach,
+        // inside the round arena of ring matches)
+        let goals = scoreboard.goals(*mode);
+        let bounce_axis = if *mode == GameMode::Ring
+            || goals.contains(&Side::Top)
+            || goals.contains(&Side::Bottom)
+        {
+            None
+        } else {
+            Some(1)
+        };
+
+        let path = (&ball_storage, &transform_storage)
+            .join()
+            .next()
+            .map(|(ball, transform)| {
+                let position = [transform.translation().x, transform.translation().y];
+                let steps = (preview.seconds / PREDICTION_STEP) as usize;
+
+                predict_path(
+                    physics.as_deref(),
+                    position,
+                    ball.velocity,
+                    ball.radius,
+                    bounce_axis,
+                    steps,
+                )
+            })
+            .unwrap_or_default();
+
+        // dots spread evenly along the path, the ones left over out of sight
+        let spacing = (path.len() / preview.dots.len()).max(1);
+
+        for (index, dot) in preview.dots.iter().enumerate() {
+            let [x, y] = path
+                .get((index + 1) * spacing - 1)
+                .cloned()
+                .unwrap_or([-ARENA_WIDTH, -ARENA_HEIGHT]);
+
+            if let Some(transform) = transform_storage.get_mut(*dot) {
+                transform.set_translation_x(x);
+                transform.set_translation_y(y);
+            }
+        }
+    }
+}